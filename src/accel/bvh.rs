@@ -146,6 +146,10 @@ impl<'a, S: Intersect> Bvh<'a, S> {
         self.root.bbox
     }
 
+    pub fn shapes(&self) -> &[S] {
+        &self.shapes
+    }
+
     pub fn count_intersection_tests(&self, ray: &Ray) -> usize {
         self.root.count_intersection_tests(ray)
     }
@@ -211,6 +215,10 @@ impl<'a, S: Intersect> Node<'a, S> {
         let bbox = Aabb::from_multiple(&shapes);
         if shapes.len() <= 2 {
             Self::leaf(bbox, shapes)
+        } else if !bbox.surface().is_finite() {
+            Self::split_unbounded(bbox, shapes, |shapes| {
+                Self::surface_area_heuristic_alternate(shapes, nb_buckets, prev_axis)
+            })
         } else {
             let axis = (prev_axis + 1) % 3;
             let axis_size = bbox.p1[axis] - bbox.p0[axis];
@@ -259,6 +267,10 @@ impl<'a, S: Intersect> Node<'a, S> {
         let bbox = Aabb::from_multiple(&shapes);
         if shapes.len() <= 2 || directions.is_empty() {
             Self::leaf(bbox, shapes)
+        } else if !bbox.surface().is_finite() {
+            Self::split_unbounded(bbox, shapes, |shapes| {
+                Self::surface_area_heuristic_longest(shapes, nb_buckets, directions)
+            })
         } else {
             let (axis, axis_size) = bbox.longest_axis_of(directions);
 
@@ -311,6 +323,32 @@ impl<'a, S: Intersect> Node<'a, S> {
         }
     }
 
+    /// Separates the shapes with an unbounded bounding box, such as planes, from the others,
+    /// since the surface area heuristic can't be evaluated for them. The bounded shapes are split
+    /// further by `split`.
+    fn split_unbounded<F>(bbox: Aabb, shapes: Vec<ShapeData<'a, S>>, split: F) -> Self
+    where
+        F: Fn(Vec<ShapeData<'a, S>>) -> Self,
+    {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = shapes
+            .into_iter()
+            .partition(|shape| shape.bbox.surface().is_finite());
+
+        if bounded.is_empty() || unbounded.is_empty() {
+            let shapes = bounded.into_iter().chain(unbounded).collect();
+            return Self::leaf(bbox, shapes);
+        }
+
+        let unbounded_bbox = Aabb::from_multiple(&unbounded);
+        Self {
+            bbox,
+            node_kind: Internal {
+                left: Box::new(split(bounded)),
+                right: Box::new(Self::leaf(unbounded_bbox, unbounded)),
+            },
+        }
+    }
+
     fn space_median_split(shapes: Vec<ShapeData<'a, S>>, axis_selection: AxisSelection) -> Self {
         match axis_selection {
             Alternate(prev_axis) => Self::space_median_split_alternate(shapes, 3, prev_axis),
@@ -566,11 +604,13 @@ impl<'a, S: Intersect> Node<'a, S> {
                 match (left.bbox.intersect(ray), right.bbox.intersect(ray)) {
                     (Some(left_t), Some(right_t)) => {
                         // TODO: Optimize this if needed
-                        if left_t < right_t {
-                            left.intersect_any_where(ray, f) || right.intersect_any_where(ray, f)
+                        let (first, second) = if left_t < right_t {
+                            (left, right)
                         } else {
-                            right.intersect_any_where(ray, f) || left.intersect_any_where(ray, f)
-                        }
+                            (right, left)
+                        };
+
+                        first.intersect_any_where(ray, f) || second.intersect_any_where(ray, f)
                     }
                     (Some(_), None) => left.intersect_any_where(ray, f),
                     (None, Some(_)) => right.intersect_any_where(ray, f),
@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 use ray_tracer::brdf::Lambertian;
 use ray_tracer::camera::CameraBuilder;
-use ray_tracer::film::Rgb;
+use ray_tracer::film::{ColorSpace, Encoding, Rgb};
 use ray_tracer::light::{AreaLight, PointLight};
 use ray_tracer::material::{Emissive, Material};
 use ray_tracer::math::Transformation;
@@ -32,8 +32,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         Vector::new(0., 0., 2.),
         Vector::new(-2., 0., 0.),
     );
-    let emissive = Emissive::new(2., Rgb::white());
-    let light = AreaLight::new(rectangle, emissive);
+    let light = AreaLight::from_power(rectangle, 50., Rgb::white());
 
     let material1 = Material::Matte {
         ambient_brdf: Lambertian::new(0.15, Rgb::new(1., 0., 0.)),
@@ -64,9 +63,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     let tracer = DirectIllumination::default();
     let buffer = tracer.render_scene(&world, &camera, &sampler);
 
-    buffer
-        .to_rgba_image(1., 2.2)
-        .save("renders/area_light.png")?;
+    buffer.save_png(
+        "renders/area_light.png",
+        1.,
+        ColorSpace::Srgb,
+        &Encoding::SRGB,
+    )?;
 
     let duration = start.elapsed();
     println!("render time: {:?}", duration);
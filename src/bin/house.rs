@@ -5,7 +5,7 @@ use ray_tracer::bvh::AxisSelection::Alternate;
 use ray_tracer::bvh::SplittingHeuristic::SpaceMedianSplit;
 use ray_tracer::bvh::{SplittingConfig, Z_AXIS};
 use ray_tracer::camera::CameraBuilder;
-use ray_tracer::film::Rgb;
+use ray_tracer::film::{ColorSpace, Encoding, Rgb};
 use ray_tracer::light::PointLight;
 use ray_tracer::material::Material;
 use ray_tracer::math::Transformation;
@@ -32,7 +32,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .build()
         .ok_or("invalid camera configuration")?;
 
-    let light1 = PointLight::white(1., Point3::new(-4., -4., 4.));
+    let light1 = PointLight::white(50., Point3::new(-4., -4., 4.));
 
     let texture = ImageTexture::new("models/house_texture.jpg")?;
     let ambient_brdf = SvLambertian::new(0.35, Box::new(texture.clone()));
@@ -75,7 +75,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let duration = start.elapsed();
     println!("render time: {:?}", duration);
 
-    buffer.to_rgba_image(1., 2.2).save("renders/house.png")?;
+    buffer.save_png("renders/house.png", 1., ColorSpace::Srgb, &Encoding::SRGB)?;
 
     Ok(())
 }
@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 use ray_tracer::brdf::Lambertian;
 use ray_tracer::camera::CameraBuilder;
-use ray_tracer::film::Rgb;
+use ray_tracer::film::{ColorSpace, Encoding, Rgb};
 use ray_tracer::light::PointLight;
 use ray_tracer::material::Material;
 use ray_tracer::math::Transformation;
@@ -28,7 +28,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .build()
         .ok_or("invalid camera configuration")?;
 
-    let light1 = PointLight::white(1., Point3::new(4., -4., 0.));
+    let light1 = PointLight::from_power(1500., Rgb::white(), Point3::new(4., -4., 0.));
 
     let t1 = Transformation::scale(5., 5., 5.).then(&Transformation::translate(0., 0., -10.));
     let t2 = Transformation::scale(4., 4., 3.).then(&Transformation::translate(4., -4., -12.));
@@ -74,7 +74,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let tracer = DirectIllumination::default();
     let buffer = tracer.render_scene(&world, &camera, &sampler);
 
-    buffer.to_rgba_image(1., 2.2).save("renders/spheres.png")?;
+    buffer.save_png("renders/spheres.png", 1., ColorSpace::Srgb, &Encoding::SRGB)?;
 
     let duration = start.elapsed();
     println!("render time: {:?}", duration);
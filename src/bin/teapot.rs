@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 use ray_tracer::brdf::Lambertian;
 use ray_tracer::camera::CameraBuilder;
-use ray_tracer::film::Rgb;
+use ray_tracer::film::{ColorSpace, Encoding, Rgb};
 use ray_tracer::light::PointLight;
 use ray_tracer::material::Material;
 use ray_tracer::math::Transformation;
@@ -27,7 +27,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .build()
         .ok_or("invalid camera configuration")?;
 
-    let light1 = PointLight::white(1., Point3::new(2., 4., 5.));
+    let light1 = PointLight::white(45., Point3::new(2., 4., 5.));
     let t = Transformation::identity();
 
     let material = Material::Matte {
@@ -59,7 +59,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let duration = start.elapsed();
     println!("render time: {:?}", duration);
 
-    buffer.to_rgba_image(1., 2.2).save("renders/bunny.png")?;
+    buffer.save_png("renders/bunny.png", 1., ColorSpace::Srgb, &Encoding::SRGB)?;
 
     Ok(())
 }
@@ -1,13 +1,31 @@
 use std::f64::consts::FRAC_1_PI;
 
 use crate::film::Rgb;
+use crate::math::OrthonormalBasis;
+use crate::sampler::{cosine_sample_hemisphere, Sample};
 use crate::shade_rec::ShadeRec;
 use crate::texture::Texture;
 use crate::Vector;
 
 pub trait Brdf {
     fn f(&self, sr: &ShadeRec, wi: &Vector, wo: &Vector) -> Rgb;
-    fn sample_f(&self, _sr: &ShadeRec, _wo: &Vector) -> (Rgb, Vector);
+    /// Samples an incident direction for the outgoing direction `wo`. Returns the value of the
+    /// brdf, the sampled direction and the density of having sampled it with respect to solid
+    /// angle.
+    fn sample_f(&self, sr: &ShadeRec, wo: &Vector, sample: Sample) -> (Rgb, Vector, f64);
+    /// Samples an incident direction as `sample_f`, for paths traced from the lights, which carry
+    /// flux rather than radiance. These only differ for refraction, which doesn't change the flux
+    /// but compresses or expands the solid angle and thus changes the radiance.
+    fn sample_f_from_light(
+        &self,
+        sr: &ShadeRec,
+        wo: &Vector,
+        sample: Sample,
+    ) -> (Rgb, Vector, f64) {
+        self.sample_f(sr, wo, sample)
+    }
+    /// Returns the density with respect to solid angle of sampling `wi` with `sample_f`.
+    fn pdf(&self, sr: &ShadeRec, wi: &Vector, wo: &Vector) -> f64;
     fn rho(&self, sr: &ShadeRec, wo: &Vector) -> Rgb;
 }
 
@@ -32,8 +50,14 @@ impl Brdf for Lambertian {
         self.cd * (self.kd * FRAC_1_PI)
     }
 
-    fn sample_f(&self, _sr: &ShadeRec, _wo: &Vector) -> (Rgb, Vector) {
-        unimplemented!()
+    fn sample_f(&self, sr: &ShadeRec, wo: &Vector, sample: Sample) -> (Rgb, Vector, f64) {
+        let wi = cosine_sample_direction(sr, sample);
+
+        (self.f(sr, &wi, wo), wi, self.pdf(sr, &wi, wo))
+    }
+
+    fn pdf(&self, sr: &ShadeRec, wi: &Vector, _wo: &Vector) -> f64 {
+        cosine_pdf(sr, wi)
     }
 
     fn rho(&self, _sr: &ShadeRec, _wo: &Vector) -> Rgb {
@@ -59,11 +83,225 @@ impl Brdf for SvLambertian {
         self.texture.get_color(sr) * self.kd * FRAC_1_PI
     }
 
-    fn sample_f(&self, _sr: &ShadeRec, _wo: &Vector) -> (Rgb, Vector) {
-        unimplemented!()
+    fn sample_f(&self, sr: &ShadeRec, wo: &Vector, sample: Sample) -> (Rgb, Vector, f64) {
+        let wi = cosine_sample_direction(sr, sample);
+
+        (self.f(sr, &wi, wo), wi, self.pdf(sr, &wi, wo))
+    }
+
+    fn pdf(&self, sr: &ShadeRec, wi: &Vector, _wo: &Vector) -> f64 {
+        cosine_pdf(sr, wi)
     }
 
     fn rho(&self, sr: &ShadeRec, _wo: &Vector) -> Rgb {
         self.texture.get_color(sr) * self.kd
     }
 }
+
+/// Perfect specular reflection, as off a mirror.
+#[derive(Debug, Copy, Clone)]
+pub struct PerfectSpecular {
+    // reflection coefficient, in [0, 1]
+    kr: f64,
+    // reflected color
+    cr: Rgb,
+}
+
+impl PerfectSpecular {
+    pub fn new(kr: f64, cr: Rgb) -> Self {
+        assert!((0. ..=1.).contains(&kr));
+
+        Self { kr, cr }
+    }
+}
+
+impl Brdf for PerfectSpecular {
+    fn f(&self, _sr: &ShadeRec, _wi: &Vector, _wo: &Vector) -> Rgb {
+        Rgb::black()
+    }
+
+    /// Returns the mirrored direction of `wo`, with a density of 1, as it is the only direction
+    /// that can be sampled.
+    fn sample_f(&self, sr: &ShadeRec, wo: &Vector, _sample: Sample) -> (Rgb, Vector, f64) {
+        let normal = sr.normal.normalize();
+        let n_dot_wo = normal.dot(wo);
+        let wi = -wo + 2. * n_dot_wo * normal;
+        let cos_theta = normal.dot(&wi).abs() / wi.norm();
+
+        if cos_theta == 0. {
+            return (Rgb::black(), wi, 0.);
+        }
+
+        (self.cr * (self.kr / cos_theta), wi, 1.)
+    }
+
+    fn pdf(&self, _sr: &ShadeRec, _wi: &Vector, _wo: &Vector) -> f64 {
+        0.
+    }
+
+    fn rho(&self, _sr: &ShadeRec, _wo: &Vector) -> Rgb {
+        self.cr * self.kr
+    }
+}
+
+/// A smooth boundary between vacuum and a dielectric such as glass or water, which reflects and
+/// refracts light according to the Fresnel equations. The normal of the surface points outside.
+#[derive(Debug, Copy, Clone)]
+pub struct Dielectric {
+    // index of refraction of the inside relative to the outside, at 587.6 nm
+    ior: f64,
+    // coefficient B of Cauchy's equation n = A + B / lambda^2, in nm^2
+    dispersion: f64,
+    // reflected color
+    cr: Rgb,
+    // transmitted color
+    ct: Rgb,
+}
+
+impl Dielectric {
+    pub fn new(ior: f64, cr: Rgb, ct: Rgb) -> Self {
+        assert!(ior > 0.);
+
+        Self {
+            ior,
+            dispersion: 0.,
+            cr,
+            ct,
+        }
+    }
+
+    /// Creates a new dielectric that neither tints the reflected nor the transmitted light.
+    pub fn clear(ior: f64) -> Self {
+        Self::new(ior, Rgb::white(), Rgb::white())
+    }
+
+    /// Makes the index of refraction vary with the wavelength according to Cauchy's equation,
+    /// with coefficient `b` in nm^2, e.g. 4200 for borosilicate glass. Only spectral renderers
+    /// split the light into its wavelengths.
+    pub fn with_dispersion(self, b: f64) -> Self {
+        assert!(b >= 0.);
+
+        Self {
+            dispersion: b,
+            ..self
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        self.dispersion > 0.
+    }
+
+    /// Returns this dielectric, with the index of refraction it has for light of wavelength
+    /// `lambda` in nm.
+    pub fn at_wavelength(&self, lambda: f64) -> Self {
+        const REFERENCE: f64 = 587.6;
+        let ior =
+            self.ior + self.dispersion * (1. / (lambda * lambda) - 1. / (REFERENCE * REFERENCE));
+
+        Self {
+            ior,
+            dispersion: 0.,
+            ..*self
+        }
+    }
+
+    /// Samples either the reflected or the refracted direction of `wo`. The refracted radiance is
+    /// scaled by the squared ratio of the indices of refraction if `radiance` is true, and is
+    /// unscaled for the flux carried from the lights otherwise.
+    fn sample(
+        &self,
+        sr: &ShadeRec,
+        wo: &Vector,
+        sample: Sample,
+        radiance: bool,
+    ) -> (Rgb, Vector, f64) {
+        let wo = wo.normalize();
+        let mut normal = sr.normal.normalize();
+        let mut cos_o = normal.dot(&wo);
+        let mut eta = self.ior;
+        if cos_o < 0. {
+            // leaving the dielectric
+            normal = -normal;
+            cos_o = -cos_o;
+            eta = 1. / eta;
+        }
+
+        let sin2_t = (1. - cos_o * cos_o).max(0.) / (eta * eta);
+        let reflected = -wo + 2. * cos_o * normal;
+        if sin2_t >= 1. {
+            // total internal reflection
+            return (self.cr * (1. / cos_o), reflected, 1.);
+        }
+
+        let cos_t = (1. - sin2_t).sqrt();
+        let reflectance = fresnel_dielectric(cos_o, cos_t, eta);
+
+        if sample.0 < reflectance {
+            (self.cr * (reflectance / cos_o), reflected, reflectance)
+        } else {
+            let refracted = -wo / eta + (cos_o / eta - cos_t) * normal;
+            let transmittance = 1. - reflectance;
+            // eta is the index of refraction on the side of the refracted direction relative to
+            // that of `wo`, from which the radiance is seen
+            let scale = if radiance { 1. / (eta * eta) } else { 1. };
+            (
+                self.ct * (scale * transmittance / cos_t),
+                refracted,
+                transmittance,
+            )
+        }
+    }
+}
+
+impl Brdf for Dielectric {
+    fn f(&self, _sr: &ShadeRec, _wi: &Vector, _wo: &Vector) -> Rgb {
+        Rgb::black()
+    }
+
+    /// Samples either the reflected or the refracted direction of `wo`, with the probability
+    /// given by the Fresnel reflectance.
+    fn sample_f(&self, sr: &ShadeRec, wo: &Vector, sample: Sample) -> (Rgb, Vector, f64) {
+        self.sample(sr, wo, sample, true)
+    }
+
+    fn sample_f_from_light(
+        &self,
+        sr: &ShadeRec,
+        wo: &Vector,
+        sample: Sample,
+    ) -> (Rgb, Vector, f64) {
+        self.sample(sr, wo, sample, false)
+    }
+
+    fn pdf(&self, _sr: &ShadeRec, _wi: &Vector, _wo: &Vector) -> f64 {
+        0.
+    }
+
+    fn rho(&self, _sr: &ShadeRec, _wo: &Vector) -> Rgb {
+        Rgb::black()
+    }
+}
+
+/// Returns the Fresnel reflectance of unpolarized light at a dielectric boundary, given the
+/// cosines of the incident and transmitted angles and the relative index of refraction.
+fn fresnel_dielectric(cos_i: f64, cos_t: f64, eta: f64) -> f64 {
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+/// Samples a direction in the hemisphere around the normal of `sr`, with a density proportional
+/// to the cosine with the normal.
+fn cosine_sample_direction(sr: &ShadeRec, sample: Sample) -> Vector {
+    let basis = OrthonormalBasis::from_vector(&sr.normal).unwrap();
+    let d = cosine_sample_hemisphere(sample);
+
+    d.x * *basis.u + d.y * *basis.v + d.z * *basis.w
+}
+
+fn cosine_pdf(sr: &ShadeRec, wi: &Vector) -> f64 {
+    let cos_theta = sr.normal.dot(wi) / (sr.normal.norm() * wi.norm());
+
+    cos_theta.max(0.) * FRAC_1_PI
+}
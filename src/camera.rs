@@ -9,6 +9,25 @@ pub trait Camera {
     fn generate_ray(&self, column: usize, row: usize, sample: Sample) -> Ray;
 
     fn resolution(&self) -> (usize, usize);
+
+    /// Returns where `point` appears on the image, or `None` if it doesn't appear on it or this
+    /// camera can't project points.
+    fn project(&self, _point: &Point3) -> Option<Projection> {
+        None
+    }
+}
+
+/// The position on the image of a point in the scene.
+#[derive(Debug, Copy, Clone)]
+pub struct Projection {
+    // continuous raster coordinates, the pixel at (column, row) covers [column, column + 1)
+    pub column: f64,
+    pub row: f64,
+    // origin of the rays towards the point
+    pub origin: Point3,
+    // density with respect to solid angle of generating the ray towards the point, when the
+    // pixels and the samples within them are chosen uniformly
+    pub pdf: f64,
 }
 
 #[derive(Debug)]
@@ -38,6 +57,31 @@ impl Camera for PerspectiveCamera {
     fn resolution(&self) -> (usize, usize) {
         (self.x_res, self.y_res)
     }
+
+    fn project(&self, point: &Point3) -> Option<Projection> {
+        let d = point - self.origin;
+        let z = -d.dot(&self.basis.w);
+        if z <= 0. {
+            return None;
+        }
+
+        let column = (d.dot(&self.basis.u) / (z * self.width) + 0.5) * self.x_res as f64;
+        let row = (d.dot(&self.basis.v) / (z * self.height) + 0.5) * self.y_res as f64;
+        if column < 0. || column >= self.x_res as f64 || row < 0. || row >= self.y_res as f64 {
+            return None;
+        }
+
+        // the image plane lies at distance 1, so the area density over it is 1 / (width * height)
+        let cos_theta = z / d.norm();
+        let pdf = 1. / (self.width * self.height * cos_theta.powi(3));
+
+        Some(Projection {
+            column,
+            row,
+            origin: self.origin,
+            pdf,
+        })
+    }
 }
 
 pub struct CameraBuilder {
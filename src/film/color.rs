@@ -0,0 +1,163 @@
+use super::Rgb;
+
+/// RGB color spaces, defined by their primaries and white point. Colors in the scene and in the
+/// frame buffer are linear in the working space, sRGB unless stated otherwise.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ColorSpace {
+    // sRGB / Rec. 709 primaries, with the D65 white point
+    Srgb,
+    // ACES AP1 primaries, with the ACES white point close to D60
+    AcesCg,
+}
+
+impl ColorSpace {
+    /// Converts the linear `color` from this color space to `target`, adapting the white point
+    /// with the Bradford transform.
+    pub fn convert(self, color: Rgb, target: ColorSpace) -> Rgb {
+        match (self, target) {
+            (ColorSpace::Srgb, ColorSpace::AcesCg) => transform(&SRGB_TO_ACESCG, color),
+            (ColorSpace::AcesCg, ColorSpace::Srgb) => transform(&ACESCG_TO_SRGB, color),
+            _ => color,
+        }
+    }
+
+    /// Returns the chromaticities of the white point and of the red, green and blue primaries.
+    pub fn chromaticities(self) -> [(f64, f64); 4] {
+        match self {
+            ColorSpace::Srgb => [(0.3127, 0.329), (0.64, 0.33), (0.3, 0.6), (0.15, 0.06)],
+            ColorSpace::AcesCg => [
+                (0.32168, 0.33767),
+                (0.713, 0.293),
+                (0.165, 0.83),
+                (0.128, 0.044),
+            ],
+        }
+    }
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        ColorSpace::Srgb
+    }
+}
+
+/// Functions that encode linear values into the values stored in an image, also known as opto-
+/// electronic transfer functions, and decode them again.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransferFunction {
+    Linear,
+    // piecewise curve of IEC 61966-2-1, close to a gamma of 2.2
+    Srgb,
+    // piecewise curve of ITU-R BT.709
+    Rec709,
+    // pure power law with the given gamma
+    Gamma(f64),
+}
+
+impl TransferFunction {
+    /// Encodes the linear `value`, in [0, 1].
+    pub fn encode(self, value: f64) -> f64 {
+        match self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb if value <= 0.0031308 => 12.92 * value,
+            TransferFunction::Srgb => 1.055 * value.powf(1. / 2.4) - 0.055,
+            TransferFunction::Rec709 if value < 0.018 => 4.5 * value,
+            TransferFunction::Rec709 => 1.099 * value.powf(0.45) - 0.099,
+            TransferFunction::Gamma(gamma) => value.powf(1. / gamma),
+        }
+    }
+
+    /// Decodes the encoded `value`, in [0, 1], into a linear value.
+    pub fn decode(self, value: f64) -> f64 {
+        match self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb if value <= 0.04045 => value / 12.92,
+            TransferFunction::Srgb => ((value + 0.055) / 1.055).powf(2.4),
+            TransferFunction::Rec709 if value < 0.081 => value / 4.5,
+            TransferFunction::Rec709 => ((value + 0.099) / 1.099).powf(1. / 0.45),
+            TransferFunction::Gamma(gamma) => value.powf(gamma),
+        }
+    }
+
+    /// Returns the exponent of the power law that best approximates the encoding.
+    pub fn exponent(self) -> f64 {
+        match self {
+            TransferFunction::Linear => 1.,
+            TransferFunction::Srgb => 1. / 2.2,
+            TransferFunction::Rec709 => 0.45,
+            TransferFunction::Gamma(gamma) => 1. / gamma,
+        }
+    }
+}
+
+/// How colors are stored in an image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Encoding {
+    pub color_space: ColorSpace,
+    pub transfer_function: TransferFunction,
+}
+
+impl Encoding {
+    /// Encoding of most images meant for displays.
+    pub const SRGB: Encoding = Encoding::new(ColorSpace::Srgb, TransferFunction::Srgb);
+    pub const LINEAR_SRGB: Encoding = Encoding::new(ColorSpace::Srgb, TransferFunction::Linear);
+    pub const REC709: Encoding = Encoding::new(ColorSpace::Srgb, TransferFunction::Rec709);
+    pub const ACESCG: Encoding = Encoding::new(ColorSpace::AcesCg, TransferFunction::Linear);
+
+    pub const fn new(color_space: ColorSpace, transfer_function: TransferFunction) -> Self {
+        Self {
+            color_space,
+            transfer_function,
+        }
+    }
+
+    /// Encodes `color`, which is linear in `working_space` and clamped to [0, 1].
+    pub fn encode(&self, color: Rgb, working_space: ColorSpace) -> Rgb {
+        let color = working_space.convert(color, self.color_space).clamp(0., 1.);
+        let encode = |value| self.transfer_function.encode(value);
+
+        Rgb::new(
+            encode(color.red()),
+            encode(color.green()),
+            encode(color.blue()),
+        )
+    }
+
+    /// Decodes `color`, of which the components lie in [0, 1], into a linear color in
+    /// `working_space`.
+    pub fn decode(&self, color: Rgb, working_space: ColorSpace) -> Rgb {
+        let decode = |value| self.transfer_function.decode(value);
+        let color = Rgb::new(
+            decode(color.red()),
+            decode(color.green()),
+            decode(color.blue()),
+        );
+
+        self.color_space.convert(color, working_space)
+    }
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::SRGB
+    }
+}
+
+fn transform(matrix: &[[f64; 3]; 3], color: Rgb) -> Rgb {
+    let apply =
+        |row: &[f64; 3]| row[0] * color.red() + row[1] * color.green() + row[2] * color.blue();
+
+    Rgb::new(apply(&matrix[0]), apply(&matrix[1]), apply(&matrix[2]))
+}
+
+const SRGB_TO_ACESCG: [[f64; 3]; 3] = [
+    [0.6130973, 0.3395229, 0.0473793],
+    [0.0701942, 0.9163556, 0.0134526],
+    [0.0206156, 0.1095698, 0.8698151],
+];
+
+const ACESCG_TO_SRGB: [[f64; 3]; 3] = [
+    [1.7050515, -0.6217923, -0.0832593],
+    [-0.1302597, 1.1408027, -0.010543],
+    [-0.0240003, -0.1289687, 1.1529691],
+];
@@ -1,6 +1,13 @@
 use image::{ImageBuffer, RgbaImage};
+use png::{
+    BitDepth, ColorType, Encoder, EncodingError, ScaledFloat, SourceChromaticities,
+    SrgbRenderingIntent,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 
-use super::{Pixel, Rgb};
+use super::{ColorSpace, Encoding, Pixel, Rgb, TransferFunction};
 
 pub struct FrameBuffer {
     buffer: Vec<Pixel>,
@@ -10,8 +17,6 @@ pub struct FrameBuffer {
 
 impl FrameBuffer {
     pub fn new(x_res: usize, y_res: usize) -> Self {
-        let x_res = x_res;
-        let y_res = y_res;
         let buffer = vec![Pixel::default(); x_res * y_res];
 
         Self {
@@ -29,15 +34,72 @@ impl FrameBuffer {
         self.buffer[self.x_res * (self.y_res - y - 1) + x]
     }
 
+    /// Converts the frame buffer to an image with a pure power law as transfer function. The
+    /// colors are scaled by `sensitivity` and clamped.
     pub fn to_rgba_image(&self, sensitivity: f64, gamma: f64) -> RgbaImage {
-        let inv_sensitivity = 1. / sensitivity;
-        let inv_gamma = 1. / gamma;
+        let encoding = Encoding::new(ColorSpace::Srgb, TransferFunction::Gamma(gamma));
+        self.to_image(sensitivity, ColorSpace::Srgb, &encoding)
+    }
 
+    /// Converts the frame buffer, of which the colors are linear in `working_space`, to an 8 bit
+    /// image with `encoding`. The colors are scaled by `sensitivity` and clamped.
+    pub fn to_image(
+        &self,
+        sensitivity: f64,
+        working_space: ColorSpace,
+        encoding: &Encoding,
+    ) -> RgbaImage {
         ImageBuffer::from_fn(self.x_res as u32, self.y_res as u32, |x, y| {
             let pixel = self.get_pixel(x as usize, y as usize);
-            Rgb::to_rgb(
-                (pixel.spectrum().clamp(0., inv_sensitivity) * sensitivity).pow(inv_gamma) * 255.,
-            )
+            let color = encoding.encode(pixel.spectrum() * sensitivity, working_space);
+            Rgb::to_rgb(color * 255.)
         })
     }
+
+    /// Writes the frame buffer, of which the colors are linear in `working_space`, to a 16 bit
+    /// PNG file with `encoding`, which is described by the chunks of the file. The colors are
+    /// scaled by `sensitivity` and clamped.
+    pub fn save_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        sensitivity: f64,
+        working_space: ColorSpace,
+        encoding: &Encoding,
+    ) -> Result<(), EncodingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = Encoder::new(file, self.x_res as u32, self.y_res as u32);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Sixteen);
+
+        if *encoding == Encoding::SRGB {
+            encoder.set_source_srgb(SrgbRenderingIntent::Perceptual);
+        } else {
+            let [white, red, green, blue] = encoding.color_space.chromaticities();
+            let chromaticity = |(x, y): (f64, f64)| (x as f32, y as f32);
+            encoder.set_source_chromaticities(SourceChromaticities::new(
+                chromaticity(white),
+                chromaticity(red),
+                chromaticity(green),
+                chromaticity(blue),
+            ));
+            encoder.set_source_gamma(ScaledFloat::new(
+                encoding.transfer_function.exponent() as f32
+            ));
+        }
+
+        let mut data = Vec::with_capacity(6 * self.x_res * self.y_res);
+        for y in 0..self.y_res {
+            for x in 0..self.x_res {
+                let pixel = self.get_pixel(x, y);
+                let color = encoding.encode(pixel.spectrum() * sensitivity, working_space);
+                for &value in &[color.red(), color.green(), color.blue()] {
+                    let value = (value * 65535. + 0.5) as u16;
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)
+    }
 }
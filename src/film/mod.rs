@@ -1,7 +1,13 @@
+pub use color::{ColorSpace, Encoding, TransferFunction};
 pub use frame_buffer::FrameBuffer;
 pub use pixel::Pixel;
 pub use rgb::Rgb;
+pub use spectrum::{
+    cie_xyz, SampledSpectrum, SampledWavelengths, LAMBDA_MAX, LAMBDA_MIN, NB_WAVELENGTHS,
+};
 
+mod color;
 mod frame_buffer;
 mod pixel;
 mod rgb;
+mod spectrum;
@@ -34,6 +34,18 @@ impl Rgb {
         }
     }
 
+    pub fn red(self) -> f64 {
+        self.red
+    }
+
+    pub fn green(self) -> f64 {
+        self.green
+    }
+
+    pub fn blue(self) -> f64 {
+        self.blue
+    }
+
     pub fn to_rgb(self) -> Rgba<u8> {
         let r = self.red.clamp(0., 255.) as u8;
         let g = self.green.clamp(0., 255.) as u8;
@@ -50,6 +62,15 @@ impl Rgb {
         Self { red, green, blue }
     }
 
+    /// Returns e raised to every component of this color.
+    pub fn exp(self) -> Self {
+        let red = self.red.exp();
+        let green = self.green.exp();
+        let blue = self.blue.exp();
+
+        Self { red, green, blue }
+    }
+
     pub fn clamp(self, low: f64, high: f64) -> Self {
         let red = self.red.clamp(low, high);
         let green = self.green.clamp(low, high);
@@ -58,6 +79,15 @@ impl Rgb {
         Self { red, green, blue }
     }
 
+    pub fn average(self) -> f64 {
+        (self.red + self.green + self.blue) / 3.
+    }
+
+    /// Returns the relative luminance of this color, using the Rec. 709 primaries.
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
     fn is_valid_color_component(val: f64) -> bool {
         val.is_finite() && !val.is_nan()
     }
@@ -125,6 +155,18 @@ impl Mul for Rgb {
     }
 }
 
+impl Div for Rgb {
+    type Output = Rgb;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let red = self.red / rhs.red;
+        let green = self.green / rhs.green;
+        let blue = self.blue / rhs.blue;
+
+        Self { red, green, blue }
+    }
+}
+
 impl AddAssign for Rgb {
     fn add_assign(&mut self, rhs: Self) {
         self.red += rhs.red;
@@ -0,0 +1,319 @@
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Div, Mul};
+
+use super::Rgb;
+
+/// Shortest wavelength that is sampled, in nm.
+pub const LAMBDA_MIN: f64 = 360.;
+/// Longest wavelength that is sampled, in nm.
+pub const LAMBDA_MAX: f64 = 830.;
+/// Number of wavelengths carried along every path. Light samples are estimated per color channel,
+/// so every channel of an `Rgb` carries one wavelength through them.
+pub const NB_WAVELENGTHS: usize = 3;
+
+/// The wavelengths carried along a path. The first one is the hero wavelength, which decides the
+/// path wherever light of different wavelengths would take different directions, the others are
+/// evenly rotated from it so all wavelengths are sampled at once.
+#[derive(Debug, Copy, Clone)]
+pub struct SampledWavelengths {
+    // wavelengths in nm
+    lambda: [f64; NB_WAVELENGTHS],
+    // densities of having sampled the wavelengths, zero for wavelengths that were terminated
+    pdf: [f64; NB_WAVELENGTHS],
+}
+
+impl SampledWavelengths {
+    /// Samples wavelengths with a density that roughly follows the sensitivity of the eye, as
+    /// proposed by Radziszewski et al. in "An Improved Technique for Full Spectral Rendering".
+    pub fn sample(u: f64) -> Self {
+        let mut lambda = [0.; NB_WAVELENGTHS];
+        let mut pdf = [0.; NB_WAVELENGTHS];
+
+        for i in 0..NB_WAVELENGTHS {
+            let u = (u + i as f64 / NB_WAVELENGTHS as f64).fract();
+            lambda[i] = (538. - 138.888889 * (0.85691062 - 1.82750197 * u).atanh())
+                .clamp(LAMBDA_MIN, LAMBDA_MAX);
+            pdf[i] = 0.0039398042 / (0.0072 * (lambda[i] - 538.)).cosh().powi(2);
+        }
+
+        Self { lambda, pdf }
+    }
+
+    /// Returns the hero wavelength, in nm.
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self) -> &[f64; NB_WAVELENGTHS] {
+        &self.lambda
+    }
+
+    /// Only keeps the hero wavelength, for when the path depends on the wavelength, such as on
+    /// dispersive surfaces.
+    pub fn terminate_secondary(&mut self) {
+        if self.is_secondary_terminated() {
+            return;
+        }
+
+        for pdf in &mut self.pdf[1..] {
+            *pdf = 0.;
+        }
+        // the hero wavelength now accounts for the whole estimate
+        self.pdf[0] /= NB_WAVELENGTHS as f64;
+    }
+
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.)
+    }
+}
+
+/// Values of a spectral distribution, such as radiance or reflectance, at sampled wavelengths.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SampledSpectrum {
+    values: [f64; NB_WAVELENGTHS],
+}
+
+impl SampledSpectrum {
+    pub const fn constant(value: f64) -> Self {
+        Self {
+            values: [value; NB_WAVELENGTHS],
+        }
+    }
+
+    pub const fn black() -> Self {
+        Self::constant(0.)
+    }
+
+    /// Uplifts `rgb` to a smooth spectrum with Smits' method, from "An RGB-to-Spectrum Conversion
+    /// for Reflectances", and evaluates it at `wavelengths`.
+    pub fn from_rgb(rgb: Rgb, wavelengths: &SampledWavelengths) -> Self {
+        let mut values = [0.; NB_WAVELENGTHS];
+        for (value, &lambda) in values.iter_mut().zip(&wavelengths.lambda) {
+            *value = smits(rgb, lambda);
+        }
+
+        Self { values }
+    }
+
+    /// Carries a value per wavelength through the channels of an `Rgb`.
+    pub(crate) fn from_channels(channels: Rgb) -> Self {
+        Self {
+            values: [channels.red(), channels.green(), channels.blue()],
+        }
+    }
+
+    /// Returns the values per wavelength in the channels of an `Rgb`, to be estimated by
+    /// interfaces that work with colors.
+    pub(crate) fn channels(self) -> Rgb {
+        Rgb::new(self.values[0], self.values[1], self.values[2])
+    }
+
+    pub fn values(&self) -> &[f64; NB_WAVELENGTHS] {
+        &self.values
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.values.iter().all(|&value| value == 0.)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.values
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Estimates the CIE XYZ tristimulus values of the spectrum, of which the values were
+    /// sampled at `wavelengths`. Y is normalized such that a constant spectrum of 1 has Y = 1.
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> [f64; 3] {
+        let mut xyz = [0.; 3];
+        for ((&value, &lambda), &pdf) in self
+            .values
+            .iter()
+            .zip(&wavelengths.lambda)
+            .zip(&wavelengths.pdf)
+        {
+            if pdf == 0. {
+                continue;
+            }
+
+            let matching = cie_xyz(lambda);
+            for (xyz, matching) in xyz.iter_mut().zip(&matching) {
+                *xyz += value * matching / (pdf * NB_WAVELENGTHS as f64);
+            }
+        }
+
+        let y_integral = cie_xyz_integrals()[1];
+        [
+            xyz[0] / y_integral,
+            xyz[1] / y_integral,
+            xyz[2] / y_integral,
+        ]
+    }
+
+    /// Converts the spectrum, of which the values were sampled at `wavelengths`, to linear sRGB,
+    /// white balanced such that a constant spectrum converts to a gray.
+    pub fn to_rgb(&self, wavelengths: &SampledWavelengths) -> Rgb {
+        let [red, green, blue] = xyz_to_srgb(self.to_xyz(wavelengths));
+        let [x, y, z] = cie_xyz_integrals();
+        let [white_red, white_green, white_blue] = xyz_to_srgb([x / y, 1., z / y]);
+
+        Rgb::new(red / white_red, green / white_green, blue / white_blue)
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        for (value, rhs) in self.values.iter_mut().zip(&rhs.values) {
+            *value += rhs;
+        }
+
+        self
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn mul(mut self, rhs: Self) -> Self::Output {
+        for (value, rhs) in self.values.iter_mut().zip(&rhs.values) {
+            *value *= rhs;
+        }
+
+        self
+    }
+}
+
+impl Mul<f64> for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn mul(mut self, rhs: f64) -> Self::Output {
+        for value in &mut self.values {
+            *value *= rhs;
+        }
+
+        self
+    }
+}
+
+impl Div<f64> for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        self * (1. / rhs)
+    }
+}
+
+/// Returns the CIE 1931 color matching functions at `lambda` in nm, with the multi-lobe fit of
+/// Wyman et al. in "Simple Analytic Approximations to the CIE XYZ Color Matching Functions".
+pub fn cie_xyz(lambda: f64) -> [f64; 3] {
+    let lobe = |&(height, mean, sigma_low, sigma_high): &(f64, f64, f64, f64)| {
+        let sigma = if lambda < mean { sigma_low } else { sigma_high };
+        let t = (lambda - mean) / sigma;
+        height * (-0.5 * t * t).exp()
+    };
+
+    let mut xyz = [0.; 3];
+    for (xyz, lobes) in xyz.iter_mut().zip(&CIE_LOBES) {
+        *xyz = lobes.iter().map(lobe).sum();
+    }
+
+    xyz
+}
+
+/// Returns the integrals of the color matching functions over all wavelengths.
+fn cie_xyz_integrals() -> [f64; 3] {
+    let mut integrals = [0.; 3];
+    for (integral, lobes) in integrals.iter_mut().zip(&CIE_LOBES) {
+        *integral = lobes
+            .iter()
+            .map(|&(height, _, sigma_low, sigma_high)| {
+                height * (PI / 2.).sqrt() * (sigma_low + sigma_high)
+            })
+            .sum();
+    }
+
+    integrals
+}
+
+// height, mean and standard deviations below and above the mean of the gaussian lobes that sum
+// to every color matching function
+#[allow(clippy::type_complexity)]
+const CIE_LOBES: [&[(f64, f64, f64, f64)]; 3] = [
+    &[
+        (1.056, 599.8, 37.9, 31.),
+        (0.362, 442., 16., 26.7),
+        (-0.065, 501.1, 20.4, 26.2),
+    ],
+    &[(0.821, 568.8, 46.9, 40.5), (0.286, 530.9, 16.3, 31.1)],
+    &[(1.217, 437., 11.8, 36.), (0.681, 459., 26., 13.8)],
+];
+
+/// Converts CIE XYZ to linear sRGB, with the D65 white point.
+fn xyz_to_srgb([x, y, z]: [f64; 3]) -> [f64; 3] {
+    [
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.969266 * x + 1.8760108 * y + 0.041556 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    ]
+}
+
+/// Evaluates the spectrum of `rgb` at `lambda` in nm, as a sum of the spectra of white and of
+/// the primary and secondary colors.
+fn smits(rgb: Rgb, lambda: f64) -> f64 {
+    let bin = ((lambda - SMITS_MIN) / (SMITS_MAX - SMITS_MIN) * SMITS_WHITE.len() as f64)
+        .max(0.)
+        .min(SMITS_WHITE.len() as f64 - 1.) as usize;
+    let (red, green, blue) = (rgb.red(), rgb.green(), rgb.blue());
+
+    if red <= green && red <= blue {
+        let base = red * SMITS_WHITE[bin];
+        if green <= blue {
+            base + (green - red) * SMITS_CYAN[bin] + (blue - green) * SMITS_BLUE[bin]
+        } else {
+            base + (blue - red) * SMITS_CYAN[bin] + (green - blue) * SMITS_GREEN[bin]
+        }
+    } else if green <= red && green <= blue {
+        let base = green * SMITS_WHITE[bin];
+        if red <= blue {
+            base + (red - green) * SMITS_MAGENTA[bin] + (blue - red) * SMITS_BLUE[bin]
+        } else {
+            base + (blue - green) * SMITS_MAGENTA[bin] + (red - blue) * SMITS_RED[bin]
+        }
+    } else {
+        let base = blue * SMITS_WHITE[bin];
+        if red <= green {
+            base + (red - blue) * SMITS_YELLOW[bin] + (green - red) * SMITS_GREEN[bin]
+        } else {
+            base + (green - blue) * SMITS_YELLOW[bin] + (red - green) * SMITS_RED[bin]
+        }
+    }
+}
+
+// range of wavelengths in nm over which the spectra of Smits' method are given, in equal bins
+const SMITS_MIN: f64 = 380.;
+const SMITS_MAX: f64 = 720.;
+
+const SMITS_WHITE: [f64; 10] = [1., 1., 0.9999, 0.9993, 0.9992, 0.9998, 1., 1., 1., 1.];
+const SMITS_CYAN: [f64; 10] = [
+    0.971, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0., 0., 0.,
+];
+const SMITS_MAGENTA: [f64; 10] = [1., 1., 0.9685, 0.2229, 0., 0.0458, 0.8369, 1., 1., 0.9959];
+const SMITS_YELLOW: [f64; 10] = [
+    0.0001, 0., 0.1088, 0.6651, 1., 1., 0.9996, 0.9586, 0.9685, 0.984,
+];
+const SMITS_RED: [f64; 10] = [
+    0.1012, 0.0515, 0., 0., 0., 0., 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; 10] = [0., 0., 0.0273, 0.7937, 1., 0.9418, 0.1719, 0., 0., 0.0025];
+const SMITS_BLUE: [f64; 10] = [
+    1., 1., 0.8916, 0.3323, 0., 0., 0.0003, 0.0369, 0.0483, 0.0496,
+];
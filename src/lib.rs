@@ -8,8 +8,11 @@ pub mod film;
 pub mod light;
 pub mod material;
 pub mod math;
+pub mod medium;
+pub mod photon_map;
 pub mod renderer;
 pub mod sampler;
+pub mod scene;
 pub mod shade_rec;
 pub mod shape;
 pub mod texture;
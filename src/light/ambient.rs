@@ -0,0 +1,92 @@
+use crate::film::Rgb;
+use crate::light::Ambient;
+use crate::math::{OrthonormalBasis, Ray};
+use crate::sampler::{cosine_sample_hemisphere, JitteredSampler, Sampler};
+use crate::shade_rec::ShadeRec;
+
+/// Ambient light that reaches every point in the scene equally.
+pub struct AmbientLight {
+    ls: f64,
+    color: Rgb,
+}
+
+impl AmbientLight {
+    pub fn new(ls: f64, color: Rgb) -> Self {
+        Self { ls, color }
+    }
+
+    pub fn white(ls: f64) -> Self {
+        let color = Rgb::white();
+        Self { ls, color }
+    }
+}
+
+impl Ambient for AmbientLight {
+    fn radiance(&self, _sr: &ShadeRec) -> Rgb {
+        self.color * self.ls
+    }
+}
+
+/// Ambient light that is attenuated by the fraction of the hemisphere above a point that is
+/// occluded by nearby objects, darkening creases and contact points.
+pub struct AmbientOccluder {
+    ls: f64,
+    color: Rgb,
+    // objects further away than this distance don't occlude
+    max_distance: f64,
+    sampler: JitteredSampler,
+}
+
+impl AmbientOccluder {
+    /// Creates a new ambient occluder that takes `nb_samples` jittered samples of the hemisphere,
+    /// rounded down to a perfect square.
+    pub fn new(ls: f64, color: Rgb, max_distance: f64, nb_samples: usize) -> Self {
+        assert!(max_distance > 0.);
+        assert!(nb_samples > 0);
+
+        let sampler = JitteredSampler::new(nb_samples);
+
+        Self {
+            ls,
+            color,
+            max_distance,
+            sampler,
+        }
+    }
+
+    pub fn white(ls: f64, max_distance: f64, nb_samples: usize) -> Self {
+        Self::new(ls, Rgb::white(), max_distance, nb_samples)
+    }
+
+    /// Returns the cosine weighted fraction of the hemisphere above the hit point of `sr` that
+    /// isn't occluded.
+    pub fn visibility(&self, sr: &ShadeRec) -> f64 {
+        let basis = match OrthonormalBasis::from_vector(&sr.normal) {
+            Some(basis) => basis,
+            None => return 1.,
+        };
+
+        self.sampler
+            .average(|sample| {
+                let d = cosine_sample_hemisphere(sample);
+                let direction = d.x * *basis.u + d.y * *basis.v + d.z * *basis.w;
+                let ray = Ray::new(sr.hit_point, direction);
+
+                if sr
+                    .world
+                    .hit_any_object_where(&ray, |hit| hit.t < self.max_distance)
+                {
+                    Rgb::black()
+                } else {
+                    Rgb::white()
+                }
+            })
+            .luminance()
+    }
+}
+
+impl Ambient for AmbientOccluder {
+    fn radiance(&self, sr: &ShadeRec) -> Rgb {
+        self.color * (self.ls * self.visibility(sr))
+    }
+}
@@ -0,0 +1,166 @@
+use nalgebra::Unit;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::film::Rgb;
+use crate::light::{Emission, Light, LightPosition, LightSample, LUMINOUS_EFFICACY};
+use crate::material::{Emissive, Material};
+use crate::math::{OrthonormalBasis, Ray};
+use crate::sampler::{cosine_sample_hemisphere, Sample, Sampler, UniformSampler};
+use crate::shade_rec::ShadeRec;
+use crate::shape::{Aabb, Bounded, GeometricObject, Intersect, Sampleable, SurfaceSample};
+use crate::Vector;
+
+/// A one-sided, diffusely emitting area light, of which the emitting surface can be any
+/// sampleable shape. The light is sampled with `sampler` at every shading point.
+pub struct AreaLight<S, P = UniformSampler> {
+    shape: Arc<S>,
+    // emitted radiance, in W/(sr m^2)
+    material: Emissive,
+    sampler: P,
+}
+
+impl<S: Sampleable> AreaLight<S> {
+    /// Creates a new area light that is sampled once at every shading point. The area of `shape`
+    /// must be exact, which excludes shapes with a non-uniform scaling, since the power of the
+    /// light and the sampling of its emission are based on it.
+    pub fn new(shape: S, material: Emissive) -> Self {
+        assert!(
+            shape.has_exact_area(),
+            "the emitting surface of an area light must have an exact area, \
+             which is unknown for non-uniformly scaled shapes"
+        );
+
+        let sampler = UniformSampler::new(1);
+
+        Self {
+            shape: Arc::new(shape),
+            material,
+            sampler,
+        }
+    }
+
+    /// Creates a new area light that emits a total radiant power of `power` W.
+    pub fn from_power(shape: S, power: f64, color: Rgb) -> Self {
+        let radiance = power / (PI * shape.area());
+        Self::new(shape, Emissive::new(radiance, color))
+    }
+
+    /// Creates a new area light that emits a total luminous flux of `flux` lm.
+    pub fn from_luminous_flux(shape: S, flux: f64, color: Rgb) -> Self {
+        Self::from_power(shape, flux / LUMINOUS_EFFICACY, color)
+    }
+}
+
+impl<S: Sampleable, P: Sampler> AreaLight<S, P> {
+    /// Replaces the sampler of this light, which determines the number and distribution of the
+    /// samples taken at every shading point.
+    pub fn with_sampler<Q: Sampler>(self, sampler: Q) -> AreaLight<S, Q> {
+        AreaLight {
+            shape: self.shape,
+            material: self.material,
+            sampler,
+        }
+    }
+}
+
+impl<S, P> AreaLight<S, P>
+where
+    S: Sampleable + Send + 'static,
+    P: Sampler + Sync + 'static,
+{
+    /// Creates a light sample for a point on the emitting surface, as seen from the hit point of
+    /// `sr`. Returns `None` if the hit point lies behind the surface.
+    fn light_sample(
+        &self,
+        sr: &ShadeRec,
+        surface_sample: &SurfaceSample,
+    ) -> Option<LightSample<'_>> {
+        let d = surface_sample.point - sr.hit_point;
+        let distance_squared = d.norm_squared();
+        let cos_light = -surface_sample.normal.dot(&d) / distance_squared.sqrt();
+
+        if cos_light <= 0. {
+            return None;
+        }
+
+        // converts the density over the area to a density over solid angle
+        let pdf = self.shape.pdf(&sr.hit_point, surface_sample) * distance_squared / cos_light;
+
+        Some(LightSample {
+            light: self,
+            position: LightPosition::Surface(surface_sample.point, surface_sample.normal),
+            radiance: self.material.ce * self.material.ls,
+            pdf,
+        })
+    }
+}
+
+impl<S, P> Light for AreaLight<S, P>
+where
+    S: Sampleable + Send + 'static,
+    P: Sampler + Sync + 'static,
+{
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        self.sampler.average(|sample| {
+            let surface_sample = self.shape.sample(&sr.hit_point, sample);
+
+            match self.light_sample(sr, &surface_sample) {
+                Some(light_sample) => f(light_sample),
+                None => Rgb::black(),
+            }
+        })
+    }
+
+    fn sample_direction(&self, sr: &ShadeRec, wi: &Unit<Vector>) -> Option<LightSample<'_>> {
+        let ray = Ray::new(sr.hit_point, **wi);
+        let hit = self.shape.intersect(&ray)?;
+        let surface_sample = SurfaceSample {
+            point: ray.origin() + hit.t * ray.direction(),
+            normal: Unit::new_normalize(hit.normal),
+        };
+
+        self.light_sample(sr, &surface_sample)
+    }
+
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    fn sample_emission(&self, position: Sample, direction: Sample) -> Option<Emission> {
+        let surface_sample = self.shape.sample_uniform(position);
+        let basis = OrthonormalBasis::from_vector(&surface_sample.normal)?;
+        let d = cosine_sample_hemisphere(direction);
+        let w = d.x * *basis.u + d.y * *basis.v + d.z * *basis.w;
+        let (pdf_position, pdf_direction) = self.pdf_emission(&surface_sample.normal, &w);
+
+        Some(Emission {
+            ray: Ray::new(surface_sample.point, w),
+            normal: Some(surface_sample.normal),
+            flux: self.power(),
+            pdf_position,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, normal: &Vector, direction: &Vector) -> (f64, f64) {
+        let cos_theta = normal.dot(direction) / (normal.norm() * direction.norm());
+
+        (1. / self.shape.area(), cos_theta.max(0.) / PI)
+    }
+
+    fn power(&self) -> Rgb {
+        self.material.ce * (PI * self.material.ls * self.shape.area())
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.shape.bbox())
+    }
+
+    fn geometric_object(&self) -> Option<GeometricObject> {
+        Some(GeometricObject::new(
+            Box::new(self.shape.clone()),
+            Material::Emissive(self.material),
+        ))
+    }
+}
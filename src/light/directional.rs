@@ -0,0 +1,57 @@
+use nalgebra::Unit;
+
+use crate::film::Rgb;
+use crate::light::{Light, LightPosition, LightSample, LUMINOUS_EFFICACY};
+use crate::material::Emissive;
+use crate::shade_rec::ShadeRec;
+use crate::Vector;
+
+/// A light that is infinitely far away, such as the sun. All of its light arrives at the scene
+/// along the same direction.
+pub struct DirectionalLight {
+    // direction in which the light travels
+    direction: Unit<Vector>,
+    // irradiance on a surface perpendicular to the light, in W/m^2
+    material: Emissive,
+}
+
+impl DirectionalLight {
+    /// Creates a new directional light with the given irradiance in W/m^2 on a surface
+    /// perpendicular to the light.
+    pub fn new(irradiance: f64, color: Rgb, direction: Vector) -> Self {
+        let direction = Unit::new_normalize(direction);
+        let material = Emissive::new(irradiance, color);
+
+        Self {
+            direction,
+            material,
+        }
+    }
+
+    pub fn white(irradiance: f64, direction: Vector) -> Self {
+        Self::new(irradiance, Rgb::white(), direction)
+    }
+
+    /// Creates a new directional light with the given illuminance in lx on a surface
+    /// perpendicular to the light.
+    pub fn from_illuminance(illuminance: f64, color: Rgb, direction: Vector) -> Self {
+        Self::new(illuminance / LUMINOUS_EFFICACY, color, direction)
+    }
+}
+
+impl Light for DirectionalLight {
+    fn average(&self, _sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        let light_sample = LightSample {
+            light: self,
+            position: LightPosition::Infinite(-self.direction),
+            radiance: self.material.ce * self.material.ls,
+            pdf: 1.,
+        };
+
+        f(light_sample)
+    }
+
+    fn power(&self) -> Rgb {
+        Rgb::black()
+    }
+}
@@ -0,0 +1,164 @@
+use image::codecs::hdr::HdrDecoder;
+use image::ImageResult;
+use nalgebra::Unit;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::film::Rgb;
+use crate::light::{Light, LightPosition, LightSample};
+use crate::math::{Distribution2D, Ray};
+use crate::sampler::{Sampler, UniformSampler};
+use crate::shade_rec::ShadeRec;
+use crate::{Point2, Vector};
+
+/// A light infinitely far away that surrounds the whole scene, of which the emitted radiance is
+/// given by an equirectangular image. The positive y-axis maps onto the top row of the image and
+/// the negative z-axis onto its center.
+pub struct EnvironmentLight {
+    width: usize,
+    height: usize,
+    // scaled radiance of every pixel, row by row starting at the top
+    image: Vec<Rgb>,
+    // distribution of the image coordinates, proportional to the luminance of the pixels
+    distribution: Distribution2D,
+    sampler: UniformSampler,
+}
+
+impl EnvironmentLight {
+    /// Creates a new environment light from the pixels of an equirectangular image, stored row by
+    /// row starting at the top, of which the radiance is scaled by `ls`.
+    pub fn new(ls: f64, width: usize, height: usize, image: Vec<Rgb>) -> Self {
+        assert_eq!(image.len(), width * height);
+
+        let image = image
+            .into_iter()
+            .map(|color| color * ls)
+            .collect::<Vec<_>>();
+        // accounts for the stretching of the rows near the poles
+        let func = image
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let theta = PI * ((i / width) as f64 + 0.5) / height as f64;
+                color.luminance() * theta.sin()
+            })
+            .collect::<Vec<_>>();
+        let distribution = Distribution2D::new(&func, width);
+        let sampler = UniformSampler::new(1);
+
+        Self {
+            width,
+            height,
+            image,
+            distribution,
+            sampler,
+        }
+    }
+
+    /// Loads an equirectangular image in the Radiance HDR format.
+    pub fn load(ls: f64, path: &str) -> ImageResult<Self> {
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let metadata = decoder.metadata();
+        let image = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .map(|pixel| {
+                let [red, green, blue] = pixel.0;
+                Rgb::new(red as f64, green as f64, blue as f64)
+            })
+            .collect();
+
+        Ok(Self::new(
+            ls,
+            metadata.width as usize,
+            metadata.height as usize,
+            image,
+        ))
+    }
+
+    /// Creates a light sample for the given image coordinates and direction, given the density
+    /// of sampling the image coordinates.
+    fn light_sample(
+        &self,
+        uv: &Point2,
+        direction: Unit<Vector>,
+        map_pdf: f64,
+        sin_theta: f64,
+    ) -> Option<LightSample<'_>> {
+        if map_pdf == 0. || sin_theta == 0. {
+            return None;
+        }
+
+        // converts the density over the image to a density over solid angle
+        let pdf = map_pdf / (2. * PI * PI * sin_theta);
+
+        Some(LightSample {
+            light: self,
+            position: LightPosition::Infinite(direction),
+            radiance: self.lookup(uv),
+            pdf,
+        })
+    }
+
+    /// Returns the radiance at the given image coordinates, in the unit square.
+    fn lookup(&self, uv: &Point2) -> Rgb {
+        let x = ((uv.x * self.width as f64) as usize).min(self.width - 1);
+        let y = ((uv.y * self.height as f64) as usize).min(self.height - 1);
+
+        self.image[y * self.width + x]
+    }
+}
+
+impl Light for EnvironmentLight {
+    fn average(&self, _sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        self.sampler.average(|sample| {
+            let (uv, map_pdf) = self.distribution.sample_continuous(sample);
+            let (direction, sin_theta) = uv_to_direction(&uv);
+
+            match self.light_sample(&uv, direction, map_pdf, sin_theta) {
+                Some(light_sample) => f(light_sample),
+                None => Rgb::black(),
+            }
+        })
+    }
+
+    fn sample_direction(&self, _sr: &ShadeRec, wi: &Unit<Vector>) -> Option<LightSample<'_>> {
+        let uv = direction_to_uv(wi);
+        let sin_theta = (PI * uv.y).sin();
+
+        self.light_sample(&uv, *wi, self.distribution.pdf(&uv), sin_theta)
+    }
+
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    fn power(&self) -> Rgb {
+        Rgb::black()
+    }
+
+    fn background(&self, ray: &Ray) -> Rgb {
+        self.lookup(&direction_to_uv(ray.direction()))
+    }
+}
+
+/// Maps image coordinates onto the direction they represent, also returning the sine of its
+/// polar angle.
+fn uv_to_direction(uv: &Point2) -> (Unit<Vector>, f64) {
+    let theta = uv.y * PI;
+    let phi = (uv.x - 0.5) * 2. * PI;
+    let sin_theta = theta.sin();
+
+    let direction = Vector::new(sin_theta * phi.sin(), theta.cos(), -sin_theta * phi.cos());
+
+    (Unit::new_normalize(direction), sin_theta)
+}
+
+fn direction_to_uv(direction: &Vector) -> Point2 {
+    let direction = direction.normalize();
+    let theta = direction.y.clamp(-1., 1.).acos();
+    let phi = direction.x.atan2(-direction.z);
+
+    Point2::new(0.5 + phi / (2. * PI), theta / PI)
+}
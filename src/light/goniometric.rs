@@ -0,0 +1,339 @@
+use nalgebra::Unit;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+
+use crate::film::Rgb;
+use crate::light::{Emission, Light, LightSample, LUMINOUS_EFFICACY};
+use crate::material::Emissive;
+use crate::math::{OrthonormalBasis, Ray};
+use crate::sampler::{uniform_sample_sphere, Sample};
+use crate::shade_rec::ShadeRec;
+use crate::shape::Aabb;
+use crate::{Point3, Vector, K_EPSILON};
+
+/// A point light of which the emitted intensity varies with the direction, as described by a
+/// photometric profile. The emitted light falls off with the squared distance.
+pub struct GoniometricLight {
+    location: Point3,
+    // w points towards the nadir of the profile, u towards its zero horizontal angle
+    basis: OrthonormalBasis,
+    profile: IesProfile,
+    material: Emissive,
+}
+
+impl GoniometricLight {
+    /// Creates a new goniometric light at `location`, for which the nadir (vertical angle 0) of
+    /// `profile` points along `nadir`. The luminous intensities of the profile are scaled by `ls`.
+    pub fn new(ls: f64, color: Rgb, location: Point3, nadir: Vector, profile: IesProfile) -> Self {
+        assert!(
+            nadir.norm() > K_EPSILON,
+            "the nadir of a goniometric light must not be a zero vector"
+        );
+
+        let basis = OrthonormalBasis::from_vector(&nadir).unwrap();
+        let material = Emissive::new(ls, color);
+
+        Self {
+            location,
+            basis,
+            profile,
+            material,
+        }
+    }
+
+    pub fn white(ls: f64, location: Point3, nadir: Vector, profile: IesProfile) -> Self {
+        Self::new(ls, Rgb::white(), location, nadir, profile)
+    }
+
+    /// Creates a new goniometric light of which `profile` is rescaled such that the light emits a
+    /// total luminous flux of `flux` lm.
+    pub fn from_luminous_flux(
+        flux: f64,
+        color: Rgb,
+        location: Point3,
+        nadir: Vector,
+        profile: IesProfile,
+    ) -> Self {
+        let profile_flux = profile.luminous_flux();
+        assert!(
+            profile_flux > 0.,
+            "the profile of a goniometric light defined by its flux must emit light"
+        );
+
+        let ls = flux / profile_flux;
+        Self::new(ls, color, location, nadir, profile)
+    }
+
+    /// Returns the luminous intensity in candela emitted along `w`, a normalized direction
+    /// pointing away from the light.
+    fn intensity(&self, w: &Unit<Vector>) -> f64 {
+        let vertical = w.dot(&self.basis.w).clamp(-1., 1.).acos().to_degrees();
+        let horizontal = w
+            .dot(&self.basis.v)
+            .atan2(w.dot(&self.basis.u))
+            .to_degrees();
+
+        self.profile.candela(vertical, horizontal)
+    }
+}
+
+impl Light for GoniometricLight {
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        let w = Unit::new_normalize(sr.hit_point - self.location);
+        let intensity =
+            self.material.ce * (self.material.ls * self.intensity(&w) / LUMINOUS_EFFICACY);
+
+        f(LightSample::point(self, self.location, intensity, sr))
+    }
+
+    fn sample_emission(&self, _position: Sample, direction: Sample) -> Option<Emission> {
+        let w = Unit::new_normalize(uniform_sample_sphere(direction));
+        let intensity =
+            self.material.ce * (self.material.ls * self.intensity(&w) / LUMINOUS_EFFICACY);
+
+        Some(Emission {
+            ray: Ray::new(self.location, *w),
+            normal: None,
+            flux: intensity * (4. * PI),
+            pdf_position: 1.,
+            pdf_direction: 1. / (4. * PI),
+        })
+    }
+
+    fn pdf_emission(&self, _normal: &Vector, _direction: &Vector) -> (f64, f64) {
+        (1., 1. / (4. * PI))
+    }
+
+    fn power(&self) -> Rgb {
+        self.material.ce * (self.material.ls * self.profile.luminous_flux() / LUMINOUS_EFFICACY)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.location, self.location))
+    }
+}
+
+/// A photometric profile in the IES LM-63 format, using type C photometry.
+#[derive(Debug, Clone)]
+pub struct IesProfile {
+    // vertical angles in degrees, ascending
+    vertical_angles: Vec<f64>,
+    // horizontal angles in degrees, ascending
+    horizontal_angles: Vec<f64>,
+    // candela values, for each horizontal angle all vertical angles
+    candela: Vec<f64>,
+}
+
+impl IesProfile {
+    pub fn load(path: &str) -> Result<Self, IesError> {
+        let input = fs::read_to_string(path)?;
+        input.parse()
+    }
+
+    /// Returns the intensity in candela for the given vertical and horizontal angle, both in
+    /// degrees.
+    pub fn candela(&self, vertical: f64, horizontal: f64) -> f64 {
+        let vertical_first = self.vertical_angles[0];
+        let vertical_last = *self.vertical_angles.last().unwrap();
+        if vertical < vertical_first || vertical > vertical_last {
+            return 0.;
+        }
+
+        let horizontal = self.fold_horizontal(horizontal);
+        let nb_vertical = self.vertical_angles.len();
+        let (h, th) = interval(&self.horizontal_angles, horizontal);
+        let (v, tv) = interval(&self.vertical_angles, vertical);
+
+        let value = |h: usize, v: usize| self.candela[h * nb_vertical + v];
+        let h1 = (h + 1).min(self.horizontal_angles.len() - 1);
+        let v1 = (v + 1).min(nb_vertical - 1);
+
+        (1. - th) * ((1. - tv) * value(h, v) + tv * value(h, v1))
+            + th * ((1. - tv) * value(h1, v) + tv * value(h1, v1))
+    }
+
+    /// Returns the total luminous flux in lm emitted according to this profile.
+    pub fn luminous_flux(&self) -> f64 {
+        const NB_VERTICAL: usize = 180;
+        const NB_HORIZONTAL: usize = 360;
+        let d_vertical = PI / NB_VERTICAL as f64;
+        let d_horizontal = 2. * PI / NB_HORIZONTAL as f64;
+
+        (0..NB_VERTICAL)
+            .map(|i| {
+                let vertical = (i as f64 + 0.5) * d_vertical;
+                let ring = (0..NB_HORIZONTAL)
+                    .map(|j| {
+                        let horizontal = (j as f64 + 0.5) * d_horizontal;
+                        self.candela(vertical.to_degrees(), horizontal.to_degrees())
+                    })
+                    .sum::<f64>();
+
+                ring * vertical.sin() * d_vertical * d_horizontal
+            })
+            .sum()
+    }
+
+    /// Maps a horizontal angle onto the range covered by the profile, taking into account the
+    /// symmetry implied by the last horizontal angle.
+    fn fold_horizontal(&self, horizontal: f64) -> f64 {
+        let horizontal = horizontal.rem_euclid(360.);
+        let last = *self.horizontal_angles.last().unwrap();
+
+        if last <= 0. {
+            // rotationally symmetric
+            0.
+        } else if last <= 90. {
+            // symmetric in each quadrant
+            let horizontal = if horizontal > 180. {
+                360. - horizontal
+            } else {
+                horizontal
+            };
+            if horizontal > 90. {
+                180. - horizontal
+            } else {
+                horizontal
+            }
+        } else if last <= 180. {
+            // bilaterally symmetric
+            if horizontal > 180. {
+                360. - horizontal
+            } else {
+                horizontal
+            }
+        } else {
+            horizontal
+        }
+    }
+}
+
+impl std::str::FromStr for IesProfile {
+    type Err = IesError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let tilt_start = input.find("TILT=").ok_or(IesError::MissingTilt)?;
+        let rest = &input[tilt_start + "TILT=".len()..];
+        let tilt_end = rest.find('\n').unwrap_or(rest.len());
+        let tilt = rest[..tilt_end].trim();
+
+        let mut numbers = rest[tilt_end..]
+            .split(|c: char| c.is_ascii_whitespace() || c == ',')
+            .filter(|s| !s.is_empty());
+        let mut next = || -> Result<f64, IesError> {
+            let token = numbers.next().ok_or(IesError::UnexpectedEnd)?;
+            token
+                .parse()
+                .map_err(|_| IesError::InvalidNumber(token.to_owned()))
+        };
+
+        match tilt {
+            "NONE" => {}
+            "INCLUDE" => {
+                // lamp-to-luminaire geometry, followed by the tilt angles and factors, which
+                // only matter for luminaires that are not mounted as measured.
+                let _geometry = next()?;
+                let nb_tilt_angles = next()? as usize;
+                for _ in 0..2 * nb_tilt_angles {
+                    next()?;
+                }
+            }
+            _ => return Err(IesError::UnsupportedTilt(tilt.to_owned())),
+        }
+
+        let _nb_lamps = next()?;
+        let _lumens_per_lamp = next()?;
+        let candela_multiplier = next()?;
+        let nb_vertical = next()? as usize;
+        let nb_horizontal = next()? as usize;
+        let photometric_type = next()?;
+        let _units_type = next()?;
+        let _width = next()?;
+        let _length = next()?;
+        let _height = next()?;
+        let ballast_factor = next()?;
+        let _ballast_lamp_photometric_factor = next()?;
+        let _input_watts = next()?;
+
+        if photometric_type as u8 != 1 {
+            return Err(IesError::UnsupportedPhotometricType(photometric_type as u8));
+        }
+        if nb_vertical == 0 || nb_horizontal == 0 {
+            return Err(IesError::UnexpectedEnd);
+        }
+
+        let vertical_angles = (0..nb_vertical).map(|_| next()).collect::<Result<_, _>>()?;
+        let horizontal_angles = (0..nb_horizontal)
+            .map(|_| next())
+            .collect::<Result<_, _>>()?;
+        let scale = candela_multiplier * ballast_factor;
+        let candela = (0..nb_vertical * nb_horizontal)
+            .map(|_| next().map(|candela| candela * scale))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+        })
+    }
+}
+
+/// Returns the index of the interval of `angles` that contains `x`, together with the relative
+/// position of `x` within that interval.
+fn interval(angles: &[f64], x: f64) -> (usize, f64) {
+    if angles.len() == 1 {
+        return (0, 0.);
+    }
+
+    let i = angles[1..angles.len() - 1]
+        .iter()
+        .take_while(|&&angle| angle <= x)
+        .count();
+    let t = (x - angles[i]) / (angles[i + 1] - angles[i]);
+
+    (i, t.clamp(0., 1.))
+}
+
+#[derive(Debug)]
+pub enum IesError {
+    Io(io::Error),
+    MissingTilt,
+    UnsupportedTilt(String),
+    UnsupportedPhotometricType(u8),
+    UnexpectedEnd,
+    InvalidNumber(String),
+}
+
+impl Display for IesError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            IesError::Io(err) => write!(f, "failed to read IES file: {}", err),
+            IesError::MissingTilt => write!(f, "missing TILT line"),
+            IesError::UnsupportedTilt(tilt) => write!(f, "unsupported TILT: {}", tilt),
+            IesError::UnsupportedPhotometricType(t) => {
+                write!(f, "unsupported photometric type: {}", t)
+            }
+            IesError::UnexpectedEnd => write!(f, "unexpected end of photometric data"),
+            IesError::InvalidNumber(token) => write!(f, "invalid number: {}", token),
+        }
+    }
+}
+
+impl Error for IesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IesError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IesError {
+    fn from(err: io::Error) -> Self {
+        IesError::Io(err)
+    }
+}
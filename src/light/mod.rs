@@ -0,0 +1,226 @@
+use nalgebra::Unit;
+
+use crate::film::Rgb;
+use crate::math::Ray;
+use crate::sampler::Sample;
+use crate::shade_rec::ShadeRec;
+use crate::shape::{Aabb, GeometricObject};
+use crate::{Point3, Vector};
+
+pub use ambient::{AmbientLight, AmbientOccluder};
+pub use area::AreaLight;
+pub use directional::DirectionalLight;
+pub use environment::EnvironmentLight;
+pub use goniometric::{GoniometricLight, IesProfile};
+pub use point::PointLight;
+pub use selection::{LightSelection, LightSelector};
+pub use spot::SpotLight;
+
+mod ambient;
+mod area;
+mod directional;
+mod environment;
+mod goniometric;
+mod point;
+mod selection;
+mod spot;
+
+/// Maximum luminous efficacy in lm/W, used to convert photometric quantities to radiometric ones.
+pub const LUMINOUS_EFFICACY: f64 = 683.;
+
+const SHADOW_EPSILON: f64 = 1e-6;
+
+/// Light that reaches the scene indirectly, approximating global illumination.
+pub trait Ambient {
+    fn radiance(&self, sr: &ShadeRec) -> Rgb;
+}
+
+pub trait Light {
+    /// Averages `f` over samples of this light, as seen from the hit point of `sr`.
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb;
+    /// Returns the sample of this light that would be reached from the hit point of `sr` along
+    /// `wi`, or `None` if this light can't be reached along `wi`.
+    fn sample_direction(&self, _sr: &ShadeRec, _wi: &Unit<Vector>) -> Option<LightSample<'_>> {
+        None
+    }
+    /// Returns the density with respect to solid angle of sampling `wi` with `average`.
+    fn pdf(&self, sr: &ShadeRec, wi: &Unit<Vector>) -> f64 {
+        self.sample_direction(sr, wi)
+            .map_or(0., |light_sample| light_sample.pdf)
+    }
+    /// Returns whether this light can only be sampled in a single way, in which case it can't be
+    /// reached by sampling any other direction.
+    fn is_delta(&self) -> bool {
+        true
+    }
+    /// Returns the radiance this light contributes along a ray that escapes the scene.
+    fn background(&self, _ray: &Ray) -> Rgb {
+        Rgb::black()
+    }
+    /// Samples a ray leaving this light. Returns `None` for lights that can't be sampled this
+    /// way, such as lights infinitely far away.
+    fn sample_emission(&self, _position: Sample, _direction: Sample) -> Option<Emission> {
+        None
+    }
+    /// Returns the densities with which `sample_emission` samples a ray leaving the light from a
+    /// point with the given surface normal, along `direction`. The density of the point is with
+    /// respect to area, 1 for lights emitting from a single point, and the density of the
+    /// direction is with respect to solid angle.
+    fn pdf_emission(&self, _normal: &Vector, _direction: &Vector) -> (f64, f64) {
+        (0., 0.)
+    }
+    /// Returns the total power emitted by this light, in W. Lights without bounds may return
+    /// black, as their power isn't used to select them.
+    fn power(&self) -> Rgb;
+    /// Returns the bounds of the emitting part of this light, or `None` if it is infinitely far
+    /// away.
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+    fn geometric_object(&self) -> Option<GeometricObject> {
+        None
+    }
+}
+
+impl<T: Light> Light for Box<T> {
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        (**self).average(sr, f)
+    }
+
+    fn sample_direction(&self, sr: &ShadeRec, wi: &Unit<Vector>) -> Option<LightSample<'_>> {
+        (**self).sample_direction(sr, wi)
+    }
+
+    fn pdf(&self, sr: &ShadeRec, wi: &Unit<Vector>) -> f64 {
+        (**self).pdf(sr, wi)
+    }
+
+    fn is_delta(&self) -> bool {
+        (**self).is_delta()
+    }
+
+    fn background(&self, ray: &Ray) -> Rgb {
+        (**self).background(ray)
+    }
+
+    fn sample_emission(&self, position: Sample, direction: Sample) -> Option<Emission> {
+        (**self).sample_emission(position, direction)
+    }
+
+    fn pdf_emission(&self, normal: &Vector, direction: &Vector) -> (f64, f64) {
+        (**self).pdf_emission(normal, direction)
+    }
+
+    fn power(&self) -> Rgb {
+        (**self).power()
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        (**self).bounds()
+    }
+
+    fn geometric_object(&self) -> Option<GeometricObject> {
+        (**self).geometric_object()
+    }
+}
+
+/// A ray of light leaving a light.
+#[derive(Debug, Clone)]
+pub struct Emission {
+    pub ray: Ray,
+    // normal of the emitting surface at the origin of the ray, if the light has a surface
+    pub normal: Option<Unit<Vector>>,
+    // flux carried by the ray divided by the density of having sampled it
+    pub flux: Rgb,
+    // densities of the origin with respect to area and of the direction with respect to solid
+    // angle, as returned by `pdf_emission`
+    pub pdf_position: f64,
+    pub pdf_direction: f64,
+}
+
+/// Where a light sample originates from.
+#[derive(Debug, Copy, Clone)]
+enum LightPosition {
+    /// A point somewhere in the scene.
+    Point(Point3),
+    /// A point on an emitting surface, with the normal of the surface.
+    Surface(Point3, Unit<Vector>),
+    /// A light infinitely far away, in the given direction as seen from the scene.
+    Infinite(Unit<Vector>),
+}
+
+pub struct LightSample<'a> {
+    light: &'a dyn Light,
+    position: LightPosition,
+    // radiance arriving at the hit point from this sample
+    radiance: Rgb,
+    // probability density of this sample with respect to solid angle, 1 for lights that can
+    // only be sampled in a single way.
+    pdf: f64,
+}
+
+impl<'a> LightSample<'a> {
+    /// Creates a sample of a light emitting `intensity` from a single point, taking into account
+    /// the inverse square falloff towards the hit point of `sr`.
+    fn point(light: &'a dyn Light, location: Point3, intensity: Rgb, sr: &ShadeRec) -> Self {
+        let distance_squared = (location - sr.hit_point).norm_squared();
+
+        Self {
+            light,
+            position: LightPosition::Point(location),
+            radiance: intensity / distance_squared,
+            pdf: 1.,
+        }
+    }
+
+    pub fn direction(&self, sr: &ShadeRec) -> Unit<Vector> {
+        match self.position {
+            LightPosition::Point(location) | LightPosition::Surface(location, _) => {
+                Unit::new_normalize(location - sr.hit_point)
+            }
+            LightPosition::Infinite(direction) => direction,
+        }
+    }
+
+    /// Returns the point this sample originates from, or `None` for lights infinitely far away.
+    pub fn location(&self) -> Option<Point3> {
+        match self.position {
+            LightPosition::Point(location) | LightPosition::Surface(location, _) => Some(location),
+            LightPosition::Infinite(_) => None,
+        }
+    }
+
+    /// Returns the normal of the emitting surface at the point this sample originates from, or
+    /// `None` if the light has no surface.
+    pub fn normal(&self) -> Option<Unit<Vector>> {
+        match self.position {
+            LightPosition::Surface(_, normal) => Some(normal),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> LightSample<'a> {
+    pub fn light(&self) -> &dyn Light {
+        self.light
+    }
+
+    pub fn radiance(&self) -> Rgb {
+        self.radiance
+    }
+
+    pub fn pdf(&self) -> f64 {
+        self.pdf
+    }
+
+    pub fn visible(&self, ray: &Ray, sr: &ShadeRec) -> bool {
+        match self.position {
+            LightPosition::Point(location) | LightPosition::Surface(location, _) => {
+                // avoids the light itself being reported as an occluder
+                let max_t = (1. - SHADOW_EPSILON) * (location - ray.origin()).norm();
+                !sr.world.hit_any_object_where(ray, |hit| hit.t < max_t)
+            }
+            LightPosition::Infinite(_) => !sr.world.hit_any_object_where(ray, |_| true),
+        }
+    }
+}
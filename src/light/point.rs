@@ -0,0 +1,72 @@
+use std::f64::consts::PI;
+
+use crate::film::Rgb;
+use crate::light::{Emission, Light, LightSample, LUMINOUS_EFFICACY};
+use crate::material::Emissive;
+use crate::math::Ray;
+use crate::sampler::{uniform_sample_sphere, Sample};
+use crate::shade_rec::ShadeRec;
+use crate::shape::Aabb;
+use crate::{Point3, Vector};
+
+/// An isotropic point light, of which the emitted light falls off with the squared distance.
+pub struct PointLight {
+    location: Point3,
+    // radiant intensity, in W/sr
+    material: Emissive,
+}
+
+impl PointLight {
+    /// Creates a new point light with the given radiant intensity in W/sr.
+    pub fn new(intensity: f64, color: Rgb, location: Point3) -> Self {
+        let material = Emissive::new(intensity, color);
+
+        Self { location, material }
+    }
+
+    pub fn white(intensity: f64, location: Point3) -> Self {
+        Self::new(intensity, Rgb::white(), location)
+    }
+
+    /// Creates a new point light that emits a total radiant power of `power` W.
+    pub fn from_power(power: f64, color: Rgb, location: Point3) -> Self {
+        Self::new(power / (4. * PI), color, location)
+    }
+
+    /// Creates a new point light that emits a total luminous flux of `flux` lm.
+    pub fn from_luminous_flux(flux: f64, color: Rgb, location: Point3) -> Self {
+        Self::from_power(flux / LUMINOUS_EFFICACY, color, location)
+    }
+}
+
+impl Light for PointLight {
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        let intensity = self.material.ce * self.material.ls;
+
+        f(LightSample::point(self, self.location, intensity, sr))
+    }
+
+    fn sample_emission(&self, _position: Sample, direction: Sample) -> Option<Emission> {
+        let direction = uniform_sample_sphere(direction);
+
+        Some(Emission {
+            ray: Ray::new(self.location, direction),
+            normal: None,
+            flux: self.power(),
+            pdf_position: 1.,
+            pdf_direction: 1. / (4. * PI),
+        })
+    }
+
+    fn pdf_emission(&self, _normal: &Vector, _direction: &Vector) -> (f64, f64) {
+        (1., 1. / (4. * PI))
+    }
+
+    fn power(&self) -> Rgb {
+        self.material.ce * (4. * PI * self.material.ls)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.location, self.location))
+    }
+}
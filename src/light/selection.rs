@@ -0,0 +1,243 @@
+use rand::prelude::*;
+
+use crate::film::Rgb;
+use crate::light::Light;
+use crate::math::Distribution1D;
+use crate::shade_rec::ShadeRec;
+use crate::shape::{Aabb, Union};
+use crate::{Point3, Vector};
+
+/// Strategy used to decide which lights are sampled at a shading point.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LightSelection {
+    /// Samples every light at every shading point.
+    All,
+    /// Samples a single light, chosen uniformly.
+    Uniform,
+    /// Samples a single light, chosen with a probability proportional to its power.
+    Power,
+    /// Samples a single light, chosen by traversing a hierarchy of lights according to their
+    /// estimated contribution at the shading point.
+    Bvh,
+}
+
+impl Default for LightSelection {
+    fn default() -> Self {
+        LightSelection::All
+    }
+}
+
+/// Selects the lights of a world to sample at a shading point, according to a `LightSelection`.
+pub struct LightSelector<'a> {
+    lights: &'a [Box<dyn Light + Sync>],
+    // lights without bounds, which are always selected uniformly
+    infinite: Vec<usize>,
+    strategy: Strategy,
+}
+
+enum Strategy {
+    All,
+    Uniform,
+    // distribution over the power of the bounded lights, given by their indices
+    Power(Option<(Distribution1D, Vec<usize>)>),
+    Bvh(Option<LightNode>),
+}
+
+impl<'a> LightSelector<'a> {
+    pub fn new(lights: &'a [Box<dyn Light + Sync>], selection: LightSelection) -> Self {
+        let (bounded, infinite): (Vec<_>, Vec<_>) = (0..lights.len())
+            .map(|i| (i, lights[i].bounds()))
+            .partition(|(_, bounds)| bounds.is_some());
+        let infinite = infinite.into_iter().map(|(i, _)| i).collect();
+        let bounded = bounded
+            .into_iter()
+            .map(|(i, bounds)| (i, bounds.unwrap(), lights[i].power().luminance()))
+            .collect::<Vec<_>>();
+
+        let strategy = match selection {
+            LightSelection::All => Strategy::All,
+            LightSelection::Uniform => Strategy::Uniform,
+            LightSelection::Power if bounded.is_empty() => Strategy::Power(None),
+            LightSelection::Power => {
+                let func = bounded.iter().map(|&(_, _, power)| power).collect();
+                let indices = bounded.iter().map(|&(i, _, _)| i).collect();
+                Strategy::Power(Some((Distribution1D::new(func), indices)))
+            }
+            LightSelection::Bvh if bounded.is_empty() => Strategy::Bvh(None),
+            LightSelection::Bvh => {
+                let leaves = bounded
+                    .into_iter()
+                    .map(|(index, bbox, power)| LightNode {
+                        bbox,
+                        power,
+                        kind: LightNodeKind::Leaf(index),
+                    })
+                    .collect();
+                Strategy::Bvh(Some(LightNode::build(leaves)))
+            }
+        };
+
+        Self {
+            lights,
+            infinite,
+            strategy,
+        }
+    }
+
+    /// Estimates the sum of `f` over all lights at the hit point of `sr`, by evaluating `f` for
+    /// the selected lights and dividing by the probability of having selected them.
+    pub fn estimate<F>(&self, sr: &ShadeRec, f: F) -> Rgb
+    where
+        F: Fn(&dyn Light) -> Rgb,
+    {
+        match self.strategy {
+            Strategy::All => self.lights.iter().map(|light| f(light.as_ref())).sum(),
+            _ => match self.select(sr, thread_rng().gen()) {
+                Some((index, probability)) => f(self.lights[index].as_ref()) / probability,
+                None => Rgb::black(),
+            },
+        }
+    }
+
+    /// Selects a single light, returning its index and the probability of having selected it.
+    fn select(&self, sr: &ShadeRec, u: f64) -> Option<(usize, f64)> {
+        if self.lights.is_empty() {
+            return None;
+        }
+
+        let has_bounded = self.infinite.len() < self.lights.len();
+        let (u, p_bounded) = match self.strategy {
+            Strategy::All | Strategy::Uniform => {
+                let n = self.lights.len();
+                let index = ((u * n as f64) as usize).min(n - 1);
+                return Some((index, 1. / n as f64));
+            }
+            _ => {
+                // the bounded lights as a whole get the same share as every infinite light
+                let nb_choices = self.infinite.len() + has_bounded as usize;
+                let p = 1. / nb_choices as f64;
+                let choice = ((u * nb_choices as f64) as usize).min(nb_choices - 1);
+
+                if choice < self.infinite.len() {
+                    return Some((self.infinite[choice], p));
+                }
+                (u * nb_choices as f64 - choice as f64, p)
+            }
+        };
+
+        let (index, probability) = match &self.strategy {
+            Strategy::Power(Some((distribution, indices))) => {
+                let (i, pmf, _) = distribution.sample_discrete(u);
+                (indices[i], pmf)
+            }
+            Strategy::Bvh(Some(root)) => root.select(&sr.hit_point, &sr.normal, u)?,
+            _ => return None,
+        };
+
+        if probability > 0. {
+            Some((index, p_bounded * probability))
+        } else {
+            None
+        }
+    }
+}
+
+/// Node of a bounding volume hierarchy over the bounded lights of a world.
+struct LightNode {
+    bbox: Aabb,
+    // luminance of the total power emitted by the lights in this node
+    power: f64,
+    kind: LightNodeKind,
+}
+
+enum LightNodeKind {
+    Leaf(usize),
+    Interior(Box<[LightNode; 2]>),
+}
+
+impl LightNode {
+    /// Builds a hierarchy over the given leaves by recursively splitting them at the median of
+    /// their centroids along the longest axis.
+    fn build(mut nodes: Vec<LightNode>) -> LightNode {
+        if nodes.len() == 1 {
+            return nodes.pop().unwrap();
+        }
+
+        let centroids = nodes.iter().fold(Aabb::default(), |bbox, node| {
+            bbox.union(node.bbox.centroid())
+        });
+        let (axis, _) = centroids.longest_axis();
+        nodes.sort_by(|a, b| {
+            a.bbox.centroid()[axis]
+                .partial_cmp(&b.bbox.centroid()[axis])
+                .unwrap()
+        });
+
+        let right = nodes.split_off(nodes.len() / 2);
+        let left = Self::build(nodes);
+        let right = Self::build(right);
+
+        LightNode {
+            bbox: left.bbox.union(right.bbox),
+            power: left.power + right.power,
+            kind: LightNodeKind::Interior(Box::new([left, right])),
+        }
+    }
+
+    /// Estimates the contribution of the lights in this node at `point`, with surface normal
+    /// `normal`, which is zero for points inside a medium. Only returns zero if none of the
+    /// lights can illuminate the point.
+    fn importance(&self, point: &Point3, normal: &Vector) -> f64 {
+        if *normal != Vector::zeros()
+            && self
+                .bbox
+                .vertices()
+                .iter()
+                .all(|vertex| (vertex - point).dot(normal) <= 0.)
+        {
+            return 0.;
+        }
+
+        let diagonal_squared = (self.bbox.p1 - self.bbox.p0).norm_squared();
+        let distance_squared = (self.bbox.centroid() - point)
+            .norm_squared()
+            .max(0.25 * diagonal_squared);
+
+        if distance_squared > 0. {
+            self.power / distance_squared
+        } else {
+            self.power
+        }
+    }
+
+    /// Selects a leaf of this node, returning the index of its light and the probability of
+    /// having selected it.
+    fn select(&self, point: &Point3, normal: &Vector, mut u: f64) -> Option<(usize, f64)> {
+        let mut node = self;
+        let mut probability = 1.;
+
+        loop {
+            match &node.kind {
+                LightNodeKind::Leaf(index) => return Some((*index, probability)),
+                LightNodeKind::Interior(children) => {
+                    let left = children[0].importance(point, normal);
+                    let right = children[1].importance(point, normal);
+                    if left + right <= 0. {
+                        return None;
+                    }
+
+                    let p_left = left / (left + right);
+                    if u < p_left {
+                        u = (u / p_left).min(1. - f64::EPSILON);
+                        probability *= p_left;
+                        node = &children[0];
+                    } else {
+                        u = ((u - p_left) / (1. - p_left)).min(1. - f64::EPSILON);
+                        probability *= 1. - p_left;
+                        node = &children[1];
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use nalgebra::Unit;
+use std::f64::consts::PI;
+
+use crate::film::Rgb;
+use crate::light::{Emission, Light, LightSample, LUMINOUS_EFFICACY};
+use crate::material::Emissive;
+use crate::math::{OrthonormalBasis, Ray};
+use crate::sampler::{uniform_sample_cone, Sample};
+use crate::shade_rec::ShadeRec;
+use crate::shape::Aabb;
+use crate::{Point3, Vector};
+
+/// A point light that only emits light within a cone around `direction`. The emitted light falls
+/// off with the squared distance.
+pub struct SpotLight {
+    location: Point3,
+    direction: Unit<Vector>,
+    // cosine of the angle at which the emitted light reaches zero
+    cos_total_width: f64,
+    // cosine of the angle at which the emitted light starts to fall off
+    cos_falloff_start: f64,
+    // radiant intensity along the central axis, in W/sr
+    material: Emissive,
+}
+
+impl SpotLight {
+    /// Creates a new spot light shining from `location` towards `direction`, with the given
+    /// radiant intensity in W/sr along its central axis. The cone angle and the angle at which the
+    /// falloff starts are both half angles, expressed in degrees.
+    pub fn new(
+        intensity: f64,
+        color: Rgb,
+        location: Point3,
+        direction: Vector,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        assert!(cone_angle > 0. && cone_angle <= 180.);
+        assert!(falloff_start >= 0. && falloff_start <= cone_angle);
+
+        let direction = Unit::new_normalize(direction);
+        let cos_total_width = cone_angle.to_radians().cos();
+        let cos_falloff_start = falloff_start.to_radians().cos();
+        let material = Emissive::new(intensity, color);
+
+        Self {
+            location,
+            direction,
+            cos_total_width,
+            cos_falloff_start,
+            material,
+        }
+    }
+
+    pub fn white(
+        intensity: f64,
+        location: Point3,
+        direction: Vector,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        Self::new(
+            intensity,
+            Rgb::white(),
+            location,
+            direction,
+            cone_angle,
+            falloff_start,
+        )
+    }
+
+    /// Creates a new spot light that emits a total radiant power of `power` W.
+    pub fn from_power(
+        power: f64,
+        color: Rgb,
+        location: Point3,
+        direction: Vector,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        let mut light = Self::new(1., color, location, direction, cone_angle, falloff_start);
+        light.material.ls = power / light.solid_angle();
+        light
+    }
+
+    /// Creates a new spot light that emits a total luminous flux of `flux` lm.
+    pub fn from_luminous_flux(
+        flux: f64,
+        color: Rgb,
+        location: Point3,
+        direction: Vector,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        Self::from_power(
+            flux / LUMINOUS_EFFICACY,
+            color,
+            location,
+            direction,
+            cone_angle,
+            falloff_start,
+        )
+    }
+
+    /// Returns the solid angle of the cone of this light, weighted by its falloff, such that the
+    /// emitted power is this times the intensity along the axis.
+    fn solid_angle(&self) -> f64 {
+        // the falloff integrates over the band to a fifth of its width in cosines
+        let band = self.cos_falloff_start - self.cos_total_width;
+        2. * PI * ((1. - self.cos_falloff_start) + band / 5.)
+    }
+
+    /// Returns the fraction of the light that is emitted along `w`, a normalized direction
+    /// pointing away from the light.
+    fn falloff(&self, w: &Unit<Vector>) -> f64 {
+        let cos_theta = w.dot(&self.direction);
+
+        if cos_theta < self.cos_total_width {
+            0.
+        } else if cos_theta >= self.cos_falloff_start {
+            1.
+        } else {
+            let delta = (cos_theta - self.cos_total_width)
+                / (self.cos_falloff_start - self.cos_total_width);
+            delta.powi(4)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn average(&self, sr: &ShadeRec, f: &dyn Fn(LightSample) -> Rgb) -> Rgb {
+        let w = Unit::new_normalize(sr.hit_point - self.location);
+        let intensity = self.material.ce * self.material.ls * self.falloff(&w);
+
+        f(LightSample::point(self, self.location, intensity, sr))
+    }
+
+    fn sample_emission(&self, _position: Sample, direction: Sample) -> Option<Emission> {
+        let basis = OrthonormalBasis::from_vector(&self.direction)?;
+        let d = uniform_sample_cone(direction, self.cos_total_width);
+        let w = Unit::new_normalize(d.x * *basis.u + d.y * *basis.v + d.z * *basis.w);
+        let intensity = self.material.ce * (self.material.ls * self.falloff(&w));
+        let pdf_direction = 1. / (2. * PI * (1. - self.cos_total_width));
+
+        Some(Emission {
+            ray: Ray::new(self.location, *w),
+            normal: None,
+            flux: intensity / pdf_direction,
+            pdf_position: 1.,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, _normal: &Vector, direction: &Vector) -> (f64, f64) {
+        if direction.dot(&self.direction) < self.cos_total_width * direction.norm() {
+            (1., 0.)
+        } else {
+            (1., 1. / (2. * PI * (1. - self.cos_total_width)))
+        }
+    }
+
+    fn power(&self) -> Rgb {
+        self.material.ce * (self.material.ls * self.solid_angle())
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.location, self.location))
+    }
+}
@@ -1,5 +1,6 @@
-use crate::brdf::{Lambertian, SvLambertian};
+use crate::brdf::{Dielectric, Lambertian, PerfectSpecular, SvLambertian};
 use crate::film::Rgb;
+use crate::medium::{HenyeyGreenstein, HomogeneousMedium};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Emissive {
@@ -16,6 +17,47 @@ impl Emissive {
     }
 }
 
+/// Light entering a closed shape, scattering inside it and leaving it elsewhere, as in skin, wax
+/// or marble. Only `VolumetricPathTracing` follows the light inside the shape, with a random walk
+/// through a homogeneous medium, other renderers shade the surface as if it were matte.
+#[derive(Debug, Clone, Copy)]
+pub struct Subsurface {
+    // approximates the light reflected by the surface, for renderers without random walks
+    pub(crate) diffuse_brdf: Lambertian,
+    pub(crate) medium: HomogeneousMedium,
+}
+
+impl Subsurface {
+    /// Creates a new subsurface scattering material of which the diffuse reflectance is roughly
+    /// `color`, where light travels on average `mean_free_path` m per color channel below the
+    /// surface before leaving it.
+    pub fn new(color: Rgb, mean_free_path: Rgb, phase_function: HenyeyGreenstein) -> Self {
+        // the single scattering albedo and the scale of the mean free path of the medium that
+        // reflects `color` with a random walk, as fitted by Chiang et al. in "Practical and
+        // Controllable Subsurface Scattering for Production Path Tracing"
+        let fit = |color: f64| {
+            let color = color.clamp(0., 1.);
+            let albedo = 1.
+                - (4.09712 + 4.20863 * color
+                    - (9.59217 + 41.6808 * color + 17.7126 * color * color).sqrt())
+                .powi(2);
+            (albedo, 1.9 - color + 3.5 * (color - 0.8).powi(2))
+        };
+        let (red, green, blue) = (fit(color.red()), fit(color.green()), fit(color.blue()));
+        let albedo = Rgb::new(red.0, green.0, blue.0);
+        let scale = Rgb::new(red.1, green.1, blue.1);
+
+        Self {
+            diffuse_brdf: Lambertian::new(1., color),
+            medium: HomogeneousMedium::from_mean_free_path(
+                mean_free_path * scale,
+                albedo,
+                phase_function,
+            ),
+        }
+    }
+}
+
 pub enum Material {
     // Perfect diffuse reflection
     Matte {
@@ -27,4 +69,10 @@ pub enum Material {
         ambient_brdf: SvLambertian,
         diffuse_brdf: SvLambertian,
     },
+    // Perfect specular reflection
+    Mirror(PerfectSpecular),
+    // Perfect specular reflection and refraction
+    Glass(Dielectric),
+    // Scattering below the surface of a closed shape
+    Subsurface(Subsurface),
 }
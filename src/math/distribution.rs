@@ -0,0 +1,158 @@
+use crate::sampler::Sample;
+use crate::Point2;
+
+/// A piecewise-constant one-dimensional distribution, defined by a non-negative function over
+/// equally sized intervals.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    func: Vec<f64>,
+    // cumulative distribution, with `func.len() + 1` entries starting at 0
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f64>) -> Self {
+        assert!(!func.is_empty());
+
+        let n = func.len() as f64;
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        cdf.push(0.);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + f / n);
+        }
+
+        let integral = cdf[func.len()];
+        if integral == 0. {
+            // falls back to a uniform distribution
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / n);
+        } else {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Samples a point in `[0, 1)` according to this distribution. Returns the point, the
+    /// density of having sampled it and the index of the interval it lies in.
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        let i = self.find_interval(u);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let du = if width > 0. {
+            (u - self.cdf[i]) / width
+        } else {
+            0.
+        };
+        let x = ((i as f64 + du) / self.count() as f64).min(1. - f64::EPSILON);
+
+        (x, self.pdf(x), i)
+    }
+
+    /// Returns the density of sampling `x`, in `[0, 1)`, with `sample_continuous`.
+    pub fn pdf(&self, x: f64) -> f64 {
+        let i = ((x * self.count() as f64) as usize).min(self.count() - 1);
+
+        if self.integral > 0. {
+            self.func[i] / self.integral
+        } else {
+            1.
+        }
+    }
+
+    /// Samples one of the intervals with a probability proportional to its value. Returns its
+    /// index, the probability of having sampled it and `u` remapped to `[0, 1)` within the
+    /// interval, so it can be reused.
+    pub fn sample_discrete(&self, u: f64) -> (usize, f64, f64) {
+        let i = self.find_interval(u);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let u_remapped = ((u - self.cdf[i]) / width).clamp(0., 1. - f64::EPSILON);
+
+        (i, self.discrete_pdf(i), u_remapped)
+    }
+
+    /// Returns the probability of sampling the interval at `index` with `sample_discrete`.
+    pub fn discrete_pdf(&self, index: usize) -> f64 {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+
+    /// Returns the index of the last entry of the cdf that is not larger than `u`, clamped to
+    /// the valid intervals.
+    fn find_interval(&self, u: f64) -> usize {
+        let (mut low, mut high) = (0, self.func.len());
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if self.cdf[mid] <= u {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        low
+    }
+}
+
+/// A piecewise-constant two-dimensional distribution over the unit square, defined by a
+/// non-negative function over a grid of equally sized cells.
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    // distribution of u within each row
+    conditionals: Vec<Distribution1D>,
+    // distribution of the rows
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Creates a new distribution from the values of `func`, stored row by row with `nu` values
+    /// per row.
+    pub fn new(func: &[f64], nu: usize) -> Self {
+        assert_eq!(func.len() % nu, 0);
+
+        let conditionals = func
+            .chunks_exact(nu)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect::<Vec<_>>();
+        let marginal = Distribution1D::new(
+            conditionals
+                .iter()
+                .map(|conditional| conditional.integral())
+                .collect(),
+        );
+
+        Self {
+            conditionals,
+            marginal,
+        }
+    }
+
+    /// Samples a point in the unit square according to this distribution. Returns the point and
+    /// the density of having sampled it.
+    pub fn sample_continuous(&self, sample: Sample) -> (Point2, f64) {
+        let (v, pdf_v, row) = self.marginal.sample_continuous(sample.1);
+        let (u, pdf_u, _) = self.conditionals[row].sample_continuous(sample.0);
+
+        (Point2::new(u, v), pdf_u * pdf_v)
+    }
+
+    /// Returns the density of sampling `p` with `sample_continuous`.
+    pub fn pdf(&self, p: &Point2) -> f64 {
+        let nv = self.conditionals.len();
+        let row = ((p.y * nv as f64) as usize).min(nv - 1);
+
+        self.marginal.pdf(p.y) * self.conditionals[row].pdf(p.x)
+    }
+}
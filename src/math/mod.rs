@@ -1,7 +1,11 @@
+pub use distribution::{Distribution1D, Distribution2D};
 pub use orthonormal_basis::OrthonormalBasis;
 pub use ray::Ray;
+pub use solve::{quadratic, quartic};
 pub use transformation::{Transformable, Transformation};
 
+mod distribution;
 mod orthonormal_basis;
 mod ray;
+mod solve;
 mod transformation;
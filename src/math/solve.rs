@@ -0,0 +1,93 @@
+/// Returns the real roots of `a x^2 + b x + c` in ascending order, if any.
+pub fn quadratic(a: f64, b: f64, c: f64) -> Option<[f64; 2]> {
+    let disc = b * b - 4. * a * c;
+    if disc < 0. {
+        return None;
+    }
+
+    let root_disc = disc.sqrt();
+    // avoids the cancellation between -b and the root of the discriminant
+    let q = if b < 0. {
+        -0.5 * (b - root_disc)
+    } else {
+        -0.5 * (b + root_disc)
+    };
+
+    let t0 = q / a;
+    let t1 = c / q;
+
+    if t0 > t1 {
+        Some([t1, t0])
+    } else {
+        Some([t0, t1])
+    }
+}
+
+/// Returns the real roots of `a x^4 + b x^3 + c x^2 + d x + e` in ascending order, where `a`
+/// isn't zero. The roots are found with Ferrari's method and refined with Newton's method.
+pub fn quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // substituting x = y - b / 4 gives y^4 + p y^2 + q y + r
+    let b2 = b * b;
+    let p = c - 3. / 8. * b2;
+    let q = d - 0.5 * b * c + 0.125 * b2 * b;
+    let r = e - 0.25 * b * d + b2 * c / 16. - 3. / 256. * b2 * b2;
+
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // biquadratic in y
+        if let Some(zs) = quadratic(1., p, r) {
+            for &z in &zs {
+                if z >= 0. {
+                    roots.push(z.sqrt());
+                    roots.push(-z.sqrt());
+                }
+            }
+        }
+    } else {
+        // a positive root of the resolvent cubic turns both sides of
+        // (y^2 + p / 2 + m)^2 = 2 m y^2 - q y + m^2 + m p + p^2 / 4 - r into squares
+        let m = largest_cubic_root(p, 0.25 * p * p - r, -0.125 * q * q);
+        let s = (2. * m).max(0.).sqrt();
+        if s > 0. {
+            let offset = 0.5 * p + m;
+            let quadratics = [(-s, offset + 0.5 * q / s), (s, offset - 0.5 * q / s)];
+            for &(linear, constant) in &quadratics {
+                if let Some(ys) = quadratic(1., linear, constant) {
+                    roots.extend_from_slice(&ys);
+                }
+            }
+        }
+    }
+
+    let f = |x: f64| (((x + b) * x + c) * x + d) * x + e;
+    let df = |x: f64| ((4. * x + 3. * b) * x + 2. * c) * x + d;
+    for root in &mut roots {
+        *root -= 0.25 * b;
+        for _ in 0..2 {
+            let slope = df(*root);
+            if slope != 0. {
+                *root -= f(*root) / slope;
+            }
+        }
+    }
+
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots
+}
+
+/// Returns the largest real root of `x^3 + a x^2 + b x + c`.
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    let q = (3. * b - a * a) / 9.;
+    let r = (9. * a * b - 27. * c - 2. * a * a * a) / 54.;
+    let disc = q * q * q + r * r;
+
+    if disc >= 0. {
+        let root_disc = disc.sqrt();
+        (r + root_disc).cbrt() + (r - root_disc).cbrt() - a / 3.
+    } else {
+        let theta = (r / (-q * q * q).sqrt()).clamp(-1., 1.).acos();
+        2. * (-q).sqrt() * (theta / 3.).cos() - a / 3.
+    }
+}
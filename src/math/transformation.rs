@@ -2,7 +2,7 @@ use crate::{Point3, Vector};
 use nalgebra as na;
 use nalgebra::{Affine3, Matrix4, Rotation3, Translation3, Unit};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Transformation {
     matrix: Affine3<f64>,
     inverse: Affine3<f64>,
@@ -87,6 +87,16 @@ impl Transformation {
         Self { matrix, inverse }
     }
 
+    /// Creates a transformation from its homogeneous matrix, or `None` if it isn't invertible.
+    pub fn from_matrix(matrix: Matrix4<f64>) -> Option<Self> {
+        let inverse = matrix.try_inverse()?;
+
+        Some(Self {
+            matrix: Affine3::from_matrix_unchecked(matrix),
+            inverse: Affine3::from_matrix_unchecked(inverse),
+        })
+    }
+
     pub fn matrix(&self) -> &Affine3<f64> {
         &self.matrix
     }
@@ -0,0 +1,205 @@
+use nalgebra::Unit;
+use rand::prelude::*;
+
+use crate::film::Rgb;
+use crate::math::{Ray, Transformation};
+use crate::medium::{HenyeyGreenstein, Medium, MediumSample};
+use crate::shape::Aabb;
+use crate::Point3;
+
+/// Densities sampled on a regular grid over the unit cube, interpolated trilinearly in between.
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    // densities with x varying fastest, then y, then z
+    densities: Vec<f64>,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    pub fn new(nx: usize, ny: usize, nz: usize, densities: Vec<f64>) -> Self {
+        assert_eq!(densities.len(), nx * ny * nz);
+        assert!(densities.iter().all(|&density| density >= 0.));
+
+        let max_density = densities.iter().cloned().fold(0., f64::max);
+
+        Self {
+            nx,
+            ny,
+            nz,
+            densities,
+            max_density,
+        }
+    }
+
+    /// Creates a grid of which the densities are given by `f` at the centers of the cells.
+    pub fn from_fn<F>(nx: usize, ny: usize, nz: usize, f: F) -> Self
+    where
+        F: Fn(Point3) -> f64,
+    {
+        let mut densities = Vec::with_capacity(nx * ny * nz);
+        for z in 0..nz {
+            for y in 0..ny {
+                for x in 0..nx {
+                    densities.push(f(Point3::new(
+                        (x as f64 + 0.5) / nx as f64,
+                        (y as f64 + 0.5) / ny as f64,
+                        (z as f64 + 0.5) / nz as f64,
+                    )));
+                }
+            }
+        }
+
+        Self::new(nx, ny, nz, densities)
+    }
+
+    /// Returns the interpolated density at `point`, zero outside the unit cube.
+    pub fn density(&self, point: &Point3) -> f64 {
+        if point.iter().any(|c| !(0. ..=1.).contains(c)) {
+            return 0.;
+        }
+
+        let x = point.x * self.nx as f64 - 0.5;
+        let y = point.y * self.ny as f64 - 0.5;
+        let z = point.z * self.nz as f64 - 0.5;
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (dx, dy, dz) = (x - x0, y - y0, z - z0);
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let d = |i: i64, j: i64, k: i64| self.value(x0 + i, y0 + j, z0 + k);
+        let lerp = |t: f64, a: f64, b: f64| (1. - t) * a + t * b;
+
+        let d00 = lerp(dx, d(0, 0, 0), d(1, 0, 0));
+        let d10 = lerp(dx, d(0, 1, 0), d(1, 1, 0));
+        let d01 = lerp(dx, d(0, 0, 1), d(1, 0, 1));
+        let d11 = lerp(dx, d(0, 1, 1), d(1, 1, 1));
+
+        lerp(dz, lerp(dy, d00, d10), lerp(dy, d01, d11))
+    }
+
+    /// Returns the density at the given cell, clamped to the cells at the border of the grid.
+    fn value(&self, x: i64, y: i64, z: i64) -> f64 {
+        let clamp = |i: i64, n: usize| i.max(0).min(n as i64 - 1) as usize;
+        let (x, y, z) = (clamp(x, self.nx), clamp(y, self.ny), clamp(z, self.nz));
+
+        self.densities[(z * self.ny + y) * self.nx + x]
+    }
+}
+
+/// A medium of which the density varies over a grid, such as smoke. The extinction coefficient
+/// is the same for all color channels, so the medium can be sampled with delta tracking.
+pub struct GridMedium {
+    // extinction coefficient at density 1, in 1/m
+    sigma_t: f64,
+    // fraction of the interactions that scatter the light rather than absorbing it
+    albedo: Rgb,
+    phase_function: HenyeyGreenstein,
+    grid: DensityGrid,
+    // maps the unit cube over which the grid is defined into the scene
+    transformation: Transformation,
+}
+
+impl GridMedium {
+    pub fn new(
+        sigma_t: f64,
+        albedo: Rgb,
+        phase_function: HenyeyGreenstein,
+        grid: DensityGrid,
+        transformation: Transformation,
+    ) -> Self {
+        Self {
+            sigma_t,
+            albedo,
+            phase_function,
+            grid,
+            transformation,
+        }
+    }
+
+    /// Returns the range of distances along the normalized `ray` that lies inside the grid,
+    /// up to `max_distance`.
+    fn range(&self, ray: &Ray, max_distance: f64) -> Option<(f64, f64)> {
+        let bounds = Aabb::new(Point3::origin(), Point3::new(1., 1., 1.));
+        let local_ray = self.transformation.apply_inverse(ray);
+        let (t0, t1) = bounds.intersect_range(&local_ray)?;
+        let (t0, t1) = (t0.max(0.), t1.min(max_distance));
+
+        if t0 < t1 {
+            Some((t0, t1))
+        } else {
+            None
+        }
+    }
+
+    fn density(&self, ray: &Ray, t: f64) -> f64 {
+        let point = self
+            .transformation
+            .apply_inverse(&(ray.origin() + t * ray.direction()));
+        self.grid.density(&point)
+    }
+
+    /// Returns the distance to the next tentative collision, using the maximal density as a
+    /// bound on the actual density.
+    fn step<R: Rng>(&self, rng: &mut R) -> f64 {
+        -(1. - rng.gen::<f64>()).ln() / (self.grid.max_density * self.sigma_t)
+    }
+}
+
+impl Medium for GridMedium {
+    /// Estimates the transmittance with ratio tracking.
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> Rgb {
+        let length = ray.direction().norm();
+        let ray = Ray::new(*ray.origin(), *Unit::new_normalize(*ray.direction()));
+        let (mut t, t1) = match self.range(&ray, t_max * length) {
+            Some(range) => range,
+            None => return Rgb::white(),
+        };
+
+        let mut rng = thread_rng();
+        let mut transmittance = 1.;
+        loop {
+            t += self.step(&mut rng);
+            if t >= t1 {
+                return Rgb::white() * transmittance;
+            }
+
+            transmittance *= 1. - self.density(&ray, t) / self.grid.max_density;
+        }
+    }
+
+    /// Samples the scattering point with delta tracking.
+    fn sample(&self, ray: &Ray, t_max: f64) -> MediumSample {
+        let length = ray.direction().norm();
+        let normalized = Ray::new(*ray.origin(), *Unit::new_normalize(*ray.direction()));
+        let passed = MediumSample {
+            t: None,
+            weight: Rgb::white(),
+        };
+        let (mut t, t1) = match self.range(&normalized, t_max * length) {
+            Some(range) => range,
+            None => return passed,
+        };
+
+        let mut rng = thread_rng();
+        loop {
+            t += self.step(&mut rng);
+            if t >= t1 {
+                return passed;
+            }
+
+            // real collisions happen in proportion to the actual density
+            if self.density(&normalized, t) / self.grid.max_density > rng.gen::<f64>() {
+                return MediumSample {
+                    t: Some(t / length),
+                    weight: self.albedo,
+                };
+            }
+        }
+    }
+
+    fn phase_function(&self) -> &HenyeyGreenstein {
+        &self.phase_function
+    }
+}
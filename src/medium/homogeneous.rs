@@ -0,0 +1,91 @@
+use rand::prelude::*;
+
+use crate::film::Rgb;
+use crate::math::Ray;
+use crate::medium::{beer_lambert, HenyeyGreenstein, Medium, MediumSample};
+
+/// A medium with the same density everywhere, such as fog.
+#[derive(Debug, Copy, Clone)]
+pub struct HomogeneousMedium {
+    // absorption and scattering coefficients per color channel, in 1/m
+    sigma_a: Rgb,
+    sigma_s: Rgb,
+    phase_function: HenyeyGreenstein,
+}
+
+impl HomogeneousMedium {
+    pub fn new(sigma_a: Rgb, sigma_s: Rgb, phase_function: HenyeyGreenstein) -> Self {
+        Self {
+            sigma_a,
+            sigma_s,
+            phase_function,
+        }
+    }
+
+    /// Creates a new medium from the mean free path per color channel, i.e. the average
+    /// distance in m light travels before interacting with the medium, and the fraction of the
+    /// interactions that scatter the light rather than absorbing it.
+    pub fn from_mean_free_path(
+        mean_free_path: Rgb,
+        albedo: Rgb,
+        phase_function: HenyeyGreenstein,
+    ) -> Self {
+        let sigma_t = Rgb::white() / mean_free_path;
+        let sigma_s = sigma_t * albedo;
+
+        Self::new(sigma_t - sigma_s, sigma_s, phase_function)
+    }
+
+    fn sigma_t(&self) -> Rgb {
+        self.sigma_a + self.sigma_s
+    }
+}
+
+impl Medium for HomogeneousMedium {
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> Rgb {
+        beer_lambert(self.sigma_t(), t_max * ray.direction().norm())
+    }
+
+    /// Samples the distance to the scattering point in a randomly chosen color channel, such
+    /// that the other channels are sampled through the average density.
+    fn sample(&self, ray: &Ray, t_max: f64) -> MediumSample {
+        let mut rng = thread_rng();
+        let sigma_t = self.sigma_t();
+        let channel = [sigma_t.red(), sigma_t.green(), sigma_t.blue()][rng.gen_range(0..3)];
+
+        let length = ray.direction().norm();
+        let distance = -(1. - rng.gen::<f64>()).ln() / channel;
+        let t = (distance / length).min(t_max);
+        let scattered = t < t_max;
+
+        let transmittance = beer_lambert(sigma_t, t * length);
+        let density = if scattered {
+            sigma_t * transmittance
+        } else {
+            transmittance
+        };
+        let pdf = density.average();
+        if pdf == 0. {
+            return MediumSample {
+                t: None,
+                weight: Rgb::black(),
+            };
+        }
+
+        if scattered {
+            MediumSample {
+                t: Some(t),
+                weight: transmittance * self.sigma_s / pdf,
+            }
+        } else {
+            MediumSample {
+                t: None,
+                weight: transmittance / pdf,
+            }
+        }
+    }
+
+    fn phase_function(&self) -> &HenyeyGreenstein {
+        &self.phase_function
+    }
+}
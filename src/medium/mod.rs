@@ -0,0 +1,51 @@
+use crate::film::Rgb;
+use crate::math::Ray;
+use crate::shape::Shape;
+
+pub use grid::{DensityGrid, GridMedium};
+pub use homogeneous::HomogeneousMedium;
+pub use phase::HenyeyGreenstein;
+
+mod grid;
+mod homogeneous;
+mod phase;
+
+/// A participating medium, which absorbs and scatters the light travelling through it.
+pub trait Medium {
+    /// Returns the fraction of the light that is transmitted along `ray`, between its origin and
+    /// the point at parameter `t_max`, which may be infinite.
+    fn transmittance(&self, ray: &Ray, t_max: f64) -> Rgb;
+    /// Samples the point along `ray` before `t_max` at which light travelling along it is
+    /// scattered, if it is scattered at all.
+    fn sample(&self, ray: &Ray, t_max: f64) -> MediumSample;
+    fn phase_function(&self) -> &HenyeyGreenstein;
+}
+
+/// The outcome of sampling where light is scattered along a ray through a medium.
+#[derive(Debug, Copy, Clone)]
+pub struct MediumSample {
+    // parameter along the ray of the point where the light is scattered, `None` if it passed
+    pub t: Option<f64>,
+    // transmittance up to the sampled point, times the scattering coefficient there if the
+    // light is scattered, divided by the density of the sample
+    pub weight: Rgb,
+}
+
+/// A medium filling the inside of a closed shape, of which the normals point outwards.
+pub struct BoundedMedium {
+    pub(crate) shape: Box<dyn Shape>,
+    pub(crate) medium: Box<dyn Medium + Sync>,
+}
+
+impl BoundedMedium {
+    pub fn new(shape: Box<dyn Shape>, medium: Box<dyn Medium + Sync>) -> Self {
+        Self { shape, medium }
+    }
+}
+
+/// Returns the transmittance over `distance` through a homogeneous medium with extinction
+/// coefficient `sigma_t`.
+fn beer_lambert(sigma_t: Rgb, distance: f64) -> Rgb {
+    // avoids multiplying zero coefficients by an infinite distance
+    (sigma_t * -distance.min(f64::MAX)).exp()
+}
@@ -0,0 +1,55 @@
+use std::f64::consts::PI;
+
+use crate::math::OrthonormalBasis;
+use crate::sampler::Sample;
+use crate::Vector;
+
+/// The Henyey-Greenstein phase function, of which the asymmetry parameter `g` in (-1, 1) goes
+/// from back scattering over isotropic scattering at 0 to forward scattering.
+#[derive(Debug, Copy, Clone)]
+pub struct HenyeyGreenstein {
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f64) -> Self {
+        assert!(g > -1. && g < 1.);
+
+        Self { g }
+    }
+
+    pub fn isotropic() -> Self {
+        Self::new(0.)
+    }
+
+    /// Returns the density of scattering light travelling along `-wo` into the direction `wi`,
+    /// both pointing away from the scattering point.
+    pub fn p(&self, wo: &Vector, wi: &Vector) -> f64 {
+        let cos_theta = wo.dot(wi) / (wo.norm() * wi.norm());
+        let denominator = 1. + self.g * self.g + 2. * self.g * cos_theta;
+
+        (1. - self.g * self.g) / (4. * PI * denominator * denominator.max(0.).sqrt())
+    }
+
+    /// Samples the direction `wi` into which light travelling along `-wo` is scattered. Returns
+    /// the normalized direction together with its density, which equals `p`.
+    pub fn sample_p(&self, wo: &Vector, sample: Sample) -> (Vector, f64) {
+        let g = self.g;
+        // cosine of the angle between the direction of travel and the scattered direction
+        let cos_theta = if g.abs() < 1e-3 {
+            1. - 2. * sample.0
+        } else {
+            let term = (1. - g * g) / (1. - g + 2. * g * sample.0);
+            ((1. + g * g - term * term) / (2. * g)).clamp(-1., 1.)
+        };
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * sample.1;
+
+        let basis = OrthonormalBasis::from_vector(&-wo).unwrap();
+        let wi = sin_theta * phi.cos() * *basis.u
+            + sin_theta * phi.sin() * *basis.v
+            + cos_theta * *basis.w;
+
+        (wi, self.p(wo, &wi))
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::film::Rgb;
+use crate::shape::{Aabb, Union};
+use crate::{Point3, Vector};
+
+/// The kind of path along which a photon reached the surface it is stored on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PhotonKind {
+    /// Straight from a light.
+    Direct,
+    /// From a light, after only specular reflections or refractions.
+    Caustic,
+    /// After at least one diffuse reflection.
+    Indirect,
+}
+
+/// A packet of light that arrived at a diffuse surface.
+#[derive(Debug, Clone)]
+pub struct Photon {
+    pub position: Point3,
+    // normalized direction pointing towards where the photon came from
+    pub direction: Vector,
+    // flux carried by the photon, in W
+    pub power: Rgb,
+    pub kind: PhotonKind,
+}
+
+/// Photons stored in a balanced kd-tree, to find the photons nearest to a point.
+pub struct PhotonMap {
+    // every photon is the root of the subtree formed by the photons in the surrounding range of
+    // which it is the median
+    photons: Vec<Photon>,
+    // axis along which the subtree rooted at the photon with the same index is split
+    axes: Vec<usize>,
+}
+
+impl PhotonMap {
+    pub fn new(mut photons: Vec<Photon>) -> Self {
+        let mut axes = vec![0; photons.len()];
+        balance(&mut photons, &mut axes);
+
+        Self { photons, axes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Returns at most `k` of the photons nearest to `point` for which `filter` holds, within
+    /// `max_distance` of `point`. Also returns the squared radius of the sphere around `point`
+    /// in which they were found.
+    pub fn nearest<F>(
+        &self,
+        point: &Point3,
+        k: usize,
+        max_distance: f64,
+        filter: F,
+    ) -> (Vec<&Photon>, f64)
+    where
+        F: Fn(&Photon) -> bool,
+    {
+        let mut query = Query {
+            point,
+            k,
+            radius_squared: max_distance * max_distance,
+            neighbours: BinaryHeap::with_capacity(k + 1),
+            filter,
+        };
+        self.search(0, self.photons.len(), &mut query);
+
+        let photons = query
+            .neighbours
+            .iter()
+            .map(|neighbour| &self.photons[neighbour.index])
+            .collect();
+
+        (photons, query.radius_squared)
+    }
+
+    fn search<F>(&self, low: usize, high: usize, query: &mut Query<F>)
+    where
+        F: Fn(&Photon) -> bool,
+    {
+        if low >= high {
+            return;
+        }
+
+        let mid = low + (high - low) / 2;
+        let photon = &self.photons[mid];
+        let axis = self.axes[mid];
+        let delta = query.point[axis] - photon.position[axis];
+        let (near, far) = if delta < 0. {
+            ((low, mid), (mid + 1, high))
+        } else {
+            ((mid + 1, high), (low, mid))
+        };
+
+        self.search(near.0, near.1, query);
+
+        let distance_squared = (photon.position - query.point).norm_squared();
+        if distance_squared < query.radius_squared && (query.filter)(photon) {
+            query.neighbours.push(Neighbour {
+                distance_squared,
+                index: mid,
+            });
+            if query.neighbours.len() > query.k {
+                query.neighbours.pop();
+            }
+            if query.neighbours.len() == query.k {
+                // only photons closer than the farthest one found so far can still be accepted
+                query.radius_squared = query.neighbours.peek().unwrap().distance_squared;
+            }
+        }
+
+        if delta * delta < query.radius_squared {
+            self.search(far.0, far.1, query);
+        }
+    }
+}
+
+/// Reorders `photons` into a balanced kd-tree, splitting every range at its median along the
+/// axis in which the photons are spread the most.
+fn balance(photons: &mut [Photon], axes: &mut [usize]) {
+    if photons.len() <= 1 {
+        return;
+    }
+
+    let bbox = photons
+        .iter()
+        .fold(Aabb::default(), |bbox, photon| bbox.union(photon.position));
+    let (axis, _) = bbox.longest_axis();
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| {
+        a.position[axis].partial_cmp(&b.position[axis]).unwrap()
+    });
+    axes[mid] = axis;
+
+    let (left, right) = photons.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+    balance(left, left_axes);
+    balance(&mut right[1..], &mut right_axes[1..]);
+}
+
+struct Query<'a, F> {
+    point: &'a Point3,
+    k: usize,
+    radius_squared: f64,
+    // max-heap of the nearest photons found so far
+    neighbours: BinaryHeap<Neighbour>,
+    filter: F,
+}
+
+struct Neighbour {
+    distance_squared: f64,
+    index: usize,
+}
+
+impl PartialEq for Neighbour {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance_squared == other.distance_squared
+    }
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_squared
+            .partial_cmp(&other.distance_squared)
+            .unwrap_or(Ordering::Equal)
+    }
+}
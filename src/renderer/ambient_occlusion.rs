@@ -0,0 +1,67 @@
+use indicatif::ParallelProgressIterator;
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::film::FrameBuffer;
+use crate::light::{Ambient, AmbientOccluder};
+use crate::renderer::Renderer;
+use crate::sampler::Sampler;
+use crate::world::World;
+
+/// Renders the ambient occlusion of the scene, i.e. the fraction of the hemisphere above every
+/// visible point that isn't occluded within `max_distance`, ignoring all lights and materials.
+#[derive(Debug)]
+pub struct AmbientOcclusion {
+    max_distance: f64,
+    nb_samples: usize,
+}
+
+impl AmbientOcclusion {
+    /// Creates a new ambient occlusion renderer that takes `nb_samples` jittered samples of the
+    /// hemisphere above every hit point, rounded down to a perfect square.
+    pub fn new(max_distance: f64, nb_samples: usize) -> Self {
+        assert!(max_distance > 0.);
+        assert!(nb_samples > 0);
+
+        Self {
+            max_distance,
+            nb_samples,
+        }
+    }
+}
+
+impl Renderer for AmbientOcclusion {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let occluder = AmbientOccluder::white(1., self.max_distance, self.nb_samples);
+
+        buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+
+                        match world.hit_objects(&ray) {
+                            None => world.background(&ray),
+                            Some(sr) => occluder.radiance(&sr),
+                        }
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
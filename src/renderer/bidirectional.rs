@@ -0,0 +1,825 @@
+use indicatif::ParallelProgressIterator;
+use itertools::Itertools;
+use nalgebra::Unit;
+use rand::prelude::*;
+use rayon::prelude::*;
+use std::cell::{Cell, RefCell};
+
+use crate::brdf::Brdf;
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb};
+use crate::material::Material;
+use crate::math::{Distribution1D, Ray};
+use crate::renderer::Renderer;
+use crate::sampler::{Heuristic, Sampler};
+use crate::shade_rec::ShadeRec;
+use crate::world::World;
+use crate::{Point3, Vector};
+
+/// Renders global illumination with bidirectional path tracing. For every camera ray, a subpath
+/// is traced from the camera and another one from a light chosen by power, and every vertex of
+/// the one is connected to every vertex of the other. All these estimates of the same paths are
+/// combined with multiple importance sampling. Light subpaths connected to the camera itself
+/// reach arbitrary pixels, so their contributions are splatted onto the image.
+///
+/// Lights infinitely far away and the background can't start light subpaths, so their light is
+/// estimated along the camera subpath only, by sampling both the lights and the brdfs. Surfaces
+/// only reflect light on the side their normal points to, and the ambient light of the world is
+/// ignored.
+#[derive(Debug)]
+pub struct BidirectionalPathTracing {
+    // maximum number of bounces between the camera and a light
+    max_depth: usize,
+    heuristic: Heuristic,
+}
+
+impl BidirectionalPathTracing {
+    pub fn new(max_depth: usize, heuristic: Heuristic) -> Self {
+        Self {
+            max_depth,
+            heuristic,
+        }
+    }
+
+    /// Estimates the radiance arriving along a camera ray, using a single light subpath.
+    /// Contributions to other pixels are added to `splats`, with the index of their pixel.
+    fn radiance<C: Camera>(
+        &self,
+        scene: &Scene<C>,
+        ray: Ray,
+        splats: &mut Vec<(usize, Rgb)>,
+    ) -> Rgb {
+        let mut camera_path = Vec::with_capacity(self.max_depth + 2);
+        let escape = self.camera_subpath(scene, ray, &mut camera_path);
+        let mut light_path = Vec::with_capacity(self.max_depth + 1);
+        self.light_subpath(scene, &mut light_path);
+
+        let mut radiance = self.infinite_radiance(scene, &camera_path, escape);
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                // connecting a light vertex straight to the camera is left to the camera subpath
+                if s + t < 2 || (s == 1 && t == 1) || s + t - 2 > self.max_depth {
+                    continue;
+                }
+
+                radiance += self.connect(scene, &light_path, &camera_path, s, t, splats);
+            }
+        }
+
+        radiance
+    }
+
+    fn camera_subpath<'a, C: Camera>(
+        &self,
+        scene: &Scene<'a, C>,
+        ray: Ray,
+        path: &mut Vec<Vertex<'a>>,
+    ) -> Option<Escape> {
+        let pdf = scene
+            .camera
+            .project(&(ray.origin() + ray.direction()))
+            .map_or(0., |projection| projection.pdf);
+        path.push(Vertex::camera(*ray.origin()));
+
+        random_walk(
+            scene.world,
+            ray,
+            Rgb::white(),
+            pdf,
+            self.max_depth + 1,
+            false,
+            path,
+        )
+    }
+
+    fn light_subpath<'a, C: Camera>(&self, scene: &Scene<'a, C>, path: &mut Vec<Vertex<'a>>) {
+        let distribution = match &scene.light_distribution {
+            Some(distribution) => distribution,
+            None => return,
+        };
+
+        let mut rng = thread_rng();
+        let (index, pmf, _) = distribution.sample_discrete(rng.gen());
+        let light = &scene.world.lights()[index];
+        let emission = match light.sample_emission(rng.gen(), rng.gen()) {
+            Some(emission) => emission,
+            None => return,
+        };
+
+        let normal = emission.normal.map_or_else(Vector::zeros, Unit::into_inner);
+        let pdf_fwd = pmf * emission.pdf_position;
+        path.push(Vertex::light(
+            index,
+            *emission.ray.origin(),
+            normal,
+            pdf_fwd,
+        ));
+
+        random_walk(
+            scene.world,
+            emission.ray,
+            emission.flux / pmf,
+            emission.pdf_direction,
+            self.max_depth,
+            true,
+            path,
+        );
+    }
+
+    /// Estimates the radiance arriving along the camera subpath from the lights infinitely far
+    /// away and from the background. The lights are sampled at every vertex, and combined with
+    /// the directions sampled by the brdfs.
+    fn infinite_radiance<C: Camera>(
+        &self,
+        scene: &Scene<C>,
+        camera_path: &[Vertex],
+        escape: Option<Escape>,
+    ) -> Rgb {
+        let lights = scene.world.lights();
+        let mut radiance = Rgb::black();
+
+        for (prev, vertex) in camera_path.iter().tuple_windows() {
+            let sr = match vertex.shade_rec() {
+                Some(sr) if vertex.is_connectible() => sr,
+                _ => continue,
+            };
+
+            for &index in &scene.infinite_lights {
+                let light = &lights[index];
+                radiance += light.average(sr, &|sample| {
+                    let wi = sample.direction(sr);
+                    let next = vertex.point + *wi;
+                    let f = vertex.f(&prev.point, &next);
+                    if f == Rgb::black() || !sample.visible(&Ray::new(sr.hit_point, *wi), sr) {
+                        return Rgb::black();
+                    }
+
+                    let weight = if light.is_delta() {
+                        1.
+                    } else {
+                        let brdf_pdf = vertex.pdf_brdf(&prev.point, &next);
+                        self.heuristic.weight(sample.pdf(), brdf_pdf)
+                    };
+
+                    vertex.beta
+                        * f
+                        * sample.radiance()
+                        * (vertex.normal.dot(&wi) * weight / sample.pdf())
+                });
+            }
+        }
+
+        if let Some(escape) = escape {
+            radiance += escape.beta * scene.world.background_color();
+
+            let last = camera_path.last().unwrap();
+            let wi = Unit::new_normalize(*escape.ray.direction());
+            for &index in &scene.infinite_lights {
+                let light = &lights[index];
+                let background = light.background(&escape.ray);
+                if background == Rgb::black() {
+                    continue;
+                }
+
+                let weight = match last.shade_rec() {
+                    Some(sr) if !last.delta => {
+                        self.heuristic.weight(escape.pdf, light.pdf(sr, &wi))
+                    }
+                    _ => 1.,
+                };
+
+                radiance += escape.beta * background * weight;
+            }
+        }
+
+        radiance
+    }
+
+    /// Estimates the contribution of the path formed by connecting the first `s` vertices of the
+    /// light subpath to the first `t` vertices of the camera subpath, weighted with multiple
+    /// importance sampling. Contributions of light subpaths connected to the camera itself are
+    /// added to `splats` instead.
+    fn connect<C: Camera>(
+        &self,
+        scene: &Scene<C>,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+        splats: &mut Vec<(usize, Rgb)>,
+    ) -> Rgb {
+        if t == 1 {
+            let qs = &light_path[s - 1];
+            if !qs.is_connectible() {
+                return Rgb::black();
+            }
+            let projection = match scene.camera.project(&qs.point) {
+                Some(projection) => projection,
+                None => return Rgb::black(),
+            };
+
+            let camera = Vertex::camera(projection.origin);
+            let f = qs.f(&light_path[s - 2].point, &camera.point);
+            let radiance =
+                qs.beta * f * (qs.cos(&camera.point) * qs.geometry_term(&camera) * projection.pdf);
+            if radiance == Rgb::black() || !unoccluded(scene.world, &qs.point, &camera.point) {
+                return Rgb::black();
+            }
+
+            let weight = self.mis_weight(scene, light_path, camera_path, Some(&camera), s, t);
+            let (x_res, _) = scene.camera.resolution();
+            let index = projection.row as usize * x_res + projection.column as usize;
+            splats.push((index, radiance * weight));
+
+            return Rgb::black();
+        }
+
+        let pt = &camera_path[t - 1];
+        let pt_minus = &camera_path[t - 2];
+        if s == 0 {
+            let radiance = pt.beta * pt.emitted(&pt_minus.point);
+            if radiance == Rgb::black() {
+                return radiance;
+            }
+
+            return radiance * self.mis_weight(scene, light_path, camera_path, None, s, t);
+        }
+
+        if !pt.is_connectible() {
+            return Rgb::black();
+        }
+
+        if s == 1 {
+            let (distribution, sr) = match (&scene.light_distribution, pt.shade_rec()) {
+                (Some(distribution), Some(sr)) => (distribution, sr),
+                _ => return Rgb::black(),
+            };
+            let (index, pmf, _) = distribution.sample_discrete(thread_rng().gen());
+            let light = &scene.world.lights()[index];
+
+            return light.average(sr, &|sample| {
+                let location = match sample.location() {
+                    Some(location) => location,
+                    None => return Rgb::black(),
+                };
+                let wi = sample.direction(sr);
+                let f = pt.f(&pt_minus.point, &location);
+                if f == Rgb::black() || !sample.visible(&Ray::new(sr.hit_point, *wi), sr) {
+                    return Rgb::black();
+                }
+
+                let normal = sample.normal().map_or_else(Vector::zeros, Unit::into_inner);
+                let pdf_position = match sample.normal() {
+                    // the density of the point actually sampled, converted from solid angle to
+                    // area
+                    Some(light_normal) => {
+                        let distance_squared = (location - sr.hit_point).norm_squared();
+                        sample.pdf() * light_normal.dot(&wi).abs() / distance_squared
+                    }
+                    None => light.pdf_emission(&normal, &normal).0,
+                };
+                let vertex = Vertex::light(index, location, normal, pmf * pdf_position);
+                let weight = self.mis_weight(scene, light_path, camera_path, Some(&vertex), s, t);
+
+                pt.beta
+                    * f
+                    * sample.radiance()
+                    * (pt.normal.dot(&wi) * weight / (sample.pdf() * pmf))
+            });
+        }
+
+        let qs = &light_path[s - 1];
+        if !qs.is_connectible() {
+            return Rgb::black();
+        }
+
+        let radiance = qs.beta
+            * qs.f(&light_path[s - 2].point, &pt.point)
+            * pt.f(&pt_minus.point, &qs.point)
+            * pt.beta
+            * (qs.cos(&pt.point) * qs.geometry_term(pt));
+        if radiance == Rgb::black() || !unoccluded(scene.world, &qs.point, &pt.point) {
+            return Rgb::black();
+        }
+
+        radiance * self.mis_weight(scene, light_path, camera_path, None, s, t)
+    }
+
+    /// Returns the weight of the strategy connecting `s` light vertices to `t` camera vertices,
+    /// relative to all strategies that could have sampled the same path. For strategies with a
+    /// single light or camera vertex, that vertex is `sampled` during the connection instead of
+    /// being taken from its subpath.
+    fn mis_weight<C: Camera>(
+        &self,
+        scene: &Scene<C>,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.;
+        }
+
+        let qs = if s == 1 {
+            sampled
+        } else {
+            s.checked_sub(1).map(|i| &light_path[i])
+        };
+        let pt = if t == 1 {
+            sampled.unwrap()
+        } else {
+            &camera_path[t - 1]
+        };
+        let qs_minus = s.checked_sub(2).map(|i| &light_path[i]);
+        let pt_minus = t.checked_sub(2).map(|i| &camera_path[i]);
+
+        let lights = scene.world.lights();
+        let light_vertex = if s == 1 { qs } else { light_path.first() };
+        let is_delta_light = light_vertex
+            .and_then(|vertex| vertex.light_index())
+            .map_or(false, |index| lights[index].is_delta());
+        if s == 0 && pt.light_index().is_none() {
+            // emitting surfaces that aren't lights can only be reached by the camera subpath
+            return 1.;
+        }
+
+        // densities of the vertices as if the path had been sampled with this strategy
+        let mut light = light_path[..s].iter().map(Densities::of).collect_vec();
+        let mut camera = camera_path[..t].iter().map(Densities::of).collect_vec();
+        if s == 1 {
+            light[0] = Densities::of(qs.unwrap());
+        }
+        if t == 1 {
+            camera[0] = Densities::of(pt);
+        }
+
+        camera[t - 1].delta = false;
+        camera[t - 1].pdf_rev = match qs {
+            Some(qs) => qs.pdf(scene, qs_minus, pt),
+            None => pt.pdf_light_origin(scene),
+        };
+        if let Some(pt_minus) = pt_minus {
+            camera[t - 2].pdf_rev = match qs {
+                Some(qs) => pt.pdf(scene, Some(qs), pt_minus),
+                None => pt.pdf_light(scene, pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light[s - 1].delta = false;
+            light[s - 1].pdf_rev = pt.pdf(scene, pt_minus, qs);
+            if let Some(qs_minus) = qs_minus {
+                light[s - 2].pdf_rev = qs.pdf(scene, Some(pt), qs_minus);
+            }
+        }
+
+        // deltas have no density, they cancel out in the ratios
+        let remap = |pdf: f64| {
+            let pdf = if pdf == 0. { 1. } else { pdf };
+            match self.heuristic {
+                Heuristic::Balance => pdf,
+                Heuristic::Power => pdf * pdf,
+            }
+        };
+
+        // sums the ratios of the densities of the other strategies to the density of this one
+        let mut sum = 0.;
+        let mut ratio = 1.;
+        for i in (1..t).rev() {
+            ratio *= remap(camera[i].pdf_rev) / remap(camera[i].pdf_fwd);
+            if !camera[i].delta && !camera[i - 1].delta {
+                sum += ratio;
+            }
+        }
+
+        ratio = 1.;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].pdf_rev) / remap(light[i].pdf_fwd);
+            let is_delta_prev = if i > 0 {
+                light[i - 1].delta
+            } else {
+                is_delta_light
+            };
+            if !light[i].delta && !is_delta_prev {
+                sum += ratio;
+            }
+        }
+
+        1. / (1. + sum)
+    }
+}
+
+impl Renderer for BidirectionalPathTracing {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let scene = Scene::new(world, camera);
+
+        let splats = buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .fold(
+                || vec![Rgb::black(); x_res * y_res],
+                |mut splats, (r, row)| {
+                    row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                        let nb_samples = Cell::new(0);
+                        let pixel_splats = RefCell::new(Vec::new());
+                        let color = sampler.average(|sample| {
+                            nb_samples.set(nb_samples.get() + 1);
+                            let ray = camera.generate_ray(c, r, sample);
+                            self.radiance(&scene, ray, &mut pixel_splats.borrow_mut())
+                        });
+
+                        pixel.set(color);
+                        // every camera sample traces a light subpath, which contributes to the
+                        // whole image
+                        for (index, splat) in pixel_splats.into_inner() {
+                            splats[index] += splat / nb_samples.get() as f64;
+                        }
+                    });
+
+                    splats
+                },
+            )
+            .reduce(
+                || vec![Rgb::black(); x_res * y_res],
+                |mut splats, other| {
+                    splats.iter_mut().zip(other).for_each(|(a, b)| *a += b);
+                    splats
+                },
+            );
+
+        buffer
+            .buffer_mut()
+            .iter_mut()
+            .zip(splats)
+            .for_each(|(pixel, splat)| pixel.set(pixel.spectrum() + splat));
+
+        buffer
+    }
+}
+
+/// The parts of the scene the subpaths of `BidirectionalPathTracing` are traced through.
+struct Scene<'a, C> {
+    world: &'a World<'a>,
+    camera: &'a C,
+    // distribution of the lights by power, used to start light subpaths, or `None` if no light
+    // can start one
+    light_distribution: Option<Distribution1D>,
+    // indices of the lights infinitely far away
+    infinite_lights: Vec<usize>,
+}
+
+impl<'a, C: Camera> Scene<'a, C> {
+    fn new(world: &'a World<'a>, camera: &'a C) -> Self {
+        let lights = world.lights();
+        let power = lights
+            .iter()
+            .map(|light| match light.bounds() {
+                Some(_) => light.power().luminance(),
+                None => 0.,
+            })
+            .collect_vec();
+        let light_distribution = if power.iter().sum::<f64>() > 0. {
+            Some(Distribution1D::new(power))
+        } else {
+            None
+        };
+        let infinite_lights = (0..lights.len())
+            .filter(|&i| lights[i].bounds().is_none())
+            .collect();
+
+        Self {
+            world,
+            camera,
+            light_distribution,
+            infinite_lights,
+        }
+    }
+}
+
+enum VertexKind<'a> {
+    Camera,
+    // the light with the given index in the lights of the world
+    Light(usize),
+    Surface(ShadeRec<'a>),
+}
+
+/// A vertex of a camera or light subpath.
+struct Vertex<'a> {
+    kind: VertexKind<'a>,
+    point: Point3,
+    // normalized normal of the surface the vertex lies on, zero if it doesn't lie on a surface
+    normal: Vector,
+    // throughput of the subpath up to this vertex divided by the density of having sampled it
+    beta: Rgb,
+    // whether the subpath is scattered perfectly specularly at this vertex
+    delta: bool,
+    // densities with respect to area of sampling this vertex from the previous vertex of its
+    // subpath, and from the next one if the subpath had been traced in the other direction
+    pdf_fwd: f64,
+    pdf_rev: f64,
+}
+
+impl<'a> Vertex<'a> {
+    fn camera(point: Point3) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            point,
+            normal: Vector::zeros(),
+            beta: Rgb::white(),
+            delta: false,
+            pdf_fwd: 0.,
+            pdf_rev: 0.,
+        }
+    }
+
+    fn light(index: usize, point: Point3, normal: Vector, pdf_fwd: f64) -> Self {
+        Self {
+            kind: VertexKind::Light(index),
+            point,
+            normal,
+            beta: Rgb::white(),
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.,
+        }
+    }
+
+    fn surface(sr: ShadeRec<'a>, beta: Rgb) -> Self {
+        Self {
+            point: sr.hit_point,
+            normal: sr.normal.normalize(),
+            kind: VertexKind::Surface(sr),
+            beta,
+            delta: false,
+            pdf_fwd: 0.,
+            pdf_rev: 0.,
+        }
+    }
+
+    fn shade_rec(&self) -> Option<&ShadeRec<'a>> {
+        match &self.kind {
+            VertexKind::Surface(sr) => Some(sr),
+            _ => None,
+        }
+    }
+
+    /// Returns the brdf of the surface this vertex lies on, and whether it is perfectly
+    /// specular. Emitting surfaces don't reflect light.
+    fn brdf(&self) -> Option<(&dyn Brdf, bool)> {
+        match self.shade_rec()?.material()? {
+            Material::Matte { diffuse_brdf, .. } => Some((diffuse_brdf, false)),
+            Material::SvMatte { diffuse_brdf, .. } => Some((diffuse_brdf, false)),
+            Material::Subsurface(subsurface) => Some((&subsurface.diffuse_brdf, false)),
+            Material::Mirror(brdf) => Some((brdf, true)),
+            Material::Glass(brdf) => Some((brdf, true)),
+            Material::Emissive(_) => None,
+        }
+    }
+
+    /// Returns whether this vertex can be connected to a vertex of another subpath.
+    fn is_connectible(&self) -> bool {
+        match self.kind {
+            VertexKind::Surface(_) => matches!(self.brdf(), Some((_, false))),
+            _ => true,
+        }
+    }
+
+    fn is_on_surface(&self) -> bool {
+        self.normal != Vector::zeros()
+    }
+
+    /// Returns the index of the light this vertex lies on, if any.
+    fn light_index(&self) -> Option<usize> {
+        match &self.kind {
+            VertexKind::Light(index) => Some(*index),
+            VertexKind::Surface(sr) => sr.shape()?.emitter(),
+            VertexKind::Camera => None,
+        }
+    }
+
+    /// Returns the cosine between the normal at this vertex and the direction towards `point`,
+    /// or 1 if this vertex doesn't lie on a surface.
+    fn cos(&self, point: &Point3) -> f64 {
+        if self.is_on_surface() {
+            let d = point - self.point;
+            self.normal.dot(&d).abs() / d.norm()
+        } else {
+            1.
+        }
+    }
+
+    /// Returns the factor converting a density with respect to solid angle at this vertex to
+    /// one with respect to area at `next`.
+    fn geometry_term(&self, next: &Vertex) -> f64 {
+        let distance_squared = (next.point - self.point).norm_squared();
+        if distance_squared == 0. {
+            return 0.;
+        }
+
+        next.cos(&self.point) / distance_squared
+    }
+
+    /// Returns the brdf at this vertex for light arriving from `next` and leaving towards `prev`.
+    fn f(&self, prev: &Point3, next: &Point3) -> Rgb {
+        let (sr, brdf) = match (self.shade_rec(), self.brdf()) {
+            (Some(sr), Some((brdf, false))) => (sr, brdf),
+            _ => return Rgb::black(),
+        };
+        let wo = prev - self.point;
+        let wi = next - self.point;
+        if self.normal.dot(&wo) <= 0. || self.normal.dot(&wi) <= 0. {
+            return Rgb::black();
+        }
+
+        brdf.f(sr, &wi.normalize(), &wo.normalize())
+    }
+
+    /// Returns the density with respect to solid angle with which the brdf at this vertex
+    /// samples the direction towards `next`, when reached from `prev`.
+    fn pdf_brdf(&self, prev: &Point3, next: &Point3) -> f64 {
+        let (sr, brdf) = match (self.shade_rec(), self.brdf()) {
+            (Some(sr), Some((brdf, false))) => (sr, brdf),
+            _ => return 0.,
+        };
+        let wo = prev - self.point;
+        let wi = next - self.point;
+        if self.normal.dot(&wo) <= 0. || self.normal.dot(&wi) <= 0. {
+            return 0.;
+        }
+
+        brdf.pdf(sr, &wi.normalize(), &wo.normalize())
+    }
+
+    /// Returns the density with respect to area of sampling `next` from this vertex, when
+    /// reached from `prev`.
+    fn pdf<C: Camera>(&self, scene: &Scene<C>, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        let pdf = match self.kind {
+            VertexKind::Camera => scene
+                .camera
+                .project(&next.point)
+                .map_or(0., |projection| projection.pdf),
+            VertexKind::Light(_) => return self.pdf_light(scene, next),
+            VertexKind::Surface(_) => {
+                prev.map_or(0., |prev| self.pdf_brdf(&prev.point, &next.point))
+            }
+        };
+
+        pdf * self.geometry_term(next)
+    }
+
+    /// Returns the density with respect to area of the light this vertex lies on emitting
+    /// towards `next`.
+    fn pdf_light<C: Camera>(&self, scene: &Scene<C>, next: &Vertex) -> f64 {
+        let index = match self.light_index() {
+            Some(index) => index,
+            None => return 0.,
+        };
+        let light = &scene.world.lights()[index];
+        let (_, pdf_direction) = light.pdf_emission(&self.normal, &(next.point - self.point));
+
+        pdf_direction * self.geometry_term(next)
+    }
+
+    /// Returns the density with respect to area of starting a light subpath at this vertex.
+    fn pdf_light_origin<C: Camera>(&self, scene: &Scene<C>) -> f64 {
+        let (index, distribution) = match (self.light_index(), &scene.light_distribution) {
+            (Some(index), Some(distribution)) => (index, distribution),
+            _ => return 0.,
+        };
+        let light = &scene.world.lights()[index];
+        let (pdf_position, _) = light.pdf_emission(&self.normal, &self.normal);
+
+        distribution.discrete_pdf(index) * pdf_position
+    }
+
+    /// Returns the radiance emitted from this vertex towards `prev`, if it lies on the emitting
+    /// side of an emissive surface.
+    fn emitted(&self, prev: &Point3) -> Rgb {
+        let sr = match self.shade_rec() {
+            Some(sr) => sr,
+            None => return Rgb::black(),
+        };
+
+        match sr.material().unwrap() {
+            Material::Emissive(emissive) if self.normal.dot(&(prev - self.point)) > 0. => {
+                emissive.ce * emissive.ls
+            }
+            _ => Rgb::black(),
+        }
+    }
+}
+
+/// The densities of a vertex, which are changed when evaluating the weight of a strategy.
+#[derive(Debug, Copy, Clone)]
+struct Densities {
+    pdf_fwd: f64,
+    pdf_rev: f64,
+    delta: bool,
+}
+
+impl Densities {
+    fn of(vertex: &Vertex) -> Self {
+        Self {
+            pdf_fwd: vertex.pdf_fwd,
+            pdf_rev: vertex.pdf_rev,
+            delta: vertex.delta,
+        }
+    }
+}
+
+/// A ray that left the scene at the end of a camera subpath.
+struct Escape {
+    ray: Ray,
+    // throughput of the subpath including the ray
+    beta: Rgb,
+    // density with respect to solid angle with which the ray was sampled
+    pdf: f64,
+}
+
+/// Extends `path` by tracing `ray` from its last vertex and sampling the brdfs of the surfaces
+/// it hits, adding at most `max_depth` vertices. `beta` is the throughput along `ray` and `pdf`
+/// the density with respect to solid angle of having sampled it. Subpaths `from_light` carry flux
+/// rather than radiance. Returns the ray that left the scene, if the subpath ended that way.
+fn random_walk<'a>(
+    world: &'a World<'a>,
+    mut ray: Ray,
+    mut beta: Rgb,
+    mut pdf: f64,
+    max_depth: usize,
+    from_light: bool,
+    path: &mut Vec<Vertex<'a>>,
+) -> Option<Escape> {
+    let mut rng = thread_rng();
+
+    for _ in 0..max_depth {
+        let sr = match world.hit_objects(&ray) {
+            Some(sr) => sr,
+            None => return Some(Escape { ray, beta, pdf }),
+        };
+
+        let mut vertex = Vertex::surface(sr, beta);
+        vertex.pdf_fwd = pdf * path.last().unwrap().geometry_term(&vertex);
+        path.push(vertex);
+
+        let vertex = path.last().unwrap();
+        let (brdf, specular) = match vertex.brdf() {
+            Some(brdf) => brdf,
+            None => break,
+        };
+        let sr = vertex.shade_rec().unwrap();
+        let wo = -ray.direction().normalize();
+        if !specular && vertex.normal.dot(&wo) <= 0. {
+            break;
+        }
+
+        let (f, wi, brdf_pdf) = if from_light {
+            brdf.sample_f_from_light(sr, &wo, rng.gen())
+        } else {
+            brdf.sample_f(sr, &wo, rng.gen())
+        };
+        let wi = wi.normalize();
+        let cos_wi = vertex.normal.dot(&wi);
+        if brdf_pdf == 0. || f == Rgb::black() || (!specular && cos_wi <= 0.) {
+            break;
+        }
+
+        beta = beta * f * (cos_wi.abs() / brdf_pdf);
+        // perfectly specular scattering has no density, and can't be sampled in reverse by
+        // another strategy
+        let pdf_rev = if specular {
+            pdf = 0.;
+            0.
+        } else {
+            pdf = brdf_pdf;
+            brdf.pdf(sr, &wo, &wi)
+        };
+        ray = Ray::new(vertex.point, wi);
+
+        let n = path.len();
+        path[n - 2].pdf_rev = pdf_rev * path[n - 1].geometry_term(&path[n - 2]);
+        path[n - 1].delta = specular;
+    }
+
+    None
+}
+
+/// Returns whether nothing blocks the segment between `from` and `to`.
+fn unoccluded(world: &World, from: &Point3, to: &Point3) -> bool {
+    let d = to - from;
+    let distance = d.norm();
+    // avoids the surface `to` lies on being reported as an occluder
+    let max_t = (1. - 1e-6) * distance;
+
+    !world.hit_any_object_where(&Ray::new(*from, d / distance), |hit| hit.t < max_t)
+}
@@ -0,0 +1,194 @@
+use indicatif::ParallelProgressIterator;
+use nalgebra::Unit;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use crate::brdf::Brdf;
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb};
+use crate::light::{LightSelection, LightSelector};
+use crate::material::Material;
+use crate::math::Ray;
+use crate::renderer::Renderer;
+use crate::sampler::{Heuristic, Sampler};
+use crate::shade_rec::ShadeRec;
+use crate::world::World;
+use crate::Vector;
+
+#[derive(Debug)]
+pub struct DirectIllumination {
+    pub(super) light_selection: LightSelection,
+    // heuristic used to combine samples of the lights and of the brdfs, if any
+    heuristic: Option<Heuristic>,
+    // number of times the direct lighting is estimated for every camera ray
+    nb_light_samples: usize,
+}
+
+impl DirectIllumination {
+    /// Creates a new renderer that estimates the direct lighting `nb_light_samples` times for
+    /// every camera ray, so shadows can be refined independently of the pixel sampler.
+    pub fn new(
+        light_selection: LightSelection,
+        heuristic: Option<Heuristic>,
+        nb_light_samples: usize,
+    ) -> Self {
+        assert!(nb_light_samples > 0);
+
+        Self {
+            light_selection,
+            heuristic,
+            nb_light_samples,
+        }
+    }
+}
+
+impl Default for DirectIllumination {
+    fn default() -> Self {
+        Self::new(LightSelection::default(), Some(Heuristic::Power), 1)
+    }
+}
+
+impl Renderer for DirectIllumination {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let lights = LightSelector::new(world.lights(), self.light_selection);
+
+        buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+
+                        match world.hit_objects(&ray) {
+                            None => world.background(&ray),
+                            Some(sr) => self.shade(sr.material().unwrap(), &sr, &ray, &lights),
+                        }
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
+
+impl DirectIllumination {
+    fn shade(&self, material: &Material, sr: &ShadeRec, ray: &Ray, lights: &LightSelector) -> Rgb {
+        match material {
+            Material::Matte {
+                ambient_brdf,
+                diffuse_brdf,
+            } => {
+                let wo = -ray.direction();
+                let ambient_radiance =
+                    ambient_brdf.rho(sr, &wo) * sr.world.ambient_light().radiance(sr);
+                let direct_diffuse_radiance = self.direct_radiance(diffuse_brdf, sr, &wo, lights);
+
+                ambient_radiance + direct_diffuse_radiance
+            }
+            Material::Emissive(emissive) => emissive.ce * emissive.ls,
+            Material::SvMatte {
+                ambient_brdf,
+                diffuse_brdf,
+            } => {
+                let wo = -ray.direction();
+                let ambient_radiance =
+                    ambient_brdf.rho(sr, &wo) * sr.world.ambient_light().radiance(sr);
+                let direct_diffuse_radiance = self.direct_radiance(diffuse_brdf, sr, &wo, lights);
+
+                ambient_radiance + direct_diffuse_radiance
+            }
+            Material::Subsurface(subsurface) => {
+                let wo = -ray.direction();
+                let diffuse_brdf = &subsurface.diffuse_brdf;
+                let ambient_radiance =
+                    diffuse_brdf.rho(sr, &wo) * sr.world.ambient_light().radiance(sr);
+                let direct_diffuse_radiance = self.direct_radiance(diffuse_brdf, sr, &wo, lights);
+
+                ambient_radiance + direct_diffuse_radiance
+            }
+            // perfectly specular surfaces only reflect light arriving from a single direction,
+            // which direct light sampling never hits
+            Material::Mirror(_) | Material::Glass(_) => Rgb::black(),
+        }
+    }
+
+    /// Estimates the radiance reflected by `brdf` towards `wo` of the light arriving directly
+    /// from the lights, averaged over `nb_light_samples` estimates.
+    pub(super) fn direct_radiance<B: Brdf + ?Sized>(
+        &self,
+        brdf: &B,
+        sr: &ShadeRec,
+        wo: &Vector,
+        lights: &LightSelector,
+    ) -> Rgb {
+        (0..self.nb_light_samples)
+            .map(|_| self.estimate_direct_radiance(brdf, sr, wo, lights))
+            .sum::<Rgb>()
+            / self.nb_light_samples as f64
+    }
+
+    /// Estimates the direct lighting once. When a heuristic is set, samples of the lights are
+    /// combined with samples of the brdf.
+    fn estimate_direct_radiance<B: Brdf + ?Sized>(
+        &self,
+        brdf: &B,
+        sr: &ShadeRec,
+        wo: &Vector,
+        lights: &LightSelector,
+    ) -> Rgb {
+        lights.estimate(sr, |light| {
+            let light_radiance = light.average(sr, &|sample| {
+                let wi = sample.direction(sr);
+                let n_dot_wi = sr.normal.dot(&wi);
+
+                if n_dot_wi <= 0. || !sample.visible(&Ray::new(sr.hit_point, *wi), sr) {
+                    return Rgb::black();
+                }
+
+                let weight = match self.heuristic {
+                    Some(heuristic) if !light.is_delta() => {
+                        heuristic.weight(sample.pdf(), brdf.pdf(sr, &wi, wo))
+                    }
+                    _ => 1.,
+                };
+
+                brdf.f(sr, wo, &wi) * sample.radiance() * (n_dot_wi * weight / sample.pdf())
+            });
+
+            let heuristic = match self.heuristic {
+                Some(heuristic) if !light.is_delta() => heuristic,
+                _ => return light_radiance,
+            };
+
+            let (f, wi, brdf_pdf) = brdf.sample_f(sr, wo, thread_rng().gen());
+            let wi = Unit::new_normalize(wi);
+            let n_dot_wi = sr.normal.dot(&wi);
+            if brdf_pdf == 0. || n_dot_wi <= 0. {
+                return light_radiance;
+            }
+
+            let brdf_radiance = match light.sample_direction(sr, &wi) {
+                Some(sample) if sample.visible(&Ray::new(sr.hit_point, *wi), sr) => {
+                    let weight = heuristic.weight(brdf_pdf, sample.pdf());
+                    f * sample.radiance() * (n_dot_wi * weight / brdf_pdf)
+                }
+                _ => Rgb::black(),
+            };
+
+            light_radiance + brdf_radiance
+        })
+    }
+}
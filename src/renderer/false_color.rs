@@ -0,0 +1,78 @@
+use indicatif::ParallelProgressIterator;
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb};
+use crate::renderer::Renderer;
+use crate::sampler::Sampler;
+use crate::world::World;
+
+#[derive(Default, Debug)]
+pub struct FalseColorNormals {}
+
+impl Renderer for FalseColorNormals {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        buffer
+            .buffer_mut()
+            .par_chunks_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+
+                        match world.hit_objects(&ray) {
+                            None => world.background(&ray),
+                            Some(sr) => {
+                                Rgb::new(sr.normal.x.abs(), sr.normal.y.abs(), sr.normal.z.abs())
+                            }
+                        }
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FalseColorIntersectionTests {}
+
+impl Renderer for FalseColorIntersectionTests {
+    type Output = Vec<usize>;
+
+    // I don't think using sample points makes a lot of sense for this
+    fn render_scene<C, S>(&self, world: &World, camera: &C, _sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut intersection_counts = vec![0; x_res * y_res];
+
+        intersection_counts
+            .par_chunks_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, nb_intersects)| {
+                    let ray = camera.generate_ray(c, r, (0.5, 0.5));
+
+                    *nb_intersects = world.count_intersection_tests(&ray)
+                })
+            });
+
+        intersection_counts
+    }
+}
@@ -0,0 +1,71 @@
+use rand::prelude::*;
+use std::ops::Div;
+
+use crate::camera::Camera;
+use crate::film::{Rgb, SampledSpectrum};
+use crate::sampler::Sampler;
+use crate::world::World;
+
+pub use ambient_occlusion::AmbientOcclusion;
+pub use bidirectional::BidirectionalPathTracing;
+pub use direct::DirectIllumination;
+pub use false_color::{FalseColorIntersectionTests, FalseColorNormals};
+pub use photon_mapping::PhotonMapping;
+pub use spectral::SpectralPathTracing;
+pub use volumetric::VolumetricPathTracing;
+
+mod ambient_occlusion;
+mod bidirectional;
+mod direct;
+mod false_color;
+mod photon_mapping;
+mod spectral;
+mod volumetric;
+
+/// Number of times a path is scattered before it may be terminated by russian roulette.
+const MIN_DEPTH: usize = 3;
+
+pub trait Renderer {
+    type Output;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync;
+}
+
+/// The weight of a path, i.e. the fraction of the light it carries, as a color or at sampled
+/// wavelengths.
+trait Throughput: Copy + Div<f64, Output = Self> {
+    /// Returns a single value for the size of the weight, which russian roulette compares against.
+    fn magnitude(&self) -> f64;
+}
+
+impl Throughput for Rgb {
+    fn magnitude(&self) -> f64 {
+        self.luminance()
+    }
+}
+
+impl Throughput for SampledSpectrum {
+    fn magnitude(&self) -> f64 {
+        self.max()
+    }
+}
+
+/// Terminates paths of which the weight became small with russian roulette, once they were
+/// scattered `MIN_DEPTH` times, and compensates the weight of the surviving ones. Returns
+/// whether the path survives.
+fn roulette<T: Throughput, R: Rng>(beta: &mut T, depth: usize, rng: &mut R) -> bool {
+    if depth < MIN_DEPTH {
+        return true;
+    }
+
+    let survival = beta.magnitude().min(0.95);
+    if rng.gen::<f64>() >= survival {
+        return false;
+    }
+
+    *beta = *beta / survival;
+    true
+}
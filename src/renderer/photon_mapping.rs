@@ -0,0 +1,346 @@
+use indicatif::ParallelProgressIterator;
+use rand::prelude::*;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+use crate::brdf::Brdf;
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb};
+use crate::light::LightSelector;
+use crate::material::Material;
+use crate::math::{Distribution1D, Ray};
+use crate::photon_map::{Photon, PhotonKind, PhotonMap};
+use crate::renderer::{DirectIllumination, Renderer};
+use crate::sampler::Sampler;
+use crate::shade_rec::ShadeRec;
+use crate::world::World;
+use crate::Vector;
+
+/// Renders global illumination with photon mapping. Photons are traced from the lights and stored
+/// on diffuse surfaces, in a global map and in a caustic map for photons that only reflected or
+/// refracted specularly. Direct lighting is sampled as with `DirectIllumination`, while the
+/// indirect lighting and caustics are estimated from the density of the nearby photons. The
+/// ambient light of the world is ignored. Lights infinitely far away don't emit photons.
+#[derive(Debug)]
+pub struct PhotonMapping {
+    direct_illumination: DirectIllumination,
+    nb_global_photons: usize,
+    nb_caustic_photons: usize,
+    // number of photons used for every density estimate
+    nb_nearest: usize,
+    // maximum distance of the photons used for a density estimate
+    max_distance: f64,
+    // number of rays traced to gather the indirect light at every camera ray hit, if any
+    nb_final_gather_rays: Option<usize>,
+}
+
+impl PhotonMapping {
+    /// Maximum number of specular bounces of camera rays and of bounces of photons.
+    const MAX_DEPTH: usize = 8;
+
+    /// Creates a new photon mapping renderer. Without final gathering, the indirect lighting is
+    /// estimated straight from the global photon map at the points seen by the camera, which is
+    /// fast but blotchy.
+    pub fn new(
+        nb_global_photons: usize,
+        nb_caustic_photons: usize,
+        nb_nearest: usize,
+        max_distance: f64,
+        nb_final_gather_rays: Option<usize>,
+    ) -> Self {
+        assert!(nb_nearest > 0);
+        assert!(max_distance > 0.);
+
+        Self {
+            direct_illumination: DirectIllumination::default(),
+            nb_global_photons,
+            nb_caustic_photons,
+            nb_nearest,
+            max_distance,
+            nb_final_gather_rays,
+        }
+    }
+
+    /// Emits `nb_photons` photons from the lights of `world`, chosen proportionally to their
+    /// power, and traces them through the scene. Returns the photons stored on diffuse surfaces,
+    /// or, if `caustics` is set, only those that reflected or refracted specularly before
+    /// reaching the first diffuse surface.
+    fn trace_photons(world: &World, nb_photons: usize, caustics: bool) -> Vec<Photon> {
+        let lights = world.lights();
+        if nb_photons == 0 || lights.is_empty() {
+            return Vec::new();
+        }
+
+        let power = lights
+            .iter()
+            .map(|light| light.power().luminance())
+            .collect();
+        let distribution = Distribution1D::new(power);
+
+        (0..nb_photons)
+            .into_par_iter()
+            .flat_map_iter(|_| {
+                let mut rng = thread_rng();
+                let mut photons = Vec::new();
+
+                let (index, pmf, _) = distribution.sample_discrete(rng.gen());
+                let emission = match lights[index].sample_emission(rng.gen(), rng.gen()) {
+                    Some(emission) if pmf > 0. => emission,
+                    _ => return photons.into_iter(),
+                };
+                let mut ray = emission.ray;
+                let mut power = emission.flux / (pmf * nb_photons as f64);
+                let mut kind = PhotonKind::Direct;
+
+                for _ in 0..Self::MAX_DEPTH {
+                    let sr = match world.hit_objects(&ray) {
+                        Some(sr) => sr,
+                        None => break,
+                    };
+                    let wo = -ray.direction().normalize();
+
+                    let (brdf, diffuse): (&dyn Brdf, bool) = match sr.material().unwrap() {
+                        Material::Matte { diffuse_brdf, .. } => (diffuse_brdf, true),
+                        Material::SvMatte { diffuse_brdf, .. } => (diffuse_brdf, true),
+                        Material::Subsurface(subsurface) => (&subsurface.diffuse_brdf, true),
+                        Material::Mirror(brdf) => (brdf, false),
+                        Material::Glass(brdf) => (brdf, false),
+                        Material::Emissive(_) => break,
+                    };
+
+                    let continuation = if diffuse {
+                        if !caustics || kind == PhotonKind::Caustic {
+                            photons.push(Photon {
+                                position: sr.hit_point,
+                                direction: wo,
+                                power,
+                                kind,
+                            });
+                        }
+                        if caustics {
+                            break;
+                        }
+
+                        // russian roulette, proportional to the reflectance
+                        let p = brdf.rho(&sr, &wo).luminance().min(1.);
+                        kind = PhotonKind::Indirect;
+                        p
+                    } else {
+                        if kind == PhotonKind::Direct {
+                            kind = PhotonKind::Caustic;
+                        }
+                        1.
+                    };
+
+                    if rng.gen::<f64>() >= continuation {
+                        break;
+                    }
+
+                    let (f, wi, pdf) = brdf.sample_f_from_light(&sr, &wo, rng.gen());
+                    if pdf == 0. {
+                        break;
+                    }
+
+                    let cos_theta = sr.normal.normalize().dot(&wi).abs() / wi.norm();
+                    power = power * f * (cos_theta / (pdf * continuation));
+                    ray = Ray::new(sr.hit_point, wi);
+                }
+
+                photons.into_iter()
+            })
+            .collect()
+    }
+
+    /// Returns the radiance arriving along `ray`.
+    fn radiance(&self, ray: &Ray, world: &World, maps: &PhotonMaps, depth: usize) -> Rgb {
+        let sr = match world.hit_objects(ray) {
+            Some(sr) => sr,
+            None => return world.background(ray),
+        };
+        let wo = -ray.direction().normalize();
+
+        match sr.material().unwrap() {
+            Material::Matte { diffuse_brdf, .. } => {
+                self.diffuse_radiance(diffuse_brdf, &sr, &wo, world, maps)
+            }
+            Material::SvMatte { diffuse_brdf, .. } => {
+                self.diffuse_radiance(diffuse_brdf, &sr, &wo, world, maps)
+            }
+            Material::Subsurface(subsurface) => {
+                self.diffuse_radiance(&subsurface.diffuse_brdf, &sr, &wo, world, maps)
+            }
+            Material::Emissive(emissive) => emissive.ce * emissive.ls,
+            Material::Mirror(brdf) => self.specular_radiance(brdf, &sr, &wo, world, maps, depth),
+            Material::Glass(brdf) => self.specular_radiance(brdf, &sr, &wo, world, maps, depth),
+        }
+    }
+
+    fn specular_radiance(
+        &self,
+        brdf: &dyn Brdf,
+        sr: &ShadeRec,
+        wo: &Vector,
+        world: &World,
+        maps: &PhotonMaps,
+        depth: usize,
+    ) -> Rgb {
+        if depth >= Self::MAX_DEPTH {
+            return Rgb::black();
+        }
+
+        let (f, wi, pdf) = brdf.sample_f(sr, wo, thread_rng().gen());
+        if pdf == 0. {
+            return Rgb::black();
+        }
+
+        let cos_theta = sr.normal.normalize().dot(&wi).abs() / wi.norm();
+        let radiance = self.radiance(&Ray::new(sr.hit_point, wi), world, maps, depth + 1);
+
+        f * radiance * (cos_theta / pdf)
+    }
+
+    fn diffuse_radiance(
+        &self,
+        brdf: &dyn Brdf,
+        sr: &ShadeRec,
+        wo: &Vector,
+        world: &World,
+        maps: &PhotonMaps,
+    ) -> Rgb {
+        let direct = self
+            .direct_illumination
+            .direct_radiance(brdf, sr, wo, &maps.lights);
+        let caustics = self.estimate(&maps.caustic, brdf, sr, wo, |_| true);
+
+        let indirect = match self.nb_final_gather_rays {
+            None => self.estimate(&maps.global, brdf, sr, wo, |photon| {
+                photon.kind == PhotonKind::Indirect
+            }),
+            Some(nb_rays) => {
+                (0..nb_rays)
+                    .map(|_| {
+                        let (f, wi, pdf) = brdf.sample_f(sr, wo, thread_rng().gen());
+                        if pdf == 0. {
+                            return Rgb::black();
+                        }
+
+                        let ray = Ray::new(sr.hit_point, wi);
+                        let gathered = self.gather(&ray, world, maps, 0);
+                        let cos_theta = sr.normal.normalize().dot(&wi).abs() / wi.norm();
+                        f * gathered * (cos_theta / pdf)
+                    })
+                    .sum::<Rgb>()
+                    / nb_rays.max(1) as f64
+            }
+        };
+
+        direct + caustics + indirect
+    }
+
+    /// Returns the radiance arriving along `ray` that was reflected by a diffuse surface, as
+    /// estimated from the global photon map. Specular surfaces are followed, while emitters are
+    /// skipped as they are accounted for by the direct lighting.
+    fn gather(&self, ray: &Ray, world: &World, maps: &PhotonMaps, depth: usize) -> Rgb {
+        let sr = match world.hit_objects(ray) {
+            Some(sr) => sr,
+            None => return Rgb::black(),
+        };
+        let wo = -ray.direction().normalize();
+
+        let brdf: &dyn Brdf = match sr.material().unwrap() {
+            Material::Matte { diffuse_brdf, .. } => {
+                return self.estimate(&maps.global, diffuse_brdf, &sr, &wo, |_| true)
+            }
+            Material::SvMatte { diffuse_brdf, .. } => {
+                return self.estimate(&maps.global, diffuse_brdf, &sr, &wo, |_| true)
+            }
+            Material::Subsurface(subsurface) => {
+                let diffuse_brdf = &subsurface.diffuse_brdf;
+                return self.estimate(&maps.global, diffuse_brdf, &sr, &wo, |_| true);
+            }
+            Material::Emissive(_) => return Rgb::black(),
+            Material::Mirror(brdf) => brdf,
+            Material::Glass(brdf) => brdf,
+        };
+
+        let (f, wi, pdf) = brdf.sample_f(&sr, &wo, thread_rng().gen());
+        if depth >= Self::MAX_DEPTH || pdf == 0. {
+            return Rgb::black();
+        }
+
+        let cos_theta = sr.normal.normalize().dot(&wi).abs() / wi.norm();
+        let radiance = self.gather(&Ray::new(sr.hit_point, wi), world, maps, depth + 1);
+
+        f * radiance * (cos_theta / pdf)
+    }
+
+    /// Estimates the radiance reflected towards `wo` from the density of the photons in `map`
+    /// near the hit point of `sr`, that arrived at the same side of the surface as `wo`.
+    fn estimate<F>(
+        &self,
+        map: &PhotonMap,
+        brdf: &dyn Brdf,
+        sr: &ShadeRec,
+        wo: &Vector,
+        filter: F,
+    ) -> Rgb
+    where
+        F: Fn(&Photon) -> bool,
+    {
+        let side = sr.normal.dot(wo).signum();
+        let (photons, radius_squared) = map.nearest(
+            &sr.hit_point,
+            self.nb_nearest,
+            self.max_distance,
+            |photon| sr.normal.dot(&photon.direction) * side > 0. && filter(photon),
+        );
+
+        photons
+            .into_iter()
+            .map(|photon| brdf.f(sr, &photon.direction, wo) * photon.power)
+            .sum::<Rgb>()
+            / (PI * radius_squared)
+    }
+}
+
+impl Renderer for PhotonMapping {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let maps = PhotonMaps {
+            global: PhotonMap::new(Self::trace_photons(world, self.nb_global_photons, false)),
+            caustic: PhotonMap::new(Self::trace_photons(world, self.nb_caustic_photons, true)),
+            lights: LightSelector::new(world.lights(), self.direct_illumination.light_selection),
+        };
+
+        buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+                        self.radiance(&ray, world, &maps, 0)
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
+
+struct PhotonMaps<'a> {
+    global: PhotonMap,
+    caustic: PhotonMap,
+    lights: LightSelector<'a>,
+}
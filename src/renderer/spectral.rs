@@ -0,0 +1,165 @@
+use indicatif::ParallelProgressIterator;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use crate::brdf::Brdf;
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb, SampledSpectrum, SampledWavelengths};
+use crate::light::{LightSelection, LightSelector};
+use crate::material::Material;
+use crate::math::Ray;
+use crate::renderer::{roulette, Renderer};
+use crate::sampler::Sampler;
+use crate::world::World;
+
+/// Renders global illumination like `VolumetricPathTracing` does without media, but carries the
+/// radiance along every path at a few sampled wavelengths rather than as a color, such that
+/// dispersive dielectrics split the light into its colors. The colors of the scene are uplifted
+/// to spectra, and the radiance is converted to linear sRGB through CIE XYZ before it's written
+/// to the frame buffer, so the working space must be sRGB. Subsurface scattering materials are
+/// shaded as if they were matte.
+#[derive(Debug)]
+pub struct SpectralPathTracing {
+    light_selection: LightSelection,
+    // maximum number of times a path is scattered
+    max_depth: usize,
+}
+
+impl SpectralPathTracing {
+    pub fn new(light_selection: LightSelection, max_depth: usize) -> Self {
+        Self {
+            light_selection,
+            max_depth,
+        }
+    }
+
+    fn radiance(
+        &self,
+        world: &World,
+        mut ray: Ray,
+        lights: &LightSelector,
+        wavelengths: &mut SampledWavelengths,
+    ) -> SampledSpectrum {
+        let mut rng = thread_rng();
+        let mut radiance = SampledSpectrum::black();
+        let mut beta = SampledSpectrum::constant(1.);
+        // whether emitted light reached along the ray isn't accounted for by sampling the lights
+        let mut specular = true;
+        let mut depth = 0;
+
+        loop {
+            let sr = match world.hit_objects(&ray) {
+                Some(sr) => sr,
+                None => {
+                    let background = if specular {
+                        world.background(&ray)
+                    } else {
+                        world.background_color()
+                    };
+                    radiance += beta * SampledSpectrum::from_rgb(background, wavelengths);
+                    break;
+                }
+            };
+            if depth >= self.max_depth {
+                break;
+            }
+            depth += 1;
+
+            let wo = -ray.direction().normalize();
+            let normal = sr.normal.normalize();
+            let dispersed;
+            let (brdf, is_specular): (&dyn Brdf, bool) = match sr.material().unwrap() {
+                Material::Matte { diffuse_brdf, .. } => (diffuse_brdf, false),
+                Material::SvMatte { diffuse_brdf, .. } => (diffuse_brdf, false),
+                Material::Subsurface(subsurface) => (&subsurface.diffuse_brdf, false),
+                Material::Mirror(brdf) => (brdf, true),
+                Material::Glass(brdf) if brdf.is_dispersive() => {
+                    // only the hero wavelength is refracted into the sampled direction
+                    wavelengths.terminate_secondary();
+                    dispersed = brdf.at_wavelength(wavelengths.hero());
+                    (&dispersed, true)
+                }
+                Material::Glass(brdf) => (brdf, true),
+                Material::Emissive(emissive) => {
+                    if specular {
+                        let emitted = emissive.ce * emissive.ls;
+                        radiance += beta * SampledSpectrum::from_rgb(emitted, wavelengths);
+                    }
+                    break;
+                }
+            };
+
+            if !is_specular {
+                if normal.dot(&wo) <= 0. {
+                    break;
+                }
+
+                let wavelengths = *wavelengths;
+                let direct = lights.estimate(&sr, |light| {
+                    light.average(&sr, &|sample| {
+                        let wi = sample.direction(&sr);
+                        let n_dot_wi = normal.dot(&wi);
+                        if n_dot_wi <= 0. || !sample.visible(&Ray::new(sr.hit_point, *wi), &sr) {
+                            return Rgb::black();
+                        }
+
+                        let f = SampledSpectrum::from_rgb(brdf.f(&sr, &wi, &wo), &wavelengths);
+                        let radiance = SampledSpectrum::from_rgb(sample.radiance(), &wavelengths);
+                        (f * radiance * (n_dot_wi / sample.pdf())).channels()
+                    })
+                });
+                radiance += beta * SampledSpectrum::from_channels(direct);
+            }
+
+            let (f, wi, pdf) = brdf.sample_f(&sr, &wo, rng.gen());
+            let cos_wi = normal.dot(&wi) / wi.norm();
+            if pdf == 0. || (!is_specular && cos_wi <= 0.) {
+                break;
+            }
+
+            beta = beta * SampledSpectrum::from_rgb(f, wavelengths) * (cos_wi.abs() / pdf);
+            ray = Ray::new(sr.hit_point, wi);
+            specular = is_specular;
+
+            if !roulette(&mut beta, depth, &mut rng) {
+                break;
+            }
+        }
+
+        radiance
+    }
+}
+
+impl Renderer for SpectralPathTracing {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let lights = LightSelector::new(world.lights(), self.light_selection);
+
+        buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+                        let mut wavelengths = SampledWavelengths::sample(thread_rng().gen());
+                        let radiance = self.radiance(world, ray, &lights, &mut wavelengths);
+                        radiance.to_rgb(&wavelengths)
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
@@ -0,0 +1,345 @@
+use indicatif::ParallelProgressIterator;
+use nalgebra::Unit;
+use rand::prelude::*;
+use rayon::prelude::*;
+use std::f64::consts::FRAC_1_PI;
+use std::ptr;
+
+use crate::brdf::{Brdf, Lambertian};
+use crate::camera::Camera;
+use crate::film::{FrameBuffer, Rgb};
+use crate::light::{LightSelection, LightSelector};
+use crate::material::Material;
+use crate::math::{OrthonormalBasis, Ray};
+use crate::medium::{HomogeneousMedium, Medium};
+use crate::renderer::{roulette, Renderer};
+use crate::sampler::{cosine_sample_hemisphere, Sampler};
+use crate::shade_rec::ShadeRec;
+use crate::world::World;
+use crate::{Point3, Vector};
+
+/// Renders global illumination in scenes with participating media, by tracing paths from the
+/// camera that are scattered by the media and the surfaces, and sampling the lights at every
+/// scattering point. The light of the lights is attenuated by the media it passes through.
+/// Emitting surfaces only contribute when reached by the camera or after specular scattering,
+/// surfaces only reflect light on the side their normal points to, and the ambient light of the
+/// world is ignored. The camera must lie outside the bounded media. Light enters and leaves
+/// subsurface scattering materials diffusely, and takes a random walk inside them.
+#[derive(Debug)]
+pub struct VolumetricPathTracing {
+    light_selection: LightSelection,
+    // maximum number of times a path is scattered
+    max_depth: usize,
+}
+
+impl VolumetricPathTracing {
+    pub fn new(light_selection: LightSelection, max_depth: usize) -> Self {
+        Self {
+            light_selection,
+            max_depth,
+        }
+    }
+
+    fn radiance(&self, world: &World, mut ray: Ray, lights: &LightSelector) -> Rgb {
+        let mut rng = thread_rng();
+        let mut radiance = Rgb::black();
+        let mut beta = Rgb::white();
+        let mut medium = world.medium();
+        // whether emitted light reached along the ray isn't accounted for by sampling the lights
+        let mut specular = true;
+        let mut depth = 0;
+
+        loop {
+            let sr = world.hit_objects(&ray);
+            let t_surface = sr.as_ref().map_or(f64::INFINITY, |sr| {
+                (sr.hit_point - ray.origin()).norm() / ray.direction().norm()
+            });
+            let boundary = world
+                .hit_medium_boundary(&ray)
+                .filter(|&(t, _)| t < t_surface);
+            let t_max = boundary.map_or(t_surface, |(t, _)| t);
+
+            if let Some(current) = medium {
+                let sample = current.sample(&ray, t_max);
+                beta = beta * sample.weight;
+
+                if let Some(t) = sample.t {
+                    if depth >= self.max_depth || beta == Rgb::black() {
+                        break;
+                    }
+                    depth += 1;
+
+                    let point = ray.origin() + t * ray.direction();
+                    let wo = -ray.direction();
+                    let phase_function = current.phase_function();
+                    let sr = ShadeRec::in_medium(point, world);
+                    radiance += beta
+                        * direct_radiance(&sr, medium, lights, |wi| {
+                            Rgb::white() * phase_function.p(&wo, wi)
+                        });
+
+                    // the phase function is sampled exactly, so the weight doesn't change
+                    let (wi, _) = phase_function.sample_p(&wo, rng.gen());
+                    ray = Ray::new(point, wi);
+                    specular = false;
+
+                    if !roulette(&mut beta, depth, &mut rng) {
+                        break;
+                    }
+                    continue;
+                }
+            }
+
+            if let Some((t, next)) = boundary {
+                // boundaries of media aren't surfaces, the ray just continues in another medium
+                ray = Ray::new(ray.origin() + t * ray.direction(), *ray.direction());
+                medium = next;
+                continue;
+            }
+
+            let sr = match sr {
+                Some(sr) => sr,
+                None => {
+                    let background = if specular {
+                        world.background(&ray)
+                    } else {
+                        world.background_color()
+                    };
+                    radiance += beta * background;
+                    break;
+                }
+            };
+            if depth >= self.max_depth {
+                break;
+            }
+            depth += 1;
+
+            let wo = -ray.direction().normalize();
+            let normal = sr.normal.normalize();
+            let (brdf, is_specular): (&dyn Brdf, bool) = match sr.material().unwrap() {
+                Material::Matte { diffuse_brdf, .. } => (diffuse_brdf, false),
+                Material::SvMatte { diffuse_brdf, .. } => (diffuse_brdf, false),
+                Material::Mirror(brdf) => (brdf, true),
+                Material::Glass(brdf) => (brdf, true),
+                Material::Emissive(emissive) => {
+                    if specular {
+                        radiance += beta * emissive.ce * emissive.ls;
+                    }
+                    break;
+                }
+                Material::Subsurface(subsurface) => {
+                    if normal.dot(&wo) <= 0. {
+                        break;
+                    }
+
+                    let (exit, weight) = match subsurface_walk(&sr, &subsurface.medium, &mut rng) {
+                        Some(exit) => exit,
+                        None => break,
+                    };
+                    beta = beta * weight;
+
+                    // the light leaves the shape diffusely
+                    let normal = exit.normal.normalize();
+                    radiance += beta
+                        * direct_radiance(&exit, medium, lights, |wi| {
+                            Rgb::white() * (normal.dot(wi).max(0.) * FRAC_1_PI)
+                        });
+
+                    // the weight of the cosine distributed direction doesn't change
+                    let brdf = Lambertian::new(1., Rgb::white());
+                    let (_, wi, _) = brdf.sample_f(&exit, &normal, rng.gen());
+                    ray = Ray::new(exit.hit_point, wi);
+                    specular = false;
+
+                    if !roulette(&mut beta, depth, &mut rng) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            if !is_specular {
+                if normal.dot(&wo) <= 0. {
+                    break;
+                }
+
+                radiance += beta
+                    * direct_radiance(&sr, medium, lights, |wi| {
+                        brdf.f(&sr, wi, &wo) * normal.dot(wi).max(0.)
+                    });
+            }
+
+            let (f, wi, pdf) = brdf.sample_f(&sr, &wo, rng.gen());
+            let cos_wi = normal.dot(&wi) / wi.norm();
+            if pdf == 0. || (!is_specular && cos_wi <= 0.) {
+                break;
+            }
+
+            beta = beta * f * (cos_wi.abs() / pdf);
+            ray = Ray::new(sr.hit_point, wi);
+            specular = is_specular;
+
+            if !roulette(&mut beta, depth, &mut rng) {
+                break;
+            }
+        }
+
+        radiance
+    }
+}
+
+impl Renderer for VolumetricPathTracing {
+    type Output = FrameBuffer;
+
+    fn render_scene<C, S>(&self, world: &World, camera: &C, sampler: &S) -> Self::Output
+    where
+        C: Camera + Sync,
+        S: Sampler + Sync,
+    {
+        let (x_res, y_res) = camera.resolution();
+        let mut buffer = FrameBuffer::new(x_res, y_res);
+        let lights = LightSelector::new(world.lights(), self.light_selection);
+
+        buffer
+            .buffer_mut()
+            .par_chunks_exact_mut(x_res)
+            .progress()
+            .enumerate()
+            .for_each(|(r, row)| {
+                row.iter_mut().enumerate().for_each(|(c, pixel)| {
+                    let color = sampler.average(|sample| {
+                        let ray = camera.generate_ray(c, r, sample);
+                        self.radiance(world, ray, &lights)
+                    });
+
+                    pixel.set(color);
+                });
+            });
+
+        buffer
+    }
+}
+
+/// Follows the light that enters the shape of `sr` at its hit point on a random walk through
+/// `medium`, which fills the shape. Returns the point where the light leaves the shape, together
+/// with the fraction of the light that gets there, or `None` if the light doesn't leave it.
+fn subsurface_walk<'a, R: Rng>(
+    sr: &ShadeRec<'a>,
+    medium: &HomogeneousMedium,
+    rng: &mut R,
+) -> Option<(ShadeRec<'a>, Rgb)> {
+    // paths that scatter more often than this barely carry light anymore
+    const MAX_STEPS: usize = 256;
+
+    // the light enters the shape diffusely
+    let basis = OrthonormalBasis::from_vector(&-sr.normal)?;
+    let d = cosine_sample_hemisphere(rng.gen());
+    let mut ray = Ray::new(
+        sr.hit_point,
+        d.x * *basis.u + d.y * *basis.v + d.z * *basis.w,
+    );
+    let mut weight = Rgb::white();
+
+    for _ in 0..MAX_STEPS {
+        let hit = sr.world.hit_objects(&ray)?;
+        let t_max = (hit.hit_point - ray.origin()).norm() / ray.direction().norm();
+        let sample = medium.sample(&ray, t_max);
+        weight = weight * sample.weight;
+        if weight == Rgb::black() {
+            return None;
+        }
+
+        match sample.t {
+            Some(t) => {
+                let point = ray.origin() + t * ray.direction();
+                let (wi, _) = medium
+                    .phase_function()
+                    .sample_p(&-ray.direction(), rng.gen());
+                ray = Ray::new(point, wi);
+            }
+            // only the shape itself, reached from the inside, lets the light out, while other
+            // objects bound the walk
+            None if is_same_shape(&hit, sr) && hit.normal.dot(ray.direction()) > 0. => {
+                return Some((hit, weight))
+            }
+            None => return None,
+        }
+    }
+
+    None
+}
+
+/// Returns whether `a` and `b` lie on the same geometric object.
+fn is_same_shape(a: &ShadeRec, b: &ShadeRec) -> bool {
+    match (a.shape(), b.shape()) {
+        (Some(a), Some(b)) => ptr::eq(a, b),
+        _ => false,
+    }
+}
+
+/// Estimates the light arriving directly from the lights at the point of `sr`, which lies in
+/// `medium`, scattered according to `f`. `f` returns the fraction of the light arriving from a
+/// direction that is scattered, including the cosine factor on surfaces.
+fn direct_radiance<'a, F>(
+    sr: &ShadeRec<'a>,
+    medium: Option<&'a dyn Medium>,
+    lights: &LightSelector,
+    f: F,
+) -> Rgb
+where
+    F: Fn(&Vector) -> Rgb,
+{
+    lights.estimate(sr, |light| {
+        light.average(sr, &|sample| {
+            let wi = sample.direction(sr);
+            let scattered = f(&wi);
+            if scattered == Rgb::black() {
+                return scattered;
+            }
+
+            let transmittance =
+                transmittance(sr.world, &sr.hit_point, &wi, sample.location(), medium);
+            scattered * transmittance * sample.radiance() / sample.pdf()
+        })
+    })
+}
+
+/// Returns the fraction of the light that travels from `point` along `direction` to `location`,
+/// or out of the scene if `None`, through the media along the way, starting in `medium`. Returns
+/// black if a surface blocks the way.
+fn transmittance<'a>(
+    world: &'a World,
+    point: &Point3,
+    direction: &Unit<Vector>,
+    location: Option<Point3>,
+    mut medium: Option<&'a dyn Medium>,
+) -> Rgb {
+    // avoids the light itself being reported as an occluder
+    let mut remaining = location.map_or(f64::INFINITY, |location| {
+        (1. - 1e-6) * (location - point).norm()
+    });
+    let mut ray = Ray::new(*point, **direction);
+    if world.hit_any_object_where(&ray, |hit| hit.t < remaining) {
+        return Rgb::black();
+    }
+
+    let mut transmittance = Rgb::white();
+    loop {
+        let boundary = world
+            .hit_medium_boundary(&ray)
+            .filter(|&(t, _)| t < remaining);
+        let t = boundary.map_or(remaining, |(t, _)| t);
+        if let Some(current) = medium {
+            transmittance = transmittance * current.transmittance(&ray, t);
+        }
+
+        match boundary {
+            Some((t, next)) => {
+                ray = Ray::new(ray.origin() + t * ray.direction(), **direction);
+                remaining -= t;
+                medium = next;
+            }
+            None => return transmittance,
+        }
+    }
+}
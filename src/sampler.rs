@@ -1,7 +1,9 @@
 use itertools::Itertools;
 use rand::prelude::*;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 
 use crate::film::Rgb;
+use crate::Vector;
 
 pub type Sample = (f64, f64);
 
@@ -48,6 +50,8 @@ impl Sampler for RegularSampler {
     }
 }
 
+/// Sampler that jitters a sample in every cell of a square grid, of which the number of samples
+/// is rounded down to a perfect square.
 pub struct JitteredSampler {
     nb_samples: usize,
 }
@@ -60,9 +64,8 @@ impl JitteredSampler {
 
 impl Sampler for JitteredSampler {
     fn average<F: Fn(Sample) -> Rgb>(&self, f: F) -> Rgb {
-        let n = (self.nb_samples as f64).sqrt();
-        let inv_n = 1. / n;
-        let n = n as usize;
+        let n = (self.nb_samples as f64).sqrt() as usize;
+        let inv_n = 1. / n as f64;
 
         (0..n)
             .cartesian_product(0..n)
@@ -73,7 +76,7 @@ impl Sampler for JitteredSampler {
                 f((x, y))
             })
             .sum::<Rgb>()
-            / self.nb_samples as f64
+            / (n * n) as f64
     }
 }
 
@@ -96,3 +99,106 @@ impl Sampler for UniformSampler {
             / self.nb_samples as f64
     }
 }
+
+/// Sampler that uses the Hammersley point set, of which the points are well distributed over the
+/// unit square for any number of samples. The set is randomly shifted for every call, so the
+/// averages remain unbiased.
+pub struct HammersleySampler {
+    nb_samples: usize,
+}
+
+impl HammersleySampler {
+    pub fn new(nb_samples: usize) -> Self {
+        assert!(nb_samples > 0);
+
+        Self { nb_samples }
+    }
+}
+
+impl Sampler for HammersleySampler {
+    fn average<F: Fn(Sample) -> Rgb>(&self, f: F) -> Rgb {
+        let (shift_x, shift_y) = thread_rng().gen::<(f64, f64)>();
+
+        (0..self.nb_samples)
+            .map(|i| {
+                let x = i as f64 / self.nb_samples as f64;
+                let y = (i as u32).reverse_bits() as f64 / (1u64 << 32) as f64;
+
+                f(((x + shift_x).fract(), (y + shift_y).fract()))
+            })
+            .sum::<Rgb>()
+            / self.nb_samples as f64
+    }
+}
+
+/// Maps a sample from the unit square to the unit disk, preserving relative areas.
+pub fn concentric_sample_disk(sample: Sample) -> (f64, f64) {
+    let x = 2. * sample.0 - 1.;
+    let y = 2. * sample.1 - 1.;
+
+    if x == 0. && y == 0. {
+        return (0., 0.);
+    }
+
+    let (r, theta) = if x.abs() > y.abs() {
+        (x, FRAC_PI_4 * (y / x))
+    } else {
+        (y, FRAC_PI_2 - FRAC_PI_4 * (x / y))
+    };
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+/// Maps a sample from the unit square to a direction on the unit sphere, with a uniform density
+/// of `1 / (4 pi)`.
+pub fn uniform_sample_sphere(sample: Sample) -> Vector {
+    let z = 1. - 2. * sample.0;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * sample.1;
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Maps a sample from the unit square to a direction within the cone around the z-axis with the
+/// given maximal angle, with a uniform density of `1 / (2 pi (1 - cos_theta_max))`.
+pub fn uniform_sample_cone(sample: Sample, cos_theta_max: f64) -> Vector {
+    let cos_theta = (1. - sample.0) + sample.0 * cos_theta_max;
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * sample.1;
+
+    Vector::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+/// Maps a sample from the unit square to a direction in the hemisphere around the z-axis, with a
+/// density of `cos(theta) / pi`.
+pub fn cosine_sample_hemisphere(sample: Sample) -> Vector {
+    let (x, y) = concentric_sample_disk(sample);
+    let z = (1. - x * x - y * y).max(0.).sqrt();
+
+    Vector::new(x, y, z)
+}
+
+/// Heuristic used to weight the samples of multiple sampling strategies that estimate the same
+/// integral.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Heuristic {
+    Balance,
+    Power,
+}
+
+impl Heuristic {
+    /// Returns the weight of a sample taken with density `f_pdf`, when the other strategy would
+    /// have taken it with density `g_pdf`.
+    pub fn weight(self, f_pdf: f64, g_pdf: f64) -> f64 {
+        let (f, g) = match self {
+            Heuristic::Balance => (f_pdf, g_pdf),
+            Heuristic::Power => (f_pdf * f_pdf, g_pdf * g_pdf),
+        };
+
+        if f + g > 0. {
+            f / (f + g)
+        } else {
+            0.
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use ::gltf::camera::Projection;
+use ::gltf::image::Format;
+use ::gltf::khr_lights_punctual::Kind;
+use ::gltf::mesh::Mode;
+use ::gltf::{buffer, image, Node, Primitive};
+use nalgebra::Matrix4;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::brdf::{Dielectric, Lambertian, PerfectSpecular, SvLambertian};
+use crate::camera::CameraBuilder;
+use crate::film::{ColorSpace, Encoding, Rgb};
+use crate::light::{DirectionalLight, Light, PointLight, SpotLight, LUMINOUS_EFFICACY};
+use crate::material::{Emissive, Material};
+use crate::math::Transformation;
+use crate::shape::{Compound, GeometricObject, Obj, SmoothTriangle};
+use crate::texture::ImageTexture;
+use crate::world::WorldBuilder;
+use crate::{Point2, Point3, Vector};
+
+/// The objects, lights and cameras of the default scene of a glTF 2.0 asset.
+pub struct GltfScene {
+    pub geometric_objects: Vec<GeometricObject>,
+    pub lights: Vec<Box<dyn Light + Sync>>,
+    pub cameras: Vec<GltfCamera>,
+}
+
+impl GltfScene {
+    /// Loads the default scene, or else the first scene, of the `.gltf` or `.glb` file at
+    /// `path`. Materials are mapped onto the closest materials of this crate, and lights are
+    /// read from the `KHR_lights_punctual` extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GltfError> {
+        let (document, buffers, images) = ::gltf::import(path)?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or(GltfError::MissingScene)?;
+
+        let mut importer = Importer {
+            buffers,
+            images,
+            textures: HashMap::new(),
+            meshes: HashMap::new(),
+            scene: Self {
+                geometric_objects: Vec::new(),
+                lights: Vec::new(),
+                cameras: Vec::new(),
+            },
+        };
+        for node in scene.nodes() {
+            importer.visit(&node, &Matrix4::identity())?;
+        }
+
+        Ok(importer.scene)
+    }
+
+    /// Adds the objects and lights of this scene to `builder`.
+    pub fn add_to(self, builder: WorldBuilder) -> WorldBuilder {
+        self.lights
+            .into_iter()
+            .fold(builder, |builder, light| builder.light(light))
+            .geometric_objects(self.geometric_objects)
+    }
+}
+
+/// A perspective camera of a glTF scene.
+#[derive(Debug, Clone)]
+pub struct GltfCamera {
+    pub name: Option<String>,
+    pub origin: Point3,
+    pub look_at: Vector,
+    pub up: Vector,
+    // vertical field of view, in degrees
+    pub yfov: f64,
+}
+
+impl GltfCamera {
+    /// Returns a builder for this camera, rendering images of `x_res` by `y_res` pixels.
+    pub fn builder(&self, x_res: usize, y_res: usize) -> CameraBuilder {
+        // the builder expects the horizontal field of view
+        let aspect_ratio = x_res as f64 / y_res as f64;
+        let fov = 2. * (aspect_ratio * (0.5 * self.yfov.to_radians()).tan()).atan();
+
+        CameraBuilder::new(self.origin)
+            .look_at(self.look_at)
+            .up(self.up)
+            .fov(fov.to_degrees())
+            .x_res(x_res)
+            .y_res(y_res)
+    }
+}
+
+struct Importer {
+    buffers: Vec<buffer::Data>,
+    images: Vec<image::Data>,
+    // textures by the index of their image and whether they hold colors
+    textures: HashMap<(usize, bool), ImageTexture>,
+    // triangles by the index of their mesh and primitive, if the primitive has any
+    meshes: HashMap<(usize, usize), Option<Arc<Compound<'static, SmoothTriangle>>>>,
+    scene: GltfScene,
+}
+
+impl Importer {
+    /// Adds the contents of `node` and its children, of which `parent` transforms the
+    /// coordinates to world coordinates.
+    fn visit(&mut self, node: &Node, parent: &Matrix4<f64>) -> Result<(), GltfError> {
+        let local = node.transform().matrix();
+        let matrix = parent * Matrix4::from_fn(|row, column| local[column][row] as f64);
+        let transformation = || {
+            Transformation::from_matrix(matrix)
+                .ok_or(GltfError::SingularTransformation(node.index()))
+        };
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                // the nodes referencing the same mesh share its triangles
+                let key = (mesh.index(), primitive.index());
+                let triangles = match self.meshes.get(&key) {
+                    Some(triangles) => triangles.clone(),
+                    None => {
+                        let triangles = self.primitive(&primitive)?.map(Arc::new);
+                        self.meshes.insert(key, triangles.clone());
+                        triangles
+                    }
+                };
+
+                if let Some(triangles) = triangles {
+                    let material = self.material(&primitive.material());
+                    self.scene.geometric_objects.push(GeometricObject::instance(
+                        &triangles,
+                        transformation()?,
+                        material,
+                    ));
+                }
+            }
+        }
+
+        let location = Point3::from((matrix * Point3::origin().to_homogeneous()).xyz());
+        // cameras and lights look along their negative z-axis
+        let forward = (matrix * -Vector::z().to_homogeneous()).xyz();
+
+        if let Some(camera) = node.camera() {
+            if let Projection::Perspective(perspective) = camera.projection() {
+                self.scene.cameras.push(GltfCamera {
+                    name: camera.name().map(str::to_owned),
+                    origin: location,
+                    look_at: forward,
+                    up: (matrix * Vector::y().to_homogeneous()).xyz(),
+                    yfov: (perspective.yfov() as f64).to_degrees(),
+                });
+            }
+        }
+
+        if let Some(light) = node.light() {
+            let [red, green, blue] = light.color();
+            let color = Rgb::new(red as f64, green as f64, blue as f64);
+            let intensity = light.intensity() as f64;
+
+            let light: Box<dyn Light + Sync> = match light.kind() {
+                // in lx
+                Kind::Directional => Box::new(DirectionalLight::from_illuminance(
+                    intensity, color, forward,
+                )),
+                // in cd
+                Kind::Point => Box::new(PointLight::new(
+                    intensity / LUMINOUS_EFFICACY,
+                    color,
+                    location,
+                )),
+                Kind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => Box::new(SpotLight::new(
+                    intensity / LUMINOUS_EFFICACY,
+                    color,
+                    location,
+                    forward,
+                    (outer_cone_angle as f64).to_degrees(),
+                    (inner_cone_angle as f64).to_degrees(),
+                )),
+            };
+            self.scene.lights.push(light);
+        }
+
+        for child in node.children() {
+            self.visit(&child, &matrix)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the triangles of `primitive`, or returns `None` if it consists of points or lines,
+    /// or has no triangles.
+    fn primitive(
+        &mut self,
+        primitive: &Primitive,
+    ) -> Result<Option<Compound<'static, SmoothTriangle>>, GltfError> {
+        let buffers = &self.buffers;
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let gltf_material = primitive.material();
+        let base_color_texture = gltf_material.pbr_metallic_roughness().base_color_texture();
+        let tex_coord = base_color_texture
+            .as_ref()
+            .map_or(0, |info| info.tex_coord());
+
+        let vertexes = reader
+            .read_positions()
+            .ok_or(GltfError::MissingPositions(primitive.index()))?
+            .map(|[x, y, z]| Point3::new(x as f64, y as f64, z as f64))
+            .collect::<Vec<_>>();
+        let normals = reader.read_normals().map_or_else(Vec::new, |normals| {
+            normals
+                .map(|[x, y, z]| Vector::new(x as f64, y as f64, z as f64))
+                .collect()
+        });
+        // the v axis of glTF points down the image
+        let texture_coordinates =
+            reader
+                .read_tex_coords(tex_coord)
+                .map_or_else(Vec::new, |coordinates| {
+                    coordinates
+                        .into_f32()
+                        .map(|[u, v]| Point2::new(u as f64, 1. - v as f64))
+                        .collect()
+                });
+        if (!normals.is_empty() && normals.len() != vertexes.len())
+            || (!texture_coordinates.is_empty() && texture_coordinates.len() != vertexes.len())
+        {
+            return Err(GltfError::MismatchedAttributes(primitive.index()));
+        }
+
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+            None => (0..vertexes.len()).collect::<Vec<_>>(),
+        };
+        if let Some(&index) = indices.iter().find(|&&index| index >= vertexes.len()) {
+            return Err(GltfError::InvalidIndex(primitive.index(), index));
+        }
+
+        let triangles = match primitive.mode() {
+            Mode::Triangles => indices
+                .chunks_exact(3)
+                .map(|corners| [corners[0], corners[1], corners[2]])
+                .collect::<Vec<_>>(),
+            Mode::TriangleStrip => (2..indices.len())
+                .map(|i| {
+                    // every other triangle is flipped to keep the winding order
+                    if i % 2 == 0 {
+                        [indices[i - 2], indices[i - 1], indices[i]]
+                    } else {
+                        [indices[i - 1], indices[i - 2], indices[i]]
+                    }
+                })
+                .collect(),
+            Mode::TriangleFan => (2..indices.len())
+                .map(|i| [indices[0], indices[i - 1], indices[i]])
+                .collect(),
+            _ => return Ok(None),
+        };
+        if triangles.is_empty() {
+            return Ok(None);
+        }
+
+        let mut obj = Obj::new(vertexes, normals, texture_coordinates, &triangles);
+        if let Some(normal_texture) = gltf_material.normal_texture() {
+            let texture = self.texture(normal_texture.texture().source().index(), false);
+            obj = obj.with_normal_map(texture, normal_texture.scale() as f64);
+        }
+
+        Ok(Some(obj.smooth()))
+    }
+
+    /// Maps a metallic-roughness material onto the closest material of this crate. Emitting
+    /// materials become emissive, transmitting ones glass, metals mirrors and all others matte,
+    /// textured when there is a base color texture. Roughness, occlusion and the emissive
+    /// texture aren't supported.
+    fn material(&mut self, material: &::gltf::Material) -> Material {
+        let pbr = material.pbr_metallic_roughness();
+        let [red, green, blue, _] = pbr.base_color_factor();
+        let base_color = Rgb::new(red as f64, green as f64, blue as f64).clamp(0., 1.);
+
+        let [red, green, blue] = material.emissive_factor();
+        let ls = red.max(green).max(blue) as f64;
+        if ls > 0. {
+            let ce = Rgb::new(red as f64, green as f64, blue as f64) / ls;
+            return Material::Emissive(Emissive::new(ls, ce));
+        }
+
+        let transmission = material
+            .transmission()
+            .map_or(0., |transmission| transmission.transmission_factor());
+        if transmission > 0. {
+            let ior = material.ior().unwrap_or(1.5) as f64;
+            return Material::Glass(Dielectric::new(ior, Rgb::white(), base_color));
+        }
+
+        if pbr.metallic_factor() >= 0.5 {
+            return Material::Mirror(PerfectSpecular::new(1., base_color));
+        }
+
+        match pbr.base_color_texture() {
+            Some(info) => {
+                // the factor scales the texture, and can only be applied as a whole
+                let kd = base_color.average();
+                let texture = self.texture(info.texture().source().index(), true);
+                Material::SvMatte {
+                    ambient_brdf: SvLambertian::new(kd, Box::new(texture.clone())),
+                    diffuse_brdf: SvLambertian::new(kd, Box::new(texture)),
+                }
+            }
+            None => Material::Matte {
+                ambient_brdf: Lambertian::new(1., base_color),
+                diffuse_brdf: Lambertian::new(1., base_color),
+            },
+        }
+    }
+
+    /// Returns the texture of the image at `index`, which is sRGB encoded if it holds colors and
+    /// linear otherwise.
+    fn texture(&mut self, index: usize, is_color: bool) -> ImageTexture {
+        let images = &self.images;
+        self.textures
+            .entry((index, is_color))
+            .or_insert_with(|| {
+                let encoding = if is_color {
+                    Encoding::SRGB
+                } else {
+                    Encoding::LINEAR_SRGB
+                };
+                let image = &images[index];
+
+                ImageTexture::from_pixels(
+                    image.width,
+                    image.height,
+                    pixels(image),
+                    &encoding,
+                    ColorSpace::Srgb,
+                )
+            })
+            .clone()
+    }
+}
+
+/// Returns the pixels of `image` row by row from the top, with components in [0, 1].
+fn pixels(image: &image::Data) -> Vec<Rgb> {
+    let (channels, is_16_bit, is_bgr) = match image.format {
+        Format::R8 => (1, false, false),
+        Format::R8G8 => (2, false, false),
+        Format::R8G8B8 => (3, false, false),
+        Format::R8G8B8A8 => (4, false, false),
+        Format::B8G8R8 => (3, false, true),
+        Format::B8G8R8A8 => (4, false, true),
+        Format::R16 => (1, true, false),
+        Format::R16G16 => (2, true, false),
+        Format::R16G16B16 => (3, true, false),
+        Format::R16G16B16A16 => (4, true, false),
+    };
+
+    let values = if is_16_bit {
+        image
+            .pixels
+            .chunks_exact(2)
+            .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]) as f64 / 65535.)
+            .collect::<Vec<_>>()
+    } else {
+        image
+            .pixels
+            .iter()
+            .map(|&byte| byte as f64 / 255.)
+            .collect()
+    };
+
+    values
+        .chunks_exact(channels)
+        .map(|pixel| match channels {
+            // gray, possibly with alpha
+            1 | 2 => Rgb::new(pixel[0], pixel[0], pixel[0]),
+            _ if is_bgr => Rgb::new(pixel[2], pixel[1], pixel[0]),
+            _ => Rgb::new(pixel[0], pixel[1], pixel[2]),
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum GltfError {
+    Gltf(::gltf::Error),
+    MissingScene,
+    // the index of the node
+    SingularTransformation(usize),
+    // the index of the primitive within its mesh
+    MissingPositions(usize),
+    MismatchedAttributes(usize),
+    // the index of the primitive within its mesh and the invalid index
+    InvalidIndex(usize, usize),
+}
+
+impl Display for GltfError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GltfError::Gltf(err) => write!(f, "failed to import glTF file: {}", err),
+            GltfError::MissingScene => write!(f, "no scene"),
+            GltfError::SingularTransformation(node) => {
+                write!(f, "node {} has a singular transformation", node)
+            }
+            GltfError::MissingPositions(primitive) => {
+                write!(f, "primitive {} has no positions", primitive)
+            }
+            GltfError::MismatchedAttributes(primitive) => write!(
+                f,
+                "primitive {} has attributes of different lengths",
+                primitive
+            ),
+            GltfError::InvalidIndex(primitive, index) => {
+                write!(f, "primitive {} has an invalid index: {}", primitive, index)
+            }
+        }
+    }
+}
+
+impl Error for GltfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GltfError::Gltf(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<::gltf::Error> for GltfError {
+    fn from(err: ::gltf::Error) -> Self {
+        GltfError::Gltf(err)
+    }
+}
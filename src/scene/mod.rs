@@ -0,0 +1,3 @@
+pub use self::gltf::{GltfCamera, GltfError, GltfScene};
+
+mod gltf;
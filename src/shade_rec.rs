@@ -1,3 +1,4 @@
+use crate::material::Material;
 use crate::shape::GeometricObject;
 use crate::world::World;
 use crate::{Point2, Point3, Vector};
@@ -10,13 +11,38 @@ pub struct ShadeRec<'a> {
     pub normal: Vector,
     pub direction: Vector,
     pub world: &'a World<'a>,
-    pub(crate) shape: NonNull<GeometricObject>,
+    // `None` for points inside a medium
+    pub(crate) shape: Option<NonNull<GeometricObject>>,
+    // index of the material of the part of the shape that was hit
+    pub(crate) material: usize,
 }
 
 impl<'a> ShadeRec<'a> {
-    pub fn shape(&self) -> &GeometricObject {
+    /// Creates a shade record for a point inside a medium, which doesn't lie on a surface and
+    /// has no normal.
+    pub(crate) fn in_medium(point: Point3, world: &'a World<'a>) -> Self {
+        Self {
+            hit_point: point,
+            local_hit_point: point,
+            uv: Point2::origin(),
+            normal: Vector::zeros(),
+            direction: Vector::zeros(),
+            world,
+            shape: None,
+            material: 0,
+        }
+    }
+
+    /// Returns the shape that was hit, or `None` for points inside a medium.
+    pub fn shape(&self) -> Option<&GeometricObject> {
         // safety: shapes are contained withing a world, so this reference will
         // at leas live as long as 'a.
-        unsafe { self.shape.as_ref() }
+        self.shape.as_ref().map(|shape| unsafe { shape.as_ref() })
+    }
+
+    /// Returns the material of the part of the shape that was hit, or `None` for points inside a
+    /// medium.
+    pub fn material(&self) -> Option<&Material> {
+        self.shape().map(|shape| &shape.materials()[self.material])
     }
 }
@@ -65,6 +65,22 @@ impl Aabb {
     }
 
     pub fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let (t0, t1) = self.intersect_range(ray)?;
+
+        if t1 > K_EPSILON {
+            if t0 > K_EPSILON {
+                Some(t0)
+            } else {
+                Some(t1)
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the range of parameters along the line through `ray` that lies inside this box,
+    /// including negative ones.
+    pub fn intersect_range(&self, ray: &Ray) -> Option<(f64, f64)> {
         let ox = ray.origin().x;
         let oy = ray.origin().y;
         let oz = ray.origin().z;
@@ -97,15 +113,12 @@ impl Aabb {
         // find largest entering t value
         let t0 = tx_min.max(ty_min).max(tz_min);
 
-        // find smallest exiting t value
-        let t1 = tx_max.min(ty_max).min(tz_max);
+        // find smallest exiting t value, enlarged by the bound on the rounding errors of the slab
+        // tests, such that rays grazing the corners of triangles aren't culled
+        let t1 = tx_max.min(ty_max).min(tz_max) * (1. + 2. * gamma(3));
 
-        if t0 <= t1 && t1 > K_EPSILON {
-            if t0 > K_EPSILON {
-                Some(t0)
-            } else {
-                Some(t1)
-            }
+        if t0 <= t1 {
+            Some((t0, t1))
         } else {
             None
         }
@@ -208,3 +221,9 @@ impl Union<Aabb> for Aabb {
         Self { p0, p1 }
     }
 }
+
+/// Returns the bound on the relative error of `n` floating point operations.
+fn gamma(n: u32) -> f64 {
+    let n_epsilon = n as f64 * f64::EPSILON * 0.5;
+    n_epsilon / (1. - n_epsilon)
+}
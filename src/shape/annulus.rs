@@ -0,0 +1,95 @@
+use nalgebra::Unit;
+use std::f64::consts::PI;
+
+use crate::math::Ray;
+use crate::sampler::Sample;
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample};
+use crate::{Point2, Point3, Vector, K_EPSILON};
+
+/// A disk with a hole in the xy-plane, centered at the origin and facing the positive z-axis.
+#[derive(Debug, Copy, Clone)]
+pub struct Annulus {
+    inner_radius: f64,
+    outer_radius: f64,
+}
+
+impl Annulus {
+    pub fn new(inner_radius: f64, outer_radius: f64) -> Self {
+        assert!(inner_radius >= 0.);
+        assert!(inner_radius < outer_radius);
+
+        Self {
+            inner_radius,
+            outer_radius,
+        }
+    }
+}
+
+impl Bounded for Annulus {
+    fn bbox(&self) -> Aabb {
+        Aabb::new(
+            Point3::new(-self.outer_radius, -self.outer_radius, 0.),
+            Point3::new(self.outer_radius, self.outer_radius, 0.),
+        )
+    }
+}
+
+impl Intersect for Annulus {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        if ray.direction().z == 0. {
+            return None;
+        }
+
+        let t = -ray.origin().z / ray.direction().z;
+        if t <= K_EPSILON {
+            return None;
+        }
+
+        let local_hit_point = ray.origin() + t * ray.direction();
+        let distance = local_hit_point.x.hypot(local_hit_point.y);
+        if distance < self.inner_radius || distance > self.outer_radius {
+            return None;
+        }
+
+        let phi = local_hit_point
+            .y
+            .atan2(local_hit_point.x)
+            .rem_euclid(2. * PI);
+        let uv = Point2::new(
+            phi / (2. * PI),
+            (self.outer_radius - distance) / (self.outer_radius - self.inner_radius),
+        );
+
+        Some(Hit {
+            t,
+            normal: Vector::new(0., 0., 1.),
+            local_hit_point,
+            shape: (),
+            uv,
+        })
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
+
+impl Sampleable for Annulus {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        let inner_squared = self.inner_radius * self.inner_radius;
+        let outer_squared = self.outer_radius * self.outer_radius;
+        let radius = (inner_squared + sample.0 * (outer_squared - inner_squared)).sqrt();
+        let phi = 2. * PI * sample.1;
+
+        SurfaceSample {
+            point: Point3::new(radius * phi.cos(), radius * phi.sin(), 0.),
+            normal: Unit::new_unchecked(Vector::new(0., 0., 1.)),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        PI * (self.outer_radius.powi(2) - self.inner_radius.powi(2))
+    }
+}
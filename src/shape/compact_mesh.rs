@@ -0,0 +1,170 @@
+use std::ptr::NonNull;
+
+use crate::math::Ray;
+use crate::shape::compound::Compound;
+use crate::shape::obj::{face_normal, intersect_triangle, NormalMap};
+use crate::shape::{Aabb, Bounded, Hit, Intersect};
+use crate::{Point2, Point3, Vector};
+
+/// A triangle mesh that stores its vertexes in single precision and its triangles as indices into
+/// them, for meshes of millions of triangles. It intersects as the triangles of `Obj::smooth` or
+/// `Obj::flat`, up to the rounding of the vertex data.
+pub struct CompactMesh {
+    // the triangles point into the buffers, so they must be dropped first
+    triangles: Compound<'static, CompactTriangle>,
+    buffers: Box<Buffers>,
+}
+
+/// The vertex data of a compact mesh, of which the corners share a single index.
+pub(super) struct Buffers {
+    pub(super) positions: Vec<[f32; 3]>,
+    pub(super) normals: Vec<[f32; 3]>,
+    pub(super) texture_coordinates: Vec<[f32; 2]>,
+    pub(super) indices: Vec<[u32; 3]>,
+    // whether the normals are interpolated over the triangles
+    pub(super) smooth: bool,
+    pub(super) normal_map: Option<NormalMap>,
+}
+
+impl CompactMesh {
+    pub(super) fn new(buffers: Buffers) -> Self {
+        assert!(buffers.positions.len() <= u32::MAX as usize);
+        assert!(buffers.indices.len() <= u32::MAX as usize);
+
+        let buffers = Box::new(buffers);
+        let pointer = NonNull::from(buffers.as_ref());
+        let triangles = (0..buffers.indices.len() as u32)
+            .map(|index| CompactTriangle {
+                buffers: pointer,
+                index,
+            })
+            .collect();
+
+        Self {
+            triangles: Compound::new(triangles),
+            buffers,
+        }
+    }
+
+    pub fn nb_triangles(&self) -> usize {
+        self.buffers.indices.len()
+    }
+
+    pub fn nb_vertexes(&self) -> usize {
+        self.buffers.positions.len()
+    }
+}
+
+impl Bounded for CompactMesh {
+    fn bbox(&self) -> Aabb {
+        self.triangles.bbox()
+    }
+}
+
+impl Intersect for CompactMesh {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        self.triangles.intersect(ray)
+    }
+
+    fn count_intersection_tests(&self, ray: &Ray) -> usize {
+        self.triangles.count_intersection_tests(ray)
+    }
+
+    fn hit(&self, ray: &Ray) -> bool {
+        self.triangles.hit(ray)
+    }
+}
+
+/// A triangle of a compact mesh, given by its index in the buffers of the mesh.
+struct CompactTriangle {
+    buffers: NonNull<Buffers>,
+    index: u32,
+}
+
+// safety: the buffers are boxed by the mesh, which owns the triangles, and are never mutated.
+unsafe impl Send for CompactTriangle {}
+unsafe impl Sync for CompactTriangle {}
+
+impl CompactTriangle {
+    fn buffers(&self) -> &Buffers {
+        // safety: the buffers outlive the triangles, see `CompactMesh`
+        unsafe { self.buffers.as_ref() }
+    }
+
+    fn corners(&self) -> [usize; 3] {
+        let [a, b, c] = self.buffers().indices[self.index as usize];
+        [a as usize, b as usize, c as usize]
+    }
+
+    fn position(&self, vertex: usize) -> Point3 {
+        let [x, y, z] = self.buffers().positions[vertex];
+        Point3::new(x as f64, y as f64, z as f64)
+    }
+
+    fn normal(&self, vertex: usize) -> Vector {
+        let [x, y, z] = self.buffers().normals[vertex];
+        Vector::new(x as f64, y as f64, z as f64)
+    }
+
+    fn texture_coordinates(&self, vertex: usize) -> Point2 {
+        let [u, v] = self.buffers().texture_coordinates[vertex];
+        Point2::new(u as f64, v as f64)
+    }
+}
+
+impl Bounded for CompactTriangle {
+    fn bbox(&self) -> Aabb {
+        let [a, b, c] = self.corners();
+        let v0 = self.position(a);
+        let v1 = self.position(b);
+        let v2 = self.position(c);
+
+        Aabb::new(v0.inf(&v1).inf(&v2), v0.sup(&v1).sup(&v2))
+    }
+}
+
+impl Intersect for CompactTriangle {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        let [a, b, c] = self.corners();
+        let v0 = self.position(a);
+        let v1 = self.position(b);
+        let v2 = self.position(c);
+        let (t, beta, gamma) = intersect_triangle(v0, v1, v2, ray)?;
+        let alpha = 1. - beta - gamma;
+
+        let vt0 = self.texture_coordinates(a);
+        let vt1 = self.texture_coordinates(b);
+        let vt2 = self.texture_coordinates(c);
+        let uv = Point2::from(alpha * vt0.coords + beta * vt1.coords + gamma * vt2.coords);
+
+        let n0 = self.normal(a);
+        let n1 = self.normal(b);
+        let n2 = self.normal(c);
+        let buffers = self.buffers();
+        let normal = if buffers.smooth {
+            let normal = alpha * n0 + beta * n1 + gamma * n2;
+            match &buffers.normal_map {
+                Some(normal_map) => normal_map.apply([v0, v1, v2], [vt0, vt1, vt2], normal, uv),
+                None => normal,
+            }
+        } else {
+            face_normal(v0, v1, v2, n0 + n1 + n2)
+        };
+
+        Some(Hit {
+            t,
+            normal,
+            local_hit_point: ray.origin() + t * ray.direction(),
+            shape: (),
+            uv,
+        })
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
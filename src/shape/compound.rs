@@ -56,6 +56,10 @@ impl<S: Intersect> Intersect for Compound<S> {
 
 #[cfg(not(any(feature = "bvh")))]
 impl<S: Intersect> Compound<S> {
+    pub fn shapes(&self) -> &[S] {
+        &self.shapes
+    }
+
     pub fn intersect_any_where<P>(&self, ray: &Ray, p: P) -> bool
     where
         P: Fn(Hit<S::Intersection>) -> bool,
@@ -108,6 +112,10 @@ impl<'a, S: Intersect> Intersect for Compound<'a, S> {
 
 #[cfg(feature = "bvh")]
 impl<'a, S: Intersect> Compound<'a, S> {
+    pub fn shapes(&self) -> &[S] {
+        self.bvh.shapes()
+    }
+
     pub fn intersect_any_where<F>(&self, ray: &Ray, f: F) -> bool
     where
         F: Fn(Hit<S::Intersection>) -> bool,
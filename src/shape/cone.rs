@@ -0,0 +1,124 @@
+use nalgebra::Unit;
+use std::f64::consts::PI;
+
+use crate::math::{quadratic, Ray};
+use crate::sampler::Sample;
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample};
+use crate::{Point2, Point3, Vector, K_EPSILON};
+
+/// An open cone around the z-axis, with its base of radius `radius` in the xy-plane and its apex
+/// at height `height`.
+#[derive(Debug, Copy, Clone)]
+pub struct Cone {
+    radius: f64,
+    height: f64,
+}
+
+impl Cone {
+    pub fn new(radius: f64, height: f64) -> Self {
+        assert!(radius > 0.);
+        assert!(height > 0.);
+
+        Self { radius, height }
+    }
+
+    /// Returns the outward normal at `point` on the cone, which isn't normalized.
+    fn normal(&self, point: &Point3) -> Vector {
+        let k = (self.radius / self.height).powi(2);
+        let normal = Vector::new(point.x, point.y, k * (self.height - point.z));
+
+        // the normal vanishes at the apex
+        if normal == Vector::zeros() {
+            Vector::z()
+        } else {
+            normal
+        }
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Option<Hit<()>> {
+        if t <= K_EPSILON {
+            return None;
+        }
+
+        let local_hit_point = ray.origin() + t * ray.direction();
+        if local_hit_point.z < 0. || local_hit_point.z > self.height {
+            return None;
+        }
+
+        let phi = local_hit_point
+            .y
+            .atan2(local_hit_point.x)
+            .rem_euclid(2. * PI);
+        let uv = Point2::new(phi / (2. * PI), local_hit_point.z / self.height);
+
+        Some(Hit {
+            t,
+            normal: self.normal(&local_hit_point),
+            local_hit_point,
+            shape: (),
+            uv,
+        })
+    }
+}
+
+impl Bounded for Cone {
+    fn bbox(&self) -> Aabb {
+        Aabb::new(
+            Point3::new(-self.radius, -self.radius, 0.),
+            Point3::new(self.radius, self.radius, self.height),
+        )
+    }
+}
+
+impl Intersect for Cone {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let k = (self.radius / self.height).powi(2);
+        let oz = origin.z - self.height;
+
+        let a =
+            direction.x * direction.x + direction.y * direction.y - k * direction.z * direction.z;
+        let b = 2. * (direction.x * origin.x + direction.y * origin.y - k * direction.z * oz);
+        let c = origin.x * origin.x + origin.y * origin.y - k * oz * oz;
+        if a == 0. {
+            // the ray is parallel to the slope of the cone, and either misses it or lies on its
+            // surface if b is 0 as well
+            if b == 0. {
+                return None;
+            }
+            return self.hit_at(ray, -c / b);
+        }
+        let [t0, t1] = quadratic(a, b, c)?;
+
+        self.hit_at(ray, t0).or_else(|| self.hit_at(ray, t1))
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
+
+impl Sampleable for Cone {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        // the circumference grows linearly with the distance to the apex
+        let s = sample.0.sqrt();
+        let phi = 2. * PI * sample.1;
+        let point = Point3::new(
+            self.radius * s * phi.cos(),
+            self.radius * s * phi.sin(),
+            self.height * (1. - s),
+        );
+
+        SurfaceSample {
+            point,
+            normal: Unit::new_normalize(self.normal(&point)),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius.hypot(self.height)
+    }
+}
@@ -1,11 +1,110 @@
-use crate::core::{Aabb, Bounded, Hit, Ray, Shape};
-use crate::shape::Sphere;
-use crate::{math, Float, Point3};
+use nalgebra::Unit;
+use std::f64::consts::PI;
 
+use crate::math::{quadratic, Ray};
+use crate::sampler::{concentric_sample_disk, Sample};
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample};
+use crate::{Point2, Point3, Vector, K_EPSILON};
+
+/// A cylinder around the z-axis, ranging from `z_min` to `z_max`, which is open unless it has
+/// caps.
+#[derive(Debug, Copy, Clone)]
 pub struct Cylinder {
-    radius: Float,
-    z_min: Float,
-    z_max: Float,
+    radius: f64,
+    z_min: f64,
+    z_max: f64,
+    // whether the ends are closed by disks
+    capped: bool,
+}
+
+impl Cylinder {
+    pub fn new(radius: f64, z_min: f64, z_max: f64) -> Self {
+        assert!(radius > 0.);
+        assert!(z_min < z_max);
+
+        Self {
+            radius,
+            z_min,
+            z_max,
+            capped: false,
+        }
+    }
+
+    /// Closes both ends of this cylinder with disks.
+    pub fn with_caps(self) -> Self {
+        Self {
+            capped: true,
+            ..self
+        }
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Option<Hit<()>> {
+        if t <= K_EPSILON {
+            return None;
+        }
+
+        let local_hit_point = ray.origin() + t * ray.direction();
+        if local_hit_point.z < self.z_min || local_hit_point.z > self.z_max {
+            return None;
+        }
+
+        let phi = local_hit_point
+            .y
+            .atan2(local_hit_point.x)
+            .rem_euclid(2. * PI);
+        let uv = Point2::new(
+            phi / (2. * PI),
+            (local_hit_point.z - self.z_min) / (self.z_max - self.z_min),
+        );
+
+        Some(Hit {
+            t,
+            normal: Vector::new(local_hit_point.x, local_hit_point.y, 0.),
+            local_hit_point,
+            shape: (),
+            uv,
+        })
+    }
+
+    /// Returns the hit of `ray` with the cap at `z`, of which the normal points along `sign`.
+    fn cap_hit(&self, ray: &Ray, z: f64, sign: f64) -> Option<Hit<()>> {
+        if ray.direction().z == 0. {
+            return None;
+        }
+
+        let t = (z - ray.origin().z) / ray.direction().z;
+        if t <= K_EPSILON {
+            return None;
+        }
+
+        let local_hit_point = ray.origin() + t * ray.direction();
+        let distance_squared = local_hit_point.x.powi(2) + local_hit_point.y.powi(2);
+        if distance_squared > self.radius * self.radius {
+            return None;
+        }
+
+        let phi = local_hit_point
+            .y
+            .atan2(local_hit_point.x)
+            .rem_euclid(2. * PI);
+        let uv = Point2::new(phi / (2. * PI), 1. - distance_squared.sqrt() / self.radius);
+
+        Some(Hit {
+            t,
+            normal: Vector::new(0., 0., sign),
+            local_hit_point,
+            shape: (),
+            uv,
+        })
+    }
+
+    fn side_area(&self) -> f64 {
+        2. * PI * self.radius * (self.z_max - self.z_min)
+    }
+
+    fn cap_area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
 }
 
 impl Bounded for Cylinder {
@@ -17,22 +116,78 @@ impl Bounded for Cylinder {
     }
 }
 
-impl Shape for Cylinder {
-    fn intersect(&self, ray: &Ray) -> Option<Hit> {
-        let a = ray.direction.x * ray.direction.x + ray.direction.y + ray.direction.y;
-        let b = 2. * (ray.direction.x * ray.origin.x + ray.direction.y * ray.origin.y);
-        let c =
-            ray.origin.x * ray.origin.x + ray.origin.y * ray.origin.y - self.radius * self.radius;
+impl Intersect for Cylinder {
+    type Intersection = ();
 
-        let [t0, t1] = math::quadratic(a, b, c)?;
-        if t1 < 0. {
-            return None;
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        let a = direction.x * direction.x + direction.y * direction.y;
+        let b = 2. * (direction.x * origin.x + direction.y * origin.y);
+        let c = origin.x * origin.x + origin.y * origin.y - self.radius * self.radius;
+        let side = match quadratic(a, b, c) {
+            Some([t0, t1]) if a != 0. => self.hit_at(ray, t0).or_else(|| self.hit_at(ray, t1)),
+            _ => None,
+        };
+
+        if !self.capped {
+            return side;
+        }
+
+        let bottom = self.cap_hit(ray, self.z_min, -1.);
+        let top = self.cap_hit(ray, self.z_max, 1.);
+
+        nearest(nearest(side, bottom), top)
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
+
+impl Sampleable for Cylinder {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        let side_fraction = self.side_area() / self.area();
+        if sample.0 >= side_fraction {
+            // reuses the sample to choose the cap and the point on it
+            let u = (sample.0 - side_fraction) / (1. - side_fraction);
+            let (z, sign, u) = if u < 0.5 {
+                (self.z_min, -1., 2. * u)
+            } else {
+                (self.z_max, 1., 2. * u - 1.)
+            };
+            let (x, y) = concentric_sample_disk((u, sample.1));
+
+            return SurfaceSample {
+                point: Point3::new(self.radius * x, self.radius * y, z),
+                normal: Unit::new_unchecked(Vector::new(0., 0., sign)),
+            };
         }
 
-        todo!()
+        let z = self.z_min + sample.0 / side_fraction * (self.z_max - self.z_min);
+        let phi = 2. * PI * sample.1;
+        let normal = Vector::new(phi.cos(), phi.sin(), 0.);
+
+        SurfaceSample {
+            point: Point3::new(self.radius * normal.x, self.radius * normal.y, z),
+            normal: Unit::new_unchecked(normal),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        if self.capped {
+            self.side_area() + 2. * self.cap_area()
+        } else {
+            self.side_area()
+        }
     }
+}
 
-    fn area(&self) -> Float {
-        todo!()
+fn nearest(a: Option<Hit<()>>, b: Option<Hit<()>>) -> Option<Hit<()>> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.t < a.t => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
     }
 }
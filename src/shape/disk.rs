@@ -0,0 +1,84 @@
+use nalgebra::Unit;
+use std::f64::consts::PI;
+
+use crate::math::Ray;
+use crate::sampler::{concentric_sample_disk, Sample};
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample};
+use crate::{Point2, Point3, Vector, K_EPSILON};
+
+/// A disk in the xy-plane, centered at the origin and facing the positive z-axis.
+#[derive(Debug, Copy, Clone)]
+pub struct Disk {
+    radius: f64,
+}
+
+impl Disk {
+    pub fn new(radius: f64) -> Self {
+        assert!(radius > 0.);
+
+        Self { radius }
+    }
+}
+
+impl Bounded for Disk {
+    fn bbox(&self) -> Aabb {
+        Aabb::new(
+            Point3::new(-self.radius, -self.radius, 0.),
+            Point3::new(self.radius, self.radius, 0.),
+        )
+    }
+}
+
+impl Intersect for Disk {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        if ray.direction().z == 0. {
+            return None;
+        }
+
+        let t = -ray.origin().z / ray.direction().z;
+        if t <= K_EPSILON {
+            return None;
+        }
+
+        let local_hit_point = ray.origin() + t * ray.direction();
+        let distance_squared = local_hit_point.x.powi(2) + local_hit_point.y.powi(2);
+        if distance_squared > self.radius * self.radius {
+            return None;
+        }
+
+        let phi = local_hit_point
+            .y
+            .atan2(local_hit_point.x)
+            .rem_euclid(2. * PI);
+        let uv = Point2::new(phi / (2. * PI), 1. - distance_squared.sqrt() / self.radius);
+
+        Some(Hit {
+            t,
+            normal: Vector::new(0., 0., 1.),
+            local_hit_point,
+            shape: (),
+            uv,
+        })
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
+
+impl Sampleable for Disk {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        let (x, y) = concentric_sample_disk(sample);
+
+        SurfaceSample {
+            point: Point3::new(self.radius * x, self.radius * y, 0.),
+            normal: Unit::new_unchecked(Vector::new(0., 0., 1.)),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
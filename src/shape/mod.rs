@@ -1,25 +1,51 @@
+use nalgebra::Unit;
+
 use crate::material::Material;
 use crate::math::{Ray, Transformation};
+use crate::sampler::Sample;
+use crate::texture::ScalarTexture;
 use crate::{Point2, Point3, Vector};
 use std::ptr::NonNull;
+use std::sync::Arc;
 
 pub use aabb::{Aabb, Union};
+pub use annulus::Annulus;
+pub use compact_mesh::CompactMesh;
 pub use compound::Compound;
+pub use cone::Cone;
 pub use cuboid::Cuboid;
-pub use obj::{FlatTriangle, Obj, SmoothTriangle};
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use mtl::{MtlError, MtlLibrary, MtlMaterial};
+pub use obj::{
+    FlatTriangle, MaterialTriangle, NormalWeighting, Obj, ObjError, SampleableMesh, SmoothTriangle,
+};
 pub use plane::Plane;
+pub use ply::PlyError;
 pub use rectangle::Rectangle;
 pub use sphere::Sphere;
 use std::ops::Deref;
+pub use stl::StlError;
+pub use torus::Torus;
 pub use transformed::Transformed;
 
 mod aabb;
+mod annulus;
+mod compact_mesh;
 mod compound;
+mod cone;
 mod cuboid;
+mod cylinder;
+mod disk;
+mod mtl;
 mod obj;
 mod plane;
+mod ply;
 mod rectangle;
 mod sphere;
+mod stl;
+mod subdivision;
+mod torus;
 mod transformed;
 
 pub trait Bounded {
@@ -73,51 +99,174 @@ pub trait Shape: Intersect<Intersection = ()> + Sync {}
 
 impl<S: Intersect<Intersection = ()> + Sync> Shape for S {}
 
+/// A shape of which the parts can have different materials. Its hits carry the index of the
+/// material of the part that was hit.
+pub trait MultiMaterialShape: Intersect<Intersection = usize> + Sync {}
+
+impl<S: Intersect<Intersection = usize> + Sync> MultiMaterialShape for S {}
+
+/// A shape of which points on the surface can be sampled, such that it can be used as an area
+/// light.
+pub trait Sampleable: Shape {
+    /// Samples a point on the surface of this shape, as seen from `reference`.
+    fn sample(&self, reference: &Point3, sample: Sample) -> SurfaceSample;
+
+    /// Samples a point uniformly over the whole surface of this shape. Defaults to `sample` as
+    /// seen from the origin, which is only correct for shapes that sample their surface
+    /// independently of the reference point.
+    fn sample_uniform(&self, sample: Sample) -> SurfaceSample {
+        self.sample(&Point3::origin(), sample)
+    }
+
+    /// Returns the probability density, with respect to surface area, of sampling `point` as seen
+    /// from `reference`.
+    fn pdf(&self, _reference: &Point3, _point: &SurfaceSample) -> f64 {
+        1. / self.area()
+    }
+
+    fn area(&self) -> f64;
+
+    /// Returns whether `area` is exact, rather than an approximation.
+    fn has_exact_area(&self) -> bool {
+        true
+    }
+}
+
+impl<T, S> Sampleable for T
+where
+    S: Sampleable + ?Sized,
+    T: Deref<Target = S> + Sync,
+{
+    fn sample(&self, reference: &Point3, sample: Sample) -> SurfaceSample {
+        (**self).sample(reference, sample)
+    }
+
+    fn sample_uniform(&self, sample: Sample) -> SurfaceSample {
+        (**self).sample_uniform(sample)
+    }
+
+    fn pdf(&self, reference: &Point3, point: &SurfaceSample) -> f64 {
+        (**self).pdf(reference, point)
+    }
+
+    fn area(&self) -> f64 {
+        (**self).area()
+    }
+
+    fn has_exact_area(&self) -> bool {
+        (**self).has_exact_area()
+    }
+}
+
+/// A point on the surface of a shape, together with the surface normal in that point.
+#[derive(Debug, Copy, Clone)]
+pub struct SurfaceSample {
+    pub point: Point3,
+    pub normal: Unit<Vector>,
+}
+
 pub struct GeometricObject {
-    shape: Box<dyn Shape>,
-    material: Material,
+    surface: Surface,
+    // materials of the parts of the surface, by index
+    materials: Vec<Material>,
+    // index in the lights of the world of the light of which this is the emitting surface, if any
+    emitter: Option<usize>,
+}
+
+enum Surface {
+    // a shape of a single material
+    Shape(Box<dyn Shape>),
+    MultiMaterial(Box<dyn MultiMaterialShape>),
 }
 
 impl Bounded for GeometricObject {
     fn bbox(&self) -> Aabb {
-        self.shape.bbox()
+        match &self.surface {
+            Surface::Shape(shape) => shape.bbox(),
+            Surface::MultiMaterial(shape) => shape.bbox(),
+        }
     }
 }
 
 impl Intersect for GeometricObject {
-    // We can't use `&'a Self`, because GAT aren't implemented yet.
-    type Intersection = NonNull<Self>;
+    // We can't use `&'a Self`, because GAT aren't implemented yet. The index is that of the
+    // material of the part of the surface that was hit.
+    type Intersection = (NonNull<Self>, usize);
 
     fn intersect(&self, ray: &Ray) -> Option<Hit<Self::Intersection>> {
-        self.shape.intersect(ray).map(|hit| Hit {
-            t: hit.t,
-            normal: hit.normal,
-            local_hit_point: hit.local_hit_point,
-            shape: self.into(),
-            uv: hit.uv,
-        })
+        match &self.surface {
+            Surface::Shape(shape) => shape.intersect(ray).map(|hit| self.object_hit(hit, 0)),
+            Surface::MultiMaterial(shape) => shape.intersect(ray).map(|hit| {
+                let material = hit.shape;
+                self.object_hit(hit, material)
+            }),
+        }
     }
 
     fn count_intersection_tests(&self, ray: &Ray) -> usize {
-        self.shape.count_intersection_tests(ray)
+        match &self.surface {
+            Surface::Shape(shape) => shape.count_intersection_tests(ray),
+            Surface::MultiMaterial(shape) => shape.count_intersection_tests(ray),
+        }
     }
 
     fn hit(&self, ray: &Ray) -> bool {
-        self.shape.hit(ray)
+        match &self.surface {
+            Surface::Shape(shape) => shape.hit(ray),
+            Surface::MultiMaterial(shape) => shape.hit(ray),
+        }
     }
 }
 
 impl GeometricObject {
     pub fn new(shape: Box<dyn Shape>, material: Material) -> Self {
-        Self { shape, material }
+        Self {
+            surface: Surface::Shape(shape),
+            materials: vec![material],
+            emitter: None,
+        }
+    }
+
+    /// Creates an object of which the parts of `shape` have different materials, indexing
+    /// `materials`.
+    pub fn multi_material(shape: Box<dyn MultiMaterialShape>, materials: Vec<Material>) -> Self {
+        assert!(!materials.is_empty());
+
+        Self {
+            surface: Surface::MultiMaterial(shape),
+            materials,
+            emitter: None,
+        }
+    }
+
+    fn object_hit<S>(&self, hit: Hit<S>, material: usize) -> Hit<(NonNull<Self>, usize)> {
+        Hit {
+            t: hit.t,
+            normal: hit.normal,
+            local_hit_point: hit.local_hit_point,
+            shape: (self.into(), material),
+            uv: hit.uv,
+        }
     }
 
-    pub fn shape(&self) -> &dyn Shape {
-        self.shape.as_ref()
+    /// Marks this object as the emitting surface of the light at index `light` in the world.
+    pub(crate) fn with_emitter(self, light: usize) -> Self {
+        Self {
+            emitter: Some(light),
+            ..self
+        }
     }
 
-    pub fn material(&self) -> &Material {
-        &self.material
+    /// Returns the materials of the parts of this object, of which there is a single one unless
+    /// the object was created with `multi_material`.
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    /// Returns the index in the lights of the world of the light of which this object is the
+    /// emitting surface, if any.
+    pub fn emitter(&self) -> Option<usize> {
+        self.emitter
     }
 
     pub fn sphere(transformation: Transformation, material: Material) -> Self {
@@ -140,10 +289,148 @@ impl GeometricObject {
         Self::new(shape, material)
     }
 
+    /// Creates an open cylinder around the z-axis, ranging from `z_min` to `z_max`.
+    pub fn cylinder(
+        radius: f64,
+        z_min: f64,
+        z_max: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::cylinder(radius, z_min, z_max, transformation));
+        Self::new(shape, material)
+    }
+
+    /// Creates a cylinder around the z-axis, ranging from `z_min` to `z_max`, of which both ends
+    /// are closed.
+    pub fn capped_cylinder(
+        radius: f64,
+        z_min: f64,
+        z_max: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let cylinder = Cylinder::new(radius, z_min, z_max).with_caps();
+        let shape = Box::new(Transformed::new(cylinder, transformation));
+        Self::new(shape, material)
+    }
+
+    pub fn disk(radius: f64, transformation: Transformation, material: Material) -> Self {
+        let shape = Box::new(Transformed::disk(radius, transformation));
+        Self::new(shape, material)
+    }
+
+    pub fn annulus(
+        inner_radius: f64,
+        outer_radius: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::annulus(
+            inner_radius,
+            outer_radius,
+            transformation,
+        ));
+        Self::new(shape, material)
+    }
+
+    pub fn cone(
+        radius: f64,
+        height: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::cone(radius, height, transformation));
+        Self::new(shape, material)
+    }
+
+    pub fn torus(
+        major_radius: f64,
+        minor_radius: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::torus(
+            major_radius,
+            minor_radius,
+            transformation,
+        ));
+        Self::new(shape, material)
+    }
+
     pub fn triangle_mesh(obj: Obj, transformation: Transformation, material: Material) -> Self {
         let shape = Box::new(Transformed::smooth_mesh(obj, transformation));
         Self::new(shape, material)
     }
+
+    /// Creates a smooth triangle mesh of `obj` subdivided `levels` times, see `Obj::subdivide`.
+    pub fn subdivision_surface(
+        obj: Obj,
+        levels: usize,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        Self::triangle_mesh(obj.subdivide(levels), transformation, material)
+    }
+
+    /// Creates a smooth triangle mesh of `obj` tessellated `levels` times and displaced along its
+    /// normals by `scale` times the height given by `texture`, see `Obj::displace`.
+    pub fn displaced_mesh(
+        obj: Obj,
+        levels: usize,
+        texture: &dyn ScalarTexture,
+        scale: f64,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let obj = obj.tessellate(levels).displace(texture, scale);
+        Self::triangle_mesh(obj, transformation, material)
+    }
+
+    /// Creates an instance of `shape` with its own transformation and material. Instances of a
+    /// triangle mesh built once, as in `Arc::new(obj.smooth())`, share its triangles and bounding
+    /// volume hierarchy, which is nested in that of the world.
+    pub fn instance<S: Shape + Send + 'static>(
+        shape: &Arc<S>,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::instance(shape, transformation));
+        Self::new(shape, material)
+    }
+
+    /// Creates a triangle mesh like `triangle_mesh`, of which the vertex data is stored compactly.
+    pub fn compact_triangle_mesh(
+        obj: Obj,
+        transformation: Transformation,
+        material: Material,
+    ) -> Self {
+        let shape = Box::new(Transformed::compact_mesh(obj, transformation));
+        Self::new(shape, material)
+    }
+
+    /// Creates a smooth triangle mesh of `obj` of which every triangle has the material given to
+    /// it in the MTL libraries of `obj`. Triangles without a material get `default_material`.
+    pub fn triangle_mesh_with_materials(
+        obj: Obj,
+        transformation: Transformation,
+        default_material: Material,
+    ) -> Result<Self, MtlError> {
+        let library = obj.load_materials()?;
+        let (triangles, names) = obj.smooth_with_materials();
+
+        let mut materials = vec![default_material];
+        for name in names {
+            let material = library
+                .get(&name)
+                .ok_or(MtlError::UnknownMaterial(name.clone()))?
+                .to_material();
+            materials.push(material);
+        }
+        let shape = Box::new(Transformed::new(triangles, transformation));
+
+        Ok(Self::multi_material(shape, materials))
+    }
 }
 
 pub struct Hit<S> {
@@ -0,0 +1,255 @@
+use image::ImageError;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::brdf::{Dielectric, Lambertian, PerfectSpecular, SvLambertian};
+use crate::film::Rgb;
+use crate::material::{Emissive, Material};
+use crate::texture::ImageTexture;
+
+/// The materials of a Wavefront MTL library, by name.
+#[derive(Default)]
+pub struct MtlLibrary {
+    materials: HashMap<String, MtlMaterial>,
+}
+
+impl MtlLibrary {
+    /// Loads the library at `path`, together with the textures it references, of which the
+    /// paths are relative to the library.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MtlError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        Self::parse(&input, directory)
+    }
+
+    /// Parses a library of which the textures are relative to `directory`.
+    pub fn parse(input: &str, directory: &Path) -> Result<Self, MtlError> {
+        let mut materials = HashMap::new();
+        let mut current: Option<(String, MtlMaterial)> = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap().trim();
+            let (keyword, rest) = match line.find(char::is_whitespace) {
+                Some(end) => (&line[..end], line[end..].trim()),
+                None => (line, ""),
+            };
+
+            if keyword == "newmtl" {
+                if let Some((name, material)) = current.take() {
+                    materials.insert(name, material);
+                }
+                current = Some((rest.to_owned(), MtlMaterial::default()));
+                continue;
+            }
+
+            let material = match (&mut current, keyword) {
+                (_, "") => continue,
+                (Some((_, material)), _) => material,
+                (None, _) => return Err(MtlError::MissingNewmtl(line_number)),
+            };
+            let number = |token: &str| {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| MtlError::InvalidNumber(line_number, token.to_owned()))
+            };
+            let color = || -> Result<Rgb, MtlError> {
+                let mut tokens = rest.split_ascii_whitespace();
+                let red = number(tokens.next().ok_or(MtlError::MissingValue(line_number))?)?;
+                // a single value stands for a gray
+                let green = tokens.next().map_or(Ok(red), number)?;
+                let blue = tokens.next().map_or(Ok(green), number)?;
+                Ok(Rgb::new(red, green, blue))
+            };
+            // texture options precede the file name, which must not contain spaces
+            let texture = || -> Result<PathBuf, MtlError> {
+                let file = rest
+                    .split_ascii_whitespace()
+                    .last()
+                    .ok_or(MtlError::MissingValue(line_number))?;
+                Ok(directory.join(file))
+            };
+
+            match keyword {
+                "Ka" => material.ambient = color()?,
+                "Kd" => material.diffuse = color()?,
+                "Ks" => material.specular = color()?,
+                "Ke" => material.emission = color()?,
+                "Tf" => material.transmission = color()?,
+                "Ns" => material.shininess = number(rest)?,
+                "Ni" => material.ior = number(rest)?,
+                "d" => material.dissolve = number(rest)?,
+                "Tr" => material.dissolve = 1. - number(rest)?,
+                "illum" => material.illumination = number(rest)? as u8,
+                "map_Kd" => {
+                    let path = texture()?;
+                    material.diffuse_texture = Some(load_texture(&path)?);
+                    material.diffuse_map = Some(path);
+                }
+                "map_Bump" | "map_bump" | "bump" => material.bump_map = Some(texture()?),
+                // other maps and the extensions for physically based rendering are unsupported
+                _ => {}
+            }
+        }
+
+        if let Some((name, material)) = current {
+            materials.insert(name, material);
+        }
+
+        Ok(Self { materials })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MtlMaterial> {
+        self.materials.get(name)
+    }
+
+    /// Adds the materials of `other`, replacing those with the same names.
+    pub fn extend(&mut self, other: MtlLibrary) {
+        self.materials.extend(other.materials);
+    }
+}
+
+/// A material as described in an MTL library.
+#[derive(Clone)]
+pub struct MtlMaterial {
+    pub ambient: Rgb,
+    pub diffuse: Rgb,
+    pub specular: Rgb,
+    pub emission: Rgb,
+    // color of the light transmitted through transparent materials
+    pub transmission: Rgb,
+    // exponent of the specular highlight, in [0, 1000]
+    pub shininess: f64,
+    pub ior: f64,
+    // opacity, in [0, 1]
+    pub dissolve: f64,
+    // illumination model, from 0 to 10
+    pub illumination: u8,
+    pub diffuse_map: Option<PathBuf>,
+    // there is no bump mapping yet, so the map isn't used by `to_material`
+    pub bump_map: Option<PathBuf>,
+    diffuse_texture: Option<ImageTexture>,
+}
+
+impl MtlMaterial {
+    /// Maps this material onto the closest material of this crate. Emitting materials become
+    /// emissive, transparent ones glass, perfectly reflecting ones mirrors and all others matte,
+    /// textured when there is a diffuse map. Glossy highlights aren't supported.
+    pub fn to_material(&self) -> Material {
+        let unit = |color: Rgb| color.clamp(0., 1.);
+
+        let ls = self
+            .emission
+            .red()
+            .max(self.emission.green())
+            .max(self.emission.blue());
+        if ls > 0. {
+            return Material::Emissive(Emissive::new(ls, self.emission / ls));
+        }
+
+        if self.dissolve < 1. || matches!(self.illumination, 4 | 6 | 7) {
+            let ior = if self.ior > 0. { self.ior } else { 1. };
+            return Material::Glass(Dielectric::new(ior, Rgb::white(), unit(self.transmission)));
+        }
+
+        let is_specular = self.specular != Rgb::black();
+        if is_specular && (matches!(self.illumination, 3 | 5) || self.shininess >= 1000.) {
+            return Material::Mirror(PerfectSpecular::new(1., unit(self.specular)));
+        }
+
+        match &self.diffuse_texture {
+            Some(texture) => {
+                // the diffuse color scales the texture, and can only be applied as a whole
+                let kd = unit(self.diffuse).average();
+                let ka = unit(self.ambient).average();
+                Material::SvMatte {
+                    ambient_brdf: SvLambertian::new(ka, Box::new(texture.clone())),
+                    diffuse_brdf: SvLambertian::new(kd, Box::new(texture.clone())),
+                }
+            }
+            None => Material::Matte {
+                ambient_brdf: Lambertian::new(1., unit(self.ambient)),
+                diffuse_brdf: Lambertian::new(1., unit(self.diffuse)),
+            },
+        }
+    }
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        Self {
+            ambient: Rgb::black(),
+            diffuse: Rgb::new(0.8, 0.8, 0.8),
+            specular: Rgb::black(),
+            emission: Rgb::black(),
+            transmission: Rgb::white(),
+            shininess: 0.,
+            ior: 1.,
+            dissolve: 1.,
+            illumination: 2,
+            diffuse_map: None,
+            bump_map: None,
+            diffuse_texture: None,
+        }
+    }
+}
+
+fn load_texture(path: &Path) -> Result<ImageTexture, MtlError> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| MtlError::InvalidPath(path.to_owned()))?;
+
+    ImageTexture::new(path_str).map_err(MtlError::Texture)
+}
+
+#[derive(Debug)]
+pub enum MtlError {
+    Io(io::Error),
+    Texture(ImageError),
+    // line numbers count from 1
+    MissingNewmtl(usize),
+    MissingValue(usize),
+    InvalidNumber(usize, String),
+    InvalidPath(PathBuf),
+    UnknownMaterial(String),
+}
+
+impl Display for MtlError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MtlError::Io(err) => write!(f, "failed to read MTL file: {}", err),
+            MtlError::Texture(err) => write!(f, "failed to load texture: {}", err),
+            MtlError::MissingNewmtl(line) => {
+                write!(f, "line {}: statement before the first newmtl", line)
+            }
+            MtlError::MissingValue(line) => write!(f, "line {}: missing value", line),
+            MtlError::InvalidNumber(line, token) => {
+                write!(f, "line {}: invalid number: {}", line, token)
+            }
+            MtlError::InvalidPath(path) => write!(f, "invalid path: {}", path.display()),
+            MtlError::UnknownMaterial(name) => write!(f, "unknown material: {}", name),
+        }
+    }
+}
+
+impl Error for MtlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MtlError::Io(err) => Some(err),
+            MtlError::Texture(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MtlError {
+    fn from(err: io::Error) -> Self {
+        MtlError::Io(err)
+    }
+}
@@ -1,13 +1,26 @@
+use itertools::Itertools;
 use nalgebra::Unit;
-use std::fs::File;
-use std::io::Read;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::iter;
 use std::mem;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::math::{Ray, Transformation};
+use crate::film::Rgb;
+use crate::math::{Distribution1D, Ray, Transformation};
+use crate::sampler::Sample;
 use crate::shape::aabb::Aabb;
+use crate::shape::compact_mesh::{Buffers, CompactMesh};
 use crate::shape::compound::Compound;
-use crate::shape::{Bounded, Hit, Intersect};
+use crate::shape::mtl::{MtlError, MtlLibrary};
+use crate::shape::subdivision::{edge, Edge, Face, PolygonMesh};
+use crate::shape::{Bounded, Hit, Intersect, Sampleable, SurfaceSample};
+use crate::texture::{ImageTexture, ScalarTexture};
 use crate::{Point2, Point3, Vector, K_EPSILON};
 
 #[derive(Default)]
@@ -15,6 +28,62 @@ pub struct Mesh {
     vertexes: Vec<Point3>,
     normals: Vec<Unit<Vector>>,
     texture_coordinates: Vec<Point2>,
+    normal_map: Option<NormalMap>,
+}
+
+/// A texture of normals in the tangent space of a surface, of which the x axis follows the u
+/// texture coordinate and the y axis the v texture coordinate.
+#[derive(Clone)]
+pub(super) struct NormalMap {
+    texture: ImageTexture,
+    // scales the x and y components of the normals
+    scale: f64,
+}
+
+impl NormalMap {
+    /// Returns the normal of this map at `uv`, transformed from the tangent space around the
+    /// interpolated `normal` to object space, on the triangle of `corners` with the given
+    /// `texture_coordinates`.
+    pub(super) fn apply(
+        &self,
+        corners: [Point3; 3],
+        texture_coordinates: [Point2; 3],
+        normal: Vector,
+        uv: Point2,
+    ) -> Vector {
+        let [p0, p1, p2] = corners;
+        let [vt0, vt1, vt2] = texture_coordinates;
+        let duv1 = vt1 - vt0;
+        let duv2 = vt2 - vt0;
+        let dp1 = p1 - p0;
+        let dp2 = p2 - p0;
+
+        let determinant = duv1.x * duv2.y - duv1.y * duv2.x;
+        if determinant.abs() < K_EPSILON {
+            return normal;
+        }
+        let dpdu = (duv2.y * dp1 - duv1.y * dp2) / determinant;
+        let dpdv = (duv1.x * dp2 - duv2.x * dp1) / determinant;
+
+        // Gram-Schmidt orthogonalization of the tangent against the normal
+        let normal = normal.normalize();
+        let tangent = dpdu - normal.dot(&dpdu) * normal;
+        if tangent.norm_squared() == 0. {
+            return normal;
+        }
+        let tangent = tangent.normalize();
+        let mut bitangent = normal.cross(&tangent);
+        if bitangent.dot(&dpdv) < 0. {
+            bitangent = -bitangent;
+        }
+
+        let color = self.texture.color_at(uv);
+        let x = (2. * color.red() - 1.) * self.scale;
+        let y = (2. * color.green() - 1.) * self.scale;
+        let z = 2. * color.blue() - 1.;
+
+        x * tangent + y * bitangent + z * normal
+    }
 }
 
 #[repr(transparent)]
@@ -36,9 +105,12 @@ impl Intersect for SmoothTriangle {
         self.inner.intersect(ray).map(|hit| {
             let beta = hit.beta;
             let gamma = hit.gamma;
-            let normal = beta * *self.inner.n1()
+            let mut normal = beta * *self.inner.n1()
                 + gamma * *self.inner.n2()
                 + (1. - beta - gamma) * *self.inner.n0();
+            if let Some(normal_map) = &self.inner.mesh.normal_map {
+                normal = self.inner.map_normal(normal_map, normal, hit.uv);
+            }
 
             Hit {
                 t: hit.t,
@@ -55,6 +127,38 @@ impl Intersect for SmoothTriangle {
     }
 }
 
+/// A smooth triangle of a mesh of which the triangles have different materials, see
+/// `Obj::smooth_with_materials`.
+pub struct MaterialTriangle {
+    triangle: SmoothTriangle,
+    // index of the material of the triangle
+    material: usize,
+}
+
+impl Bounded for MaterialTriangle {
+    fn bbox(&self) -> Aabb {
+        self.triangle.bbox()
+    }
+}
+
+impl Intersect for MaterialTriangle {
+    type Intersection = usize;
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<usize>> {
+        self.triangle.intersect(ray).map(|hit| Hit {
+            t: hit.t,
+            normal: hit.normal,
+            local_hit_point: hit.local_hit_point,
+            shape: self.material,
+            uv: hit.uv,
+        })
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
+
 #[repr(transparent)]
 pub struct FlatTriangle {
     inner: Triangle,
@@ -84,6 +188,71 @@ impl Intersect for FlatTriangle {
     }
 }
 
+/// A triangle mesh of which points can be sampled uniformly with respect to its surface area,
+/// such that it can be used as an emissive area light.
+pub struct SampleableMesh {
+    triangles: Compound<'static, SmoothTriangle>,
+    // distribution of the triangles according to their area
+    distribution: Distribution1D,
+    area: f64,
+}
+
+impl SampleableMesh {
+    pub fn new(triangles: Vec<SmoothTriangle>) -> Self {
+        let areas = triangles
+            .iter()
+            .map(|triangle| triangle.inner.area())
+            .collect_vec();
+        let area = areas.iter().sum();
+        let distribution = Distribution1D::new(areas);
+        let triangles = Compound::new(triangles);
+
+        Self {
+            triangles,
+            distribution,
+            area,
+        }
+    }
+}
+
+impl Bounded for SampleableMesh {
+    fn bbox(&self) -> Aabb {
+        self.triangles.bbox()
+    }
+}
+
+impl Intersect for SampleableMesh {
+    type Intersection = ();
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        self.triangles.intersect(ray)
+    }
+
+    fn count_intersection_tests(&self, ray: &Ray) -> usize {
+        self.triangles.count_intersection_tests(ray)
+    }
+
+    fn hit(&self, ray: &Ray) -> bool {
+        self.triangles.hit(ray)
+    }
+}
+
+impl Sampleable for SampleableMesh {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        let (i, _, u) = self.distribution.sample_discrete(sample.0);
+        let triangle = &self.triangles.shapes()[i].inner;
+
+        SurfaceSample {
+            point: triangle.sample((u, sample.1)),
+            normal: triangle.geometric_normal(),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        self.area
+    }
+}
+
 #[derive(Clone)]
 struct Triangle {
     mesh: Arc<Mesh>,
@@ -115,54 +284,7 @@ impl Bounded for Triangle {
 
 impl Triangle {
     fn intersect(&self, ray: &Ray) -> Option<TriangleHit> {
-        let v0 = self.v0();
-        let v1 = self.v1();
-        let v2 = self.v2();
-
-        let a = v0.x - v1.x;
-        let b = v0.x - v2.x;
-        let c = ray.direction().x;
-        let d = v0.x - ray.origin().x;
-
-        let e = v0.y - v1.y;
-        let f = v0.y - v2.y;
-        let g = ray.direction().y;
-        let h = v0.y - ray.origin().y;
-
-        let i = v0.z - v1.z;
-        let j = v0.z - v2.z;
-        let k = ray.direction().z;
-        let l = v0.z - ray.origin().z;
-
-        let m = f * k - g * j;
-        let n = h * k - g * l;
-        let p = f * l - h * j;
-        let q = g * i - e * k;
-        let s = e * j - f * i;
-
-        let inv_denom = 1. / (a * m + b * q + c * s);
-
-        let e1 = d * m - b * n - c * p;
-        let beta = e1 * inv_denom;
-
-        if beta < 0. {
-            return None;
-        }
-
-        let r = e * l - h * i;
-        let e2 = a * n + d * q + c * r;
-        let gamma = e2 * inv_denom;
-
-        if gamma < 0. || beta + gamma > 1. {
-            return None;
-        }
-
-        let e3 = a * p - b * r + d * s;
-        let t = e3 * inv_denom;
-
-        if t < K_EPSILON {
-            return None;
-        }
+        let (t, beta, gamma) = intersect_triangle(self.v0(), self.v1(), self.v2(), ray)?;
 
         let local_hit_point = ray.origin() + t * ray.direction();
         let vt0 = self.mesh.texture_coordinates[self.vt.0];
@@ -181,6 +303,49 @@ impl Triangle {
         })
     }
 
+    fn area(&self) -> f64 {
+        0.5 * (self.v1() - self.v0())
+            .cross(&(self.v2() - self.v0()))
+            .norm()
+    }
+
+    /// Returns the normal of the plane of this triangle, oriented along the vertex normals.
+    fn geometric_normal(&self) -> Unit<Vector> {
+        let normal = Unit::new_normalize((self.v1() - self.v0()).cross(&(self.v2() - self.v0())));
+
+        if normal.dot(&self.normal) < 0. {
+            -normal
+        } else {
+            normal
+        }
+    }
+
+    /// Samples a point on this triangle, uniformly with respect to its area.
+    fn sample(&self, sample: Sample) -> Point3 {
+        let su0 = sample.0.sqrt();
+        let b0 = 1. - su0;
+        let b1 = sample.1 * su0;
+
+        Point3::from(
+            b0 * self.v0().coords + b1 * self.v1().coords + (1. - b0 - b1) * self.v2().coords,
+        )
+    }
+
+    fn map_normal(&self, normal_map: &NormalMap, normal: Vector, uv: Point2) -> Vector {
+        let texture_coordinates = [
+            self.mesh.texture_coordinates[self.vt.0],
+            self.mesh.texture_coordinates[self.vt.1],
+            self.mesh.texture_coordinates[self.vt.2],
+        ];
+
+        normal_map.apply(
+            [self.v0(), self.v1(), self.v2()],
+            texture_coordinates,
+            normal,
+            uv,
+        )
+    }
+
     fn n0(&self) -> Unit<Vector> {
         self.mesh.normals[self.n.0]
     }
@@ -206,6 +371,88 @@ impl Triangle {
     }
 }
 
+/// Returns the geometric normal of the triangle of corners `v0`, `v1` and `v2`, oriented along
+/// `shading_normal`, or `shading_normal` itself if the triangle is degenerate.
+pub(super) fn face_normal(v0: Point3, v1: Point3, v2: Point3, shading_normal: Vector) -> Vector {
+    let normal = (v1 - v0).cross(&(v2 - v0));
+
+    match normal.try_normalize(0.) {
+        Some(normal) if normal.dot(&shading_normal) < 0. => -normal,
+        Some(normal) => normal,
+        None => shading_normal,
+    }
+}
+
+/// Returns the index of the midpoint between the values at `a` and `b`, which is added by `add`
+/// unless it's in `midpoints` already.
+fn midpoint_index<F>(
+    midpoints: &mut HashMap<(usize, usize), usize>,
+    (a, b): (usize, usize),
+    add: F,
+) -> usize
+where
+    F: FnOnce((usize, usize)) -> usize,
+{
+    let key = (a.min(b), a.max(b));
+    *midpoints.entry(key).or_insert_with(|| add(key))
+}
+
+/// Intersects `ray` with the triangle of corners `v0`, `v1` and `v2`, returning the ray parameter
+/// and the barycentric coordinates of the hit point with respect to `v1` and `v2`.
+///
+/// This is the watertight algorithm of Woop et al.: the edge functions are evaluated in a frame
+/// where the ray lies along the z-axis, so that the rays hitting an edge or a vertex shared by
+/// several triangles hit at least one of them.
+pub(super) fn intersect_triangle(
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    ray: &Ray,
+) -> Option<(f64, f64, f64)> {
+    let direction = ray.direction();
+
+    // permute the axes such that the largest component of the direction is along z
+    let kz = direction.iamax();
+    let kx = (kz + 1) % 3;
+    let ky = (kx + 1) % 3;
+    let permute = |v: Vector| Vector::new(v[kx], v[ky], v[kz]);
+    let d = permute(*direction);
+
+    // shear the vertexes, relative to the origin of the ray, such that the ray is along z
+    let shear_x = -d.x / d.z;
+    let shear_y = -d.y / d.z;
+    let shear_z = 1. / d.z;
+    let shear = |v: Point3| {
+        let p = permute(v - ray.origin());
+        Vector::new(p.x + shear_x * p.z, p.y + shear_y * p.z, p.z * shear_z)
+    };
+    let p0 = shear(v0);
+    let p1 = shear(v1);
+    let p2 = shear(v2);
+
+    // the edge functions, which are twice the signed areas opposite to each vertex
+    let e0 = p1.x * p2.y - p1.y * p2.x;
+    let e1 = p2.x * p0.y - p2.y * p0.x;
+    let e2 = p0.x * p1.y - p0.y * p1.x;
+
+    if (e0 < 0. || e1 < 0. || e2 < 0.) && (e0 > 0. || e1 > 0. || e2 > 0.) {
+        return None;
+    }
+
+    // the ray is in the plane of the triangle, or the triangle is degenerate
+    let determinant = e0 + e1 + e2;
+    if determinant == 0. {
+        return None;
+    }
+
+    let t = (e0 * p0.z + e1 * p1.z + e2 * p2.z) / determinant;
+    if t < K_EPSILON {
+        return None;
+    }
+
+    Some((t, e1 / determinant, e2 / determinant))
+}
+
 struct TriangleHit {
     t: f64,
     local_hit_point: Point3,
@@ -220,54 +467,132 @@ pub struct Obj {
     texture_coordinates: Vec<Point2>,
     vertex_normals: Vec<Vector>,
     triangles: Vec<ObjTriangle>,
+    // names of the materials used by the triangles
+    material_names: Vec<String>,
+    material_libraries: Vec<PathBuf>,
+    normal_map: Option<NormalMap>,
+    // colors of the vertexes, if any
+    vertex_colors: Vec<Rgb>,
+    // number of triangles of each polygon of the file, which are consecutive fans
+    polygon_sizes: Vec<usize>,
 }
 
 impl Obj {
-    pub fn load(path: &str) -> Option<Obj> {
-        let input = read_file(path)?;
+    /// Creates a mesh of `triangles`, given by the indices of their corners in `vertexes`, of
+    /// which the corners share their index in `vertex_normals` and `texture_coordinates`.
+    /// Missing normals and texture coordinates are generated as for OBJ files, if either is
+    /// empty.
+    pub fn new(
+        vertexes: Vec<Point3>,
+        vertex_normals: Vec<Vector>,
+        texture_coordinates: Vec<Point2>,
+        triangles: &[[usize; 3]],
+    ) -> Self {
+        assert!(vertex_normals.is_empty() || vertex_normals.len() == vertexes.len());
+        assert!(texture_coordinates.is_empty() || texture_coordinates.len() == vertexes.len());
+
+        let has_normals = !vertex_normals.is_empty();
+        let has_texture_coordinates = !texture_coordinates.is_empty();
+        let corner = |vertex: usize| {
+            assert!(vertex < vertexes.len());
+
+            ObjCorner {
+                vertex,
+                texture: Some(vertex).filter(|_| has_texture_coordinates),
+                normal: Some(vertex).filter(|_| has_normals),
+            }
+        };
+        let faces = triangles
+            .iter()
+            .map(|&[a, b, c]| ([corner(a), corner(b), corner(c)], None))
+            .collect();
 
         let mut obj = Self {
-            vertexes: Vec::new(),
-            texture_coordinates: Vec::new(),
-            vertex_normals: Vec::new(),
+            vertexes,
+            texture_coordinates,
+            vertex_normals,
             triangles: Vec::new(),
+            material_names: Vec::new(),
+            material_libraries: Vec::new(),
+            normal_map: None,
+            vertex_colors: Vec::new(),
+            polygon_sizes: vec![1; triangles.len()],
         };
+        obj.complete(faces);
 
-        for line in input.lines() {
-            let mut parts = line.split_ascii_whitespace();
-            match parts.next()? {
-                "v" => {
-                    let x = parts.next()?.parse().ok()?;
-                    let y = parts.next()?.parse().ok()?;
-                    let z = parts.next()?.parse().ok()?;
+        obj
+    }
 
-                    obj.vertexes.push(Point3::new(x, y, z));
-                }
-                "vt" => {
-                    let u = parts.next()?.parse().ok()?;
-                    let v = parts.next()?.parse().ok()?;
+    /// Perturbs the normals of the smooth triangles of this mesh with the tangent space normals
+    /// of `texture`, of which the x and y components are multiplied by `scale`.
+    pub fn with_normal_map(self, texture: ImageTexture, scale: f64) -> Self {
+        Self {
+            normal_map: Some(NormalMap { texture, scale }),
+            ..self
+        }
+    }
 
-                    obj.texture_coordinates.push(Point2::new(u, v));
-                }
-                "vn" => {
-                    let x = parts.next()?.parse().ok()?;
-                    let y = parts.next()?.parse().ok()?;
-                    let z = parts.next()?.parse().ok()?;
+    /// Sets the colors of the vertexes, which aren't used by the triangles of this mesh.
+    pub fn with_vertex_colors(self, vertex_colors: Vec<Rgb>) -> Self {
+        assert_eq!(vertex_colors.len(), self.vertexes.len());
 
-                    obj.vertex_normals.push(Vector::new(x, y, z));
-                }
-                "f" => {
-                    let a = ObjTriangleCorner::parse(parts.next()?)?;
-                    let b = ObjTriangleCorner::parse(parts.next()?)?;
-                    let c = ObjTriangleCorner::parse(parts.next()?)?;
+        Self {
+            vertex_colors,
+            ..self
+        }
+    }
 
-                    obj.triangles.push(ObjTriangle(a, b, c))
-                }
-                _ => return None,
-            }
+    /// Returns the colors of the vertexes, or an empty slice if they have none.
+    pub fn vertex_colors(&self) -> &[Rgb] {
+        &self.vertex_colors
+    }
+
+    pub fn load(path: &str) -> Result<Obj, ObjError> {
+        let input = fs::read_to_string(path)?;
+        let mut obj: Obj = input.parse()?;
+
+        // material libraries are relative to the OBJ file
+        let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+        for library in &mut obj.material_libraries {
+            *library = directory.join(&library);
+        }
+
+        Ok(obj)
+    }
+
+    /// Returns the paths of the MTL libraries referenced by this Obj.
+    pub fn material_libraries(&self) -> &[PathBuf] {
+        &self.material_libraries
+    }
+
+    /// Loads the materials of all libraries referenced by this Obj.
+    pub fn load_materials(&self) -> Result<MtlLibrary, MtlError> {
+        let mut materials = MtlLibrary::default();
+        for library in &self.material_libraries {
+            materials.extend(MtlLibrary::load(library)?);
         }
 
-        Some(obj)
+        Ok(materials)
+    }
+
+    /// Creates a smooth triangle mesh of which the hits carry the index of the material of the
+    /// triangle that was hit: 0 for triangles without a material, and `i + 1` for those of which
+    /// the material has the `i`th of the returned names. All triangles share the vertex data.
+    pub fn smooth_with_materials(mut self) -> (Compound<'static, MaterialTriangle>, Vec<String>) {
+        let names = mem::take(&mut self.material_names);
+        let materials = self.triangles.iter().map(|t| t.3).collect_vec();
+
+        let triangles = self
+            .smooth_triangles()
+            .into_iter()
+            .zip(materials)
+            .map(|(triangle, material)| MaterialTriangle {
+                triangle,
+                material: material.map_or(0, |i| i + 1),
+            })
+            .collect();
+
+        (Compound::new(triangles), names)
     }
 
     pub fn smooth(self) -> Compound<'static, SmoothTriangle> {
@@ -278,6 +603,20 @@ impl Obj {
         Compound::new(self.flat_triangles())
     }
 
+    /// Builds a compact mesh with the normals of `smooth`.
+    pub fn compact_smooth(self) -> CompactMesh {
+        self.compact(true)
+    }
+
+    /// Builds a compact mesh with the normals of `flat`.
+    pub fn compact_flat(self) -> CompactMesh {
+        self.compact(false)
+    }
+
+    pub fn sampleable(self) -> SampleableMesh {
+        SampleableMesh::new(self.smooth_triangles())
+    }
+
     pub fn smooth_triangles(self) -> Vec<SmoothTriangle> {
         // safety: SmoothTriangle is a different transparent representation of Triangle
         unsafe {
@@ -302,6 +641,450 @@ impl Obj {
         }
     }
 
+    /// Returns the normal of each triangle, following the winding order of its corners, scaled
+    /// by twice its area.
+    pub fn face_normals(&self) -> Vec<Vector> {
+        self.triangles
+            .iter()
+            .map(|ObjTriangle(a, b, c, _)| {
+                let v0 = self.vertexes[a.vertex_idx];
+                let v1 = self.vertexes[b.vertex_idx];
+                let v2 = self.vertexes[c.vertex_idx];
+                (v1 - v0).cross(&(v2 - v0))
+            })
+            .collect()
+    }
+
+    /// Replaces the normals of all corners by the weighted average of the normals of the
+    /// triangles around their vertex, leaving out the triangles at an angle of more than
+    /// `crease_angle` degrees to the triangle of the corner, such that creases stay sharp.
+    pub fn with_smooth_normals(mut self, weighting: NormalWeighting, crease_angle: f64) -> Self {
+        let face_normals = self.face_normals();
+        let cos_crease = crease_angle.to_radians().cos();
+
+        // the weighted normal of each triangle around each vertex
+        let mut vertex_faces = vec![Vec::new(); self.vertexes.len()];
+        for (i, ObjTriangle(a, b, c, _)) in self.triangles.iter().enumerate() {
+            let unit_normal = match face_normals[i].try_normalize(0.) {
+                Some(unit_normal) => unit_normal,
+                None => continue,
+            };
+
+            let corners = [a.vertex_idx, b.vertex_idx, c.vertex_idx];
+            for k in 0..3 {
+                let weight = match weighting {
+                    NormalWeighting::Area => face_normals[i].norm(),
+                    NormalWeighting::Angle => {
+                        let p = self.vertexes[corners[k]];
+                        let e1 = self.vertexes[corners[(k + 1) % 3]] - p;
+                        let e2 = self.vertexes[corners[(k + 2) % 3]] - p;
+                        e1.angle(&e2)
+                    }
+                };
+                vertex_faces[corners[k]].push((unit_normal, weight));
+            }
+        }
+
+        let mut normals = Vec::new();
+        // the index of each distinct normal of each vertex
+        let mut normal_indices = HashMap::new();
+        for (i, ObjTriangle(a, b, c, _)) in self.triangles.iter_mut().enumerate() {
+            let face_normal = face_normals[i].try_normalize(0.);
+
+            for corner in &mut [a, b, c] {
+                let faces = &vertex_faces[corner.vertex_idx];
+                let normal = match face_normal {
+                    Some(face_normal) => faces
+                        .iter()
+                        .filter(|(normal, _)| normal.dot(&face_normal) >= cos_crease)
+                        .map(|&(normal, weight)| weight * normal)
+                        .sum::<Vector>()
+                        .try_normalize(0.)
+                        .unwrap_or(face_normal),
+                    // degenerate triangles take the normal of all triangles around the vertex
+                    None => faces
+                        .iter()
+                        .map(|&(normal, weight)| weight * normal)
+                        .sum::<Vector>()
+                        .try_normalize(0.)
+                        .unwrap_or_else(Vector::z),
+                };
+
+                let key = (corner.vertex_idx, normal.map(f64::to_bits));
+                corner.normal_idx = *normal_indices.entry(key).or_insert_with(|| {
+                    normals.push(normal);
+                    normals.len() - 1
+                });
+            }
+        }
+        self.vertex_normals = normals;
+
+        self
+    }
+
+    /// Merges the vertexes that are at most `tolerance` apart, such that the triangles around
+    /// them share their vertexes, as needed for `with_smooth_normals`. The normals and texture
+    /// coordinates of the corners are unchanged.
+    pub fn weld(mut self, tolerance: f64) -> Self {
+        // the vertexes are sorted into a grid of cells of the size of the tolerance, or by their
+        // coordinates if they must be equal
+        let cell = |p: &Point3| {
+            if tolerance > 0. {
+                let cell = p / tolerance;
+                (
+                    cell.x.floor() as i64,
+                    cell.y.floor() as i64,
+                    cell.z.floor() as i64,
+                )
+            } else {
+                // adding 0 turns -0 into 0, which are equal but differ in their bits
+                let cell = p.map(|x| (x + 0.).to_bits() as i64);
+                (cell.x, cell.y, cell.z)
+            }
+        };
+        let range = if tolerance > 0. { 1 } else { 0 };
+        let mut grid: HashMap<_, Vec<usize>> = HashMap::new();
+        let mut vertexes: Vec<Point3> = Vec::new();
+        let mut vertex_colors = Vec::new();
+
+        let mut new_indices = Vec::with_capacity(self.vertexes.len());
+        for (i, p) in self.vertexes.iter().enumerate() {
+            let (x, y, z) = cell(p);
+            let neighbor = (x - range..=x + range)
+                .cartesian_product(y - range..=y + range)
+                .cartesian_product(z - range..=z + range)
+                .filter_map(|((x, y), z)| grid.get(&(x, y, z)))
+                .flatten()
+                .find(|&&j| (vertexes[j] - p).norm() <= tolerance)
+                .copied();
+
+            let index = neighbor.unwrap_or_else(|| {
+                vertexes.push(*p);
+                if let Some(&color) = self.vertex_colors.get(i) {
+                    vertex_colors.push(color);
+                }
+                grid.entry((x, y, z)).or_default().push(vertexes.len() - 1);
+                vertexes.len() - 1
+            });
+            new_indices.push(index);
+        }
+
+        for ObjTriangle(a, b, c, _) in &mut self.triangles {
+            for corner in &mut [a, b, c] {
+                corner.vertex_idx = new_indices[corner.vertex_idx];
+            }
+        }
+        self.vertexes = vertexes;
+        self.vertex_colors = vertex_colors;
+
+        self
+    }
+
+    /// Removes the triangles without area, such as those of which two corners share a vertex.
+    pub fn remove_degenerate_triangles(mut self) -> Self {
+        let is_degenerate = self
+            .face_normals()
+            .iter()
+            .map(|normal| normal.norm_squared() == 0.)
+            .collect_vec();
+
+        // the polygons that lose a triangle are split into their remaining triangles
+        let mut polygon_sizes = Vec::new();
+        let mut start = 0;
+        for &size in &self.polygon_sizes {
+            let polygon = &is_degenerate[start..start + size];
+            if polygon.iter().any(|&degenerate| degenerate) {
+                let remaining = polygon.iter().filter(|&&degenerate| !degenerate).count();
+                polygon_sizes.extend(vec![1; remaining]);
+            } else {
+                polygon_sizes.push(size);
+            }
+            start += size;
+        }
+        self.polygon_sizes = polygon_sizes;
+
+        let mut is_degenerate = is_degenerate.into_iter();
+        self.triangles.retain(|_| !is_degenerate.next().unwrap());
+
+        self
+    }
+
+    /// Subdivides this mesh `levels` times, with Catmull-Clark if it has polygons other than
+    /// triangles and with Loop otherwise.
+    pub fn subdivide(self, levels: usize) -> Self {
+        if self.polygon_sizes.iter().all(|&size| size == 1) {
+            self.loop_subdivide(levels)
+        } else {
+            self.catmull_clark(levels)
+        }
+    }
+
+    /// Subdivides the triangles of this mesh `levels` times with Loop's scheme, which makes each
+    /// triangle four. The edges where the normals of the faces on either side differ, as well as
+    /// the boundaries, stay sharp, and the texture coordinates are interpolated linearly.
+    pub fn loop_subdivide(self, levels: usize) -> Self {
+        let mut mesh = self.polygon_mesh(false);
+        for _ in 0..levels {
+            mesh = mesh.loop_subdivide();
+        }
+
+        self.with_polygon_mesh(mesh)
+    }
+
+    /// Subdivides the polygons of this mesh `levels` times with the scheme of Catmull and Clark,
+    /// which makes each polygon of n sides n quadrilaterals. Creases, boundaries and texture
+    /// coordinates are handled as by `loop_subdivide`.
+    pub fn catmull_clark(self, levels: usize) -> Self {
+        let mut mesh = self.polygon_mesh(true);
+        for _ in 0..levels {
+            mesh = mesh.catmull_clark();
+        }
+
+        self.with_polygon_mesh(mesh)
+    }
+
+    /// Splits every triangle of this mesh `levels` times into four, at the midpoints of its
+    /// edges, keeping its shape. The normals and texture coordinates are interpolated linearly.
+    pub fn tessellate(mut self, levels: usize) -> Self {
+        for _ in 0..levels {
+            let mut vertex_midpoints = HashMap::new();
+            let mut texture_midpoints = HashMap::new();
+            let mut normal_midpoints = HashMap::new();
+
+            let triangles = mem::take(&mut self.triangles);
+            for ObjTriangle(a, b, c, material) in triangles {
+                let mut midpoint = |a: &ObjTriangleCorner, b: &ObjTriangleCorner| {
+                    let vertex_idx = midpoint_index(
+                        &mut vertex_midpoints,
+                        (a.vertex_idx, b.vertex_idx),
+                        |(a, b)| {
+                            if !self.vertex_colors.is_empty() {
+                                let color = (self.vertex_colors[a] + self.vertex_colors[b]) / 2.;
+                                self.vertex_colors.push(color);
+                            }
+                            let p = self.vertexes[a] + (self.vertexes[b] - self.vertexes[a]) / 2.;
+                            self.vertexes.push(p);
+                            self.vertexes.len() - 1
+                        },
+                    );
+                    let texture_idx = midpoint_index(
+                        &mut texture_midpoints,
+                        (a.texture_idx, b.texture_idx),
+                        |(a, b)| {
+                            let uv = self.texture_coordinates[a]
+                                + (self.texture_coordinates[b] - self.texture_coordinates[a]) / 2.;
+                            self.texture_coordinates.push(uv);
+                            self.texture_coordinates.len() - 1
+                        },
+                    );
+                    let normal_idx = midpoint_index(
+                        &mut normal_midpoints,
+                        (a.normal_idx, b.normal_idx),
+                        |(a, b)| {
+                            let normal = self.vertex_normals[a].normalize()
+                                + self.vertex_normals[b].normalize();
+                            self.vertex_normals
+                                .push(normal.try_normalize(0.).unwrap_or(self.vertex_normals[a]));
+                            self.vertex_normals.len() - 1
+                        },
+                    );
+
+                    ObjTriangleCorner {
+                        vertex_idx,
+                        texture_idx,
+                        normal_idx,
+                    }
+                };
+
+                let ab = midpoint(&a, &b);
+                let bc = midpoint(&b, &c);
+                let ca = midpoint(&c, &a);
+                self.triangles.extend(vec![
+                    ObjTriangle(a, ab.clone(), ca.clone(), material),
+                    ObjTriangle(b, bc.clone(), ab.clone(), material),
+                    ObjTriangle(c, ca.clone(), bc.clone(), material),
+                    ObjTriangle(ab, bc, ca, material),
+                ]);
+            }
+        }
+        self.polygon_sizes = vec![1; self.triangles.len()];
+
+        self
+    }
+
+    /// Moves every vertex along its normal by `scale` times the height given by `texture`. The
+    /// vertexes with several normals or texture coordinates move along the average of their
+    /// normals by the average of their heights, such that the mesh doesn't tear. The normals are
+    /// then computed from the displaced surface, keeping the creases. Tessellate the mesh first
+    /// to displace it in detail.
+    pub fn displace(self, texture: &dyn ScalarTexture, scale: f64) -> Self {
+        let mut normals = vec![Vector::zeros(); self.vertexes.len()];
+        let mut heights = vec![(0., 0); self.vertexes.len()];
+        let mut corners = HashSet::new();
+        for ObjTriangle(a, b, c, _) in &self.triangles {
+            for corner in &[a, b, c] {
+                // each distinct corner of a vertex counts once
+                let key = (corner.vertex_idx, corner.texture_idx, corner.normal_idx);
+                if !corners.insert(key) {
+                    continue;
+                }
+
+                let uv = self.texture_coordinates[corner.texture_idx];
+                let p = self.vertexes[corner.vertex_idx];
+                let (sum, count) = &mut heights[corner.vertex_idx];
+                *sum += texture.get_value(uv, p);
+                *count += 1;
+                normals[corner.vertex_idx] += self.vertex_normals[corner.normal_idx].normalize();
+            }
+        }
+
+        let mut mesh = self.polygon_mesh(false);
+        for ((p, normal), (sum, count)) in mesh.positions.iter_mut().zip(normals).zip(heights) {
+            if let Some(normal) = normal.try_normalize(0.) {
+                *p += scale * sum / count as f64 * normal;
+            }
+        }
+
+        self.with_polygon_mesh(mesh)
+    }
+
+    /// Returns the faces of this mesh, either its polygons or its triangles, with the edges where
+    /// the normals of the faces on either side differ as creases.
+    fn polygon_mesh(&self, polygons: bool) -> PolygonMesh {
+        let sizes = if polygons {
+            self.polygon_sizes.clone()
+        } else {
+            vec![1; self.triangles.len()]
+        };
+
+        let mut faces = Vec::with_capacity(sizes.len());
+        // the normals at both ends of the edges, by face
+        let mut edge_normals: HashMap<Edge, Vec<(Vector, Vector)>> = HashMap::new();
+        let mut start = 0;
+        for size in sizes {
+            let fan = &self.triangles[start..start + size];
+            let corners = iter::once(&fan[0].0)
+                .chain(iter::once(&fan[0].1))
+                .chain(fan.iter().map(|triangle| &triangle.2))
+                .collect_vec();
+            start += size;
+
+            let n = corners.len();
+            for i in 0..n {
+                let (a, b) = (corners[i], corners[(i + 1) % n]);
+                let normal =
+                    |corner: &ObjTriangleCorner| self.vertex_normals[corner.normal_idx].normalize();
+                let normals = if a.vertex_idx < b.vertex_idx {
+                    (normal(a), normal(b))
+                } else {
+                    (normal(b), normal(a))
+                };
+                edge_normals
+                    .entry(edge(a.vertex_idx, b.vertex_idx))
+                    .or_default()
+                    .push(normals);
+            }
+
+            faces.push(Face {
+                vertexes: corners.iter().map(|c| c.vertex_idx).collect(),
+                texture_coordinates: corners
+                    .iter()
+                    .map(|c| self.texture_coordinates[c.texture_idx])
+                    .collect(),
+                material: fan[0].3,
+            });
+        }
+
+        let is_same = |n1: &Vector, n2: &Vector| n1.dot(n2) > 1. - 1e-9;
+        let creases = edge_normals
+            .into_iter()
+            .filter(|(_, normals)| {
+                normals
+                    .iter()
+                    .tuple_windows()
+                    .any(|((a1, b1), (a2, b2))| !is_same(a1, a2) || !is_same(b1, b2))
+            })
+            .map(|(edge, _)| edge)
+            .collect();
+
+        PolygonMesh {
+            positions: self.vertexes.clone(),
+            colors: self.vertex_colors.clone(),
+            faces,
+            creases,
+        }
+    }
+
+    /// Replaces the geometry of this mesh by the faces of `mesh`, split into fans of triangles.
+    fn with_polygon_mesh(mut self, mesh: PolygonMesh) -> Self {
+        let corner_normals = mesh.corner_normals();
+
+        self.vertex_normals.clear();
+        self.texture_coordinates.clear();
+        self.triangles.clear();
+        self.polygon_sizes.clear();
+        let mut normal_indices = HashMap::new();
+        let mut texture_indices = HashMap::new();
+
+        for (face, normals) in mesh.faces.iter().zip(corner_normals) {
+            let corners = face
+                .vertexes
+                .iter()
+                .zip(&face.texture_coordinates)
+                .zip(normals)
+                .map(|((&vertex_idx, uv), normal)| {
+                    let normal_key = (vertex_idx, normal.map(f64::to_bits));
+                    let normal_idx = *normal_indices.entry(normal_key).or_insert_with(|| {
+                        self.vertex_normals.push(normal);
+                        self.vertex_normals.len() - 1
+                    });
+                    let texture_key = (uv.x.to_bits(), uv.y.to_bits());
+                    let texture_idx = *texture_indices.entry(texture_key).or_insert_with(|| {
+                        self.texture_coordinates.push(*uv);
+                        self.texture_coordinates.len() - 1
+                    });
+
+                    ObjTriangleCorner {
+                        vertex_idx,
+                        texture_idx,
+                        normal_idx,
+                    }
+                })
+                .collect_vec();
+
+            for (b, c) in corners[1..].iter().tuple_windows() {
+                self.triangles.push(ObjTriangle(
+                    corners[0].clone(),
+                    b.clone(),
+                    c.clone(),
+                    face.material,
+                ));
+            }
+            self.polygon_sizes.push(corners.len() - 2);
+        }
+        self.vertexes = mesh.positions;
+        self.vertex_colors = mesh.colors;
+
+        self
+    }
+
+    /// Returns the transformation that moves the center of the bounding box of this mesh to the
+    /// origin.
+    pub fn centering_transformation(&self) -> Transformation {
+        let center = self.bbox().centroid();
+        Transformation::translate(-center.x, -center.y, -center.z)
+    }
+
+    /// Returns the transformation that centers this mesh and scales it uniformly, such that its
+    /// bounding box fits in the cube from -1 to 1 along each axis.
+    pub fn normalizing_transformation(&self) -> Transformation {
+        let (_, length) = self.bbox().longest_axis();
+        let scale = if length > 0. { 2. / length } else { 1. };
+
+        self.centering_transformation()
+            .then(&Transformation::scale(scale, scale, scale))
+    }
+
     /// Returns a new instance of this Obj for which `transformation` has been applied to all
     /// vertexes.
     pub fn transform(&self, transformation: &Transformation) -> Self {
@@ -311,21 +1094,57 @@ impl Obj {
             .iter()
             .map(|p| transformation.apply(p))
             .collect();
+        let inverse_transpose = transformation
+            .inverse()
+            .matrix()
+            .fixed_slice::<3, 3>(0, 0)
+            .transpose();
         obj.vertex_normals = obj
             .vertex_normals
             .iter()
-            .map(|n| {
-                transformation
-                    .inverse()
-                    .matrix()
-                    .transpose()
-                    .transform_vector(n)
-                    .normalize()
-            })
+            .map(|n| (inverse_transpose * n).normalize())
             .collect();
         obj
     }
 
+    fn compact(self, smooth: bool) -> CompactMesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut texture_coordinates = Vec::new();
+        // index of each distinct combination of vertex, texture coordinates and normal
+        let mut corner_indices = HashMap::new();
+
+        let indices = self
+            .triangles
+            .iter()
+            .map(|ObjTriangle(a, b, c, _)| {
+                let mut index = |corner: &ObjTriangleCorner| {
+                    let key = (corner.vertex_idx, corner.texture_idx, corner.normal_idx);
+                    *corner_indices.entry(key).or_insert_with(|| {
+                        let p = self.vertexes[corner.vertex_idx];
+                        let n = self.vertex_normals[corner.normal_idx].normalize();
+                        let vt = self.texture_coordinates[corner.texture_idx];
+                        positions.push([p.x as f32, p.y as f32, p.z as f32]);
+                        normals.push([n.x as f32, n.y as f32, n.z as f32]);
+                        texture_coordinates.push([vt.x as f32, vt.y as f32]);
+                        positions.len() as u32 - 1
+                    })
+                };
+
+                [index(a), index(b), index(c)]
+            })
+            .collect();
+
+        CompactMesh::new(Buffers {
+            positions,
+            normals,
+            texture_coordinates,
+            indices,
+            smooth,
+            normal_map: self.normal_map,
+        })
+    }
+
     fn triangles(self) -> Vec<Triangle> {
         let normals = self
             .vertex_normals
@@ -336,16 +1155,22 @@ impl Obj {
             vertexes: self.vertexes,
             normals,
             texture_coordinates: self.texture_coordinates,
+            normal_map: self.normal_map,
         });
 
         self.triangles
             .iter()
-            .map(|ObjTriangle(a, b, c)| {
+            .map(|ObjTriangle(a, b, c, _)| {
                 let n0 = mesh.normals[a.normal_idx];
                 let n1 = mesh.normals[b.normal_idx];
                 let n2 = mesh.normals[c.normal_idx];
 
-                let normal = Unit::new_normalize((*n0 + *n1 + *n2) / 3.);
+                let normal = Unit::new_normalize(face_normal(
+                    mesh.vertexes[a.vertex_idx],
+                    mesh.vertexes[b.vertex_idx],
+                    mesh.vertexes[c.vertex_idx],
+                    *n0 + *n1 + *n2,
+                ));
                 let n = (a.normal_idx, b.normal_idx, c.normal_idx);
                 let v = (a.vertex_idx, b.vertex_idx, c.vertex_idx);
                 let vt = (a.texture_idx, b.texture_idx, c.texture_idx);
@@ -360,10 +1185,243 @@ impl Obj {
             })
             .collect()
     }
+
+    /// Gives every corner without a normal the area weighted average of the normals of the faces
+    /// around its vertex, and every corner without texture coordinates those of the matching
+    /// corner of the unit square.
+    fn complete(&mut self, faces: Vec<([ObjCorner; 3], Option<usize>)>) {
+        let mut smooth_normals = vec![None; self.vertexes.len()];
+        for (face, _) in faces
+            .iter()
+            .filter(|(face, _)| face.iter().any(|c| c.normal.is_none()))
+        {
+            let [v0, v1, v2] = [face[0].vertex, face[1].vertex, face[2].vertex];
+            // the cross product is twice the area of the face
+            let normal = (self.vertexes[v1] - self.vertexes[v0])
+                .cross(&(self.vertexes[v2] - self.vertexes[v0]));
+
+            for &v in &[v0, v1, v2] {
+                let sum = smooth_normals[v].get_or_insert_with(Vector::zeros);
+                *sum += normal;
+            }
+        }
+
+        let mut normal_indexes = vec![None; self.vertexes.len()];
+        let mut default_texture_idx = None;
+        for (face, material) in faces {
+            let mut corners = Vec::with_capacity(3);
+            for (i, corner) in face.iter().enumerate() {
+                let normal_idx = match corner.normal {
+                    Some(normal_idx) => normal_idx,
+                    None => *normal_indexes[corner.vertex].get_or_insert_with(|| {
+                        let normal = smooth_normals[corner.vertex]
+                            .filter(|n| n.norm_squared() > 0.)
+                            .unwrap_or_else(Vector::z);
+                        self.vertex_normals.push(normal);
+                        self.vertex_normals.len() - 1
+                    }),
+                };
+                let texture_idx = match corner.texture {
+                    Some(texture_idx) => texture_idx,
+                    None => {
+                        let first = *default_texture_idx.get_or_insert_with(|| {
+                            self.texture_coordinates.extend(&[
+                                Point2::new(0., 0.),
+                                Point2::new(1., 0.),
+                                Point2::new(1., 1.),
+                            ]);
+                            self.texture_coordinates.len() - 3
+                        });
+                        first + i
+                    }
+                };
+
+                corners.push(ObjTriangleCorner {
+                    vertex_idx: corner.vertex,
+                    texture_idx,
+                    normal_idx,
+                });
+            }
+
+            let c = corners.pop().unwrap();
+            let b = corners.pop().unwrap();
+            let a = corners.pop().unwrap();
+            self.triangles.push(ObjTriangle(a, b, c, material));
+        }
+    }
+}
+
+impl Bounded for Obj {
+    fn bbox(&self) -> Aabb {
+        let mut vertexes = self.vertexes.iter();
+        let first = match vertexes.next() {
+            Some(first) => *first,
+            None => return Aabb::new(Point3::origin(), Point3::origin()),
+        };
+
+        let (p0, p1) = vertexes.fold((first, first), |(p0, p1), p| (p0.inf(p), p1.sup(p)));
+        Aabb::new(p0, p1)
+    }
+}
+
+/// How the normals of the triangles around a vertex are weighted in its smooth normal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NormalWeighting {
+    // by the area of the triangles
+    Area,
+    // by the angle of the triangles at the vertex
+    Angle,
+}
+
+impl FromStr for Obj {
+    type Err = ObjError;
+
+    /// Parses the geometry and material names of a Wavefront OBJ file. Polygons are split into
+    /// fans of triangles, so they must be convex, and statements other than vertex data, faces
+    /// and materials are skipped.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut obj = Self {
+            vertexes: Vec::new(),
+            texture_coordinates: Vec::new(),
+            vertex_normals: Vec::new(),
+            triangles: Vec::new(),
+            material_names: Vec::new(),
+            material_libraries: Vec::new(),
+            normal_map: None,
+            vertex_colors: Vec::new(),
+            polygon_sizes: Vec::new(),
+        };
+        let mut faces = Vec::new();
+        let mut material = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap();
+            let mut parts = line.split_ascii_whitespace();
+            let keyword = parts.next();
+            let mut next_number = || -> Result<f64, ObjError> {
+                let token = parts.next().ok_or(ObjError::MissingValue(line_number))?;
+                token
+                    .parse()
+                    .map_err(|_| ObjError::InvalidNumber(line_number, token.to_owned()))
+            };
+
+            match keyword {
+                Some("v") => {
+                    let x = next_number()?;
+                    let y = next_number()?;
+                    let z = next_number()?;
+
+                    obj.vertexes.push(Point3::new(x, y, z));
+                }
+                Some("vt") => {
+                    let u = next_number()?;
+                    // the v coordinate is optional, but must be valid if present
+                    let v = match next_number() {
+                        Err(ObjError::MissingValue(_)) => 0.,
+                        v => v?,
+                    };
+
+                    obj.texture_coordinates.push(Point2::new(u, v));
+                }
+                Some("vn") => {
+                    let x = next_number()?;
+                    let y = next_number()?;
+                    let z = next_number()?;
+
+                    obj.vertex_normals.push(Vector::new(x, y, z));
+                }
+                Some("f") => {
+                    let corners = line
+                        .split_ascii_whitespace()
+                        .skip(1)
+                        .map(|s| ObjCorner::parse(s, &obj, line_number))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if corners.len() < 3 {
+                        return Err(ObjError::TooFewCorners(line_number));
+                    }
+
+                    for (b, c) in corners[1..].iter().tuple_windows() {
+                        faces.push(([corners[0], *b, *c], material));
+                    }
+                    obj.polygon_sizes.push(corners.len() - 2);
+                }
+                Some("usemtl") => {
+                    let name = line.trim()["usemtl".len()..].trim();
+                    material = match obj.material_names.iter().position(|n| n == name) {
+                        Some(i) => Some(i),
+                        None => {
+                            obj.material_names.push(name.to_owned());
+                            Some(obj.material_names.len() - 1)
+                        }
+                    };
+                }
+                Some("mtllib") => obj
+                    .material_libraries
+                    .extend(line.split_ascii_whitespace().skip(1).map(PathBuf::from)),
+                // objects, groups and smoothing groups don't change the geometry, and points,
+                // lines and free-form surfaces aren't rendered
+                _ => {}
+            }
+        }
+
+        obj.complete(faces);
+
+        Ok(obj)
+    }
+}
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    // line numbers count from 1
+    MissingValue(usize),
+    InvalidNumber(usize, String),
+    InvalidIndex(usize, String),
+    TooFewCorners(usize),
 }
 
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "failed to read OBJ file: {}", err),
+            ObjError::MissingValue(line) => write!(f, "line {}: missing value", line),
+            ObjError::InvalidNumber(line, token) => {
+                write!(f, "line {}: invalid number: {}", line, token)
+            }
+            ObjError::InvalidIndex(line, token) => {
+                write!(f, "line {}: invalid index: {}", line, token)
+            }
+            ObjError::TooFewCorners(line) => {
+                write!(f, "line {}: face with fewer than 3 corners", line)
+            }
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+// the corners and the index of the material name
 #[derive(Clone)]
-struct ObjTriangle(ObjTriangleCorner, ObjTriangleCorner, ObjTriangleCorner);
+struct ObjTriangle(
+    ObjTriangleCorner,
+    ObjTriangleCorner,
+    ObjTriangleCorner,
+    Option<usize>,
+);
 
 #[derive(Clone)]
 struct ObjTriangleCorner {
@@ -372,25 +1430,54 @@ struct ObjTriangleCorner {
     normal_idx: usize,
 }
 
-impl ObjTriangleCorner {
-    fn parse(s: &str) -> Option<Self> {
-        let mut parts = s.split_terminator('/');
+/// A corner of a face as written in the file, of which the texture coordinates and the normal
+/// may be missing.
+#[derive(Copy, Clone)]
+struct ObjCorner {
+    vertex: usize,
+    texture: Option<usize>,
+    normal: Option<usize>,
+}
+
+impl ObjCorner {
+    /// Parses a corner of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    fn parse(s: &str, obj: &Obj, line: usize) -> Result<Self, ObjError> {
+        let mut parts = s.split('/');
+        let mut next_index = |len: usize| -> Result<Option<usize>, ObjError> {
+            match parts.next() {
+                None | Some("") => Ok(None),
+                Some(token) => resolve_index(token, len)
+                    .map(Some)
+                    .ok_or_else(|| ObjError::InvalidIndex(line, token.to_owned())),
+            }
+        };
 
-        let vertex_idx = parts.next()?.parse::<usize>().ok()? - 1;
-        let texture_idx = parts.next()?.parse::<usize>().ok()? - 1;
-        let normal_idx = parts.next()?.parse::<usize>().ok()? - 1;
+        let vertex = next_index(obj.vertexes.len())?
+            .ok_or_else(|| ObjError::InvalidIndex(line, s.to_owned()))?;
+        let texture = next_index(obj.texture_coordinates.len())?;
+        let normal = next_index(obj.vertex_normals.len())?;
 
-        Some(Self {
-            vertex_idx,
-            texture_idx,
-            normal_idx,
+        Ok(Self {
+            vertex,
+            texture,
+            normal,
         })
     }
 }
 
-fn read_file(path: &str) -> Option<String> {
-    let mut input = String::new();
-    let mut file = File::open(path).ok()?;
-    let _ = file.read_to_string(&mut input).ok()?;
-    Some(input)
+/// Converts an index of an OBJ file, which counts from 1 or, when negative, backwards from the
+/// last of the `len` elements defined so far, to an index counting from 0.
+fn resolve_index(token: &str, len: usize) -> Option<usize> {
+    let index = token.parse::<isize>().ok()?;
+    let resolved = if index < 0 {
+        len as isize + index
+    } else {
+        index - 1
+    };
+
+    if (0..len as isize).contains(&resolved) {
+        Some(resolved as usize)
+    } else {
+        None
+    }
 }
@@ -30,13 +30,13 @@ impl Intersect for Plane {
             ((self.point - ray.origin()).dot(&self.normal)) / (ray.direction().dot(&self.normal));
 
         if t > K_EPSILON {
-            return Some(Hit {
+            Some(Hit {
                 t,
                 normal: self.normal,
                 local_hit_point: ray.origin() + t * ray.direction(),
                 shape: (),
                 uv: Point2::origin(),
-            });
+            })
         } else {
             None
         }
@@ -0,0 +1,414 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use crate::film::Rgb;
+use crate::shape::Obj;
+use crate::{Point2, Point3, Vector};
+
+impl Obj {
+    /// Loads a mesh from a PLY file, in ASCII or binary format of either endianness. The
+    /// positions, normals, texture coordinates and colors of the vertexes are read, and the faces
+    /// are split into fans of triangles. Other elements are skipped.
+    pub fn load_ply(path: &str) -> Result<Obj, PlyError> {
+        Self::read_ply(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read_ply<R: BufRead>(mut reader: R) -> Result<Obj, PlyError> {
+        let header = Header::read(&mut reader)?;
+        let mut values = match header.format {
+            Format::Ascii => Values::Ascii(AsciiValues::new(reader)),
+            Format::BinaryLittleEndian => Values::Binary(reader, false),
+            Format::BinaryBigEndian => Values::Binary(reader, true),
+        };
+
+        let mut vertexes = Vec::new();
+        let mut normals = Vec::new();
+        let mut texture_coordinates = Vec::new();
+        let mut colors = Vec::new();
+        let mut triangles = Vec::new();
+
+        for element in &header.elements {
+            let mut row = vec![0.; element.properties.len()];
+            for _ in 0..element.count {
+                match element.name.as_str() {
+                    "vertex" => {
+                        for (value, property) in row.iter_mut().zip(&element.properties) {
+                            *value = values.scalar(property)?;
+                        }
+                        let get = |names: &[&str]| {
+                            element
+                                .properties
+                                .iter()
+                                .position(|p| names.contains(&p.name.as_str()))
+                                .map(|i| (row[i], element.properties[i].kind))
+                        };
+
+                        let position = (get(&["x"]), get(&["y"]), get(&["z"]));
+                        match position {
+                            (Some((x, _)), Some((y, _)), Some((z, _))) => {
+                                vertexes.push(Point3::new(x, y, z))
+                            }
+                            _ => return Err(PlyError::MissingProperty("x, y or z")),
+                        }
+                        if let (Some((x, _)), Some((y, _)), Some((z, _))) =
+                            (get(&["nx"]), get(&["ny"]), get(&["nz"]))
+                        {
+                            normals.push(Vector::new(x, y, z));
+                        }
+                        if let (Some((u, _)), Some((v, _))) = (
+                            get(&["u", "s", "texture_u", "texture_s"]),
+                            get(&["v", "t", "texture_v", "texture_t"]),
+                        ) {
+                            texture_coordinates.push(Point2::new(u, v));
+                        }
+                        if let (Some((red, kind)), Some((green, _)), Some((blue, _))) = (
+                            get(&["red", "r", "diffuse_red"]),
+                            get(&["green", "g", "diffuse_green"]),
+                            get(&["blue", "b", "diffuse_blue"]),
+                        ) {
+                            // integer colors use the whole range of their type
+                            let scale = kind.max_value().map_or(1., |max| 1. / max);
+                            colors.push(Rgb::new(red * scale, green * scale, blue * scale));
+                        }
+                    }
+                    "face" => {
+                        let mut corners = Vec::new();
+                        for property in &element.properties {
+                            let is_indices = property.name == "vertex_indices"
+                                || property.name == "vertex_index";
+                            match property.count {
+                                Some(count) => {
+                                    let count = values.value(count)? as usize;
+                                    for _ in 0..count {
+                                        let value = values.value(property.kind)?;
+                                        if is_indices {
+                                            corners.push(value as usize);
+                                        }
+                                    }
+                                }
+                                None => {
+                                    values.value(property.kind)?;
+                                }
+                            }
+                        }
+
+                        if let Some(&corner) = corners.iter().find(|&&c| c >= header.nb_vertexes())
+                        {
+                            return Err(PlyError::InvalidIndex(corner));
+                        }
+                        for i in 2..corners.len() {
+                            triangles.push([corners[0], corners[i - 1], corners[i]]);
+                        }
+                    }
+                    _ => {
+                        for property in &element.properties {
+                            values.scalar(property)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        // only complete vertex attributes are kept
+        if normals.len() != vertexes.len() {
+            normals.clear();
+        }
+        if texture_coordinates.len() != vertexes.len() {
+            texture_coordinates.clear();
+        }
+
+        let obj = Obj::new(vertexes, normals, texture_coordinates, &triangles);
+        if colors.is_empty() {
+            Ok(obj)
+        } else {
+            Ok(obj.with_vertex_colors(colors))
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+}
+
+impl Header {
+    fn read<R: BufRead>(reader: &mut R) -> Result<Self, PlyError> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(PlyError::UnexpectedEnd);
+            }
+            let line = line.trim().to_owned();
+            if line == "end_header" {
+                break;
+            }
+            lines.push(line);
+        }
+
+        if lines.first().map(String::as_str) != Some("ply") {
+            return Err(PlyError::InvalidHeader("missing magic number".to_owned()));
+        }
+
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        for line in &lines[1..] {
+            let parts = line.split_ascii_whitespace().collect::<Vec<_>>();
+            match parts.as_slice() {
+                ["format", "ascii", _] => format = Some(Format::Ascii),
+                ["format", "binary_little_endian", _] => format = Some(Format::BinaryLittleEndian),
+                ["format", "binary_big_endian", _] => format = Some(Format::BinaryBigEndian),
+                ["element", name, count] => elements.push(Element {
+                    name: (*name).to_owned(),
+                    count: count
+                        .parse()
+                        .map_err(|_| PlyError::InvalidNumber((*count).to_owned()))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count, kind, name] => elements
+                    .last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader(line.clone()))?
+                    .properties
+                    .push(Property {
+                        name: (*name).to_owned(),
+                        kind: Type::parse(kind)?,
+                        count: Some(Type::parse(count)?),
+                    }),
+                ["property", kind, name] => elements
+                    .last_mut()
+                    .ok_or_else(|| PlyError::InvalidHeader(line.clone()))?
+                    .properties
+                    .push(Property {
+                        name: (*name).to_owned(),
+                        kind: Type::parse(kind)?,
+                        count: None,
+                    }),
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                _ => return Err(PlyError::InvalidHeader(line.clone())),
+            }
+        }
+
+        let format = format.ok_or_else(|| PlyError::InvalidHeader("missing format".to_owned()))?;
+
+        Ok(Self { format, elements })
+    }
+
+    fn nb_vertexes(&self) -> usize {
+        self.elements
+            .iter()
+            .find(|element| element.name == "vertex")
+            .map_or(0, |element| element.count)
+    }
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Property {
+    name: String,
+    kind: Type,
+    // type of the length of list properties
+    count: Option<Type>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Type {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl Type {
+    fn parse(s: &str) -> Result<Self, PlyError> {
+        match s {
+            "char" | "int8" => Ok(Type::Int8),
+            "uchar" | "uint8" => Ok(Type::Uint8),
+            "short" | "int16" => Ok(Type::Int16),
+            "ushort" | "uint16" => Ok(Type::Uint16),
+            "int" | "int32" => Ok(Type::Int32),
+            "uint" | "uint32" => Ok(Type::Uint32),
+            "float" | "float32" => Ok(Type::Float32),
+            "double" | "float64" => Ok(Type::Float64),
+            _ => Err(PlyError::UnsupportedType(s.to_owned())),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Type::Int8 | Type::Uint8 => 1,
+            Type::Int16 | Type::Uint16 => 2,
+            Type::Int32 | Type::Uint32 | Type::Float32 => 4,
+            Type::Float64 => 8,
+        }
+    }
+
+    /// Returns the largest value of integer types.
+    fn max_value(self) -> Option<f64> {
+        match self {
+            Type::Int8 => Some(i8::MAX as f64),
+            Type::Uint8 => Some(u8::MAX as f64),
+            Type::Int16 => Some(i16::MAX as f64),
+            Type::Uint16 => Some(u16::MAX as f64),
+            Type::Int32 => Some(i32::MAX as f64),
+            Type::Uint32 => Some(u32::MAX as f64),
+            Type::Float32 | Type::Float64 => None,
+        }
+    }
+}
+
+/// The values of the body of a PLY file.
+enum Values<R> {
+    Ascii(AsciiValues<R>),
+    // the reader and whether the values are big endian
+    Binary(R, bool),
+}
+
+impl<R: BufRead> Values<R> {
+    fn value(&mut self, kind: Type) -> Result<f64, PlyError> {
+        match self {
+            Values::Ascii(values) => values.next(),
+            Values::Binary(reader, big_endian) => {
+                let mut bytes = [0; 8];
+                let bytes = &mut bytes[..kind.size()];
+                reader.read_exact(bytes).map_err(|err| match err.kind() {
+                    io::ErrorKind::UnexpectedEof => PlyError::UnexpectedEnd,
+                    _ => PlyError::Io(err),
+                })?;
+                if *big_endian != cfg!(target_endian = "big") {
+                    bytes.reverse();
+                }
+
+                let value = match kind {
+                    Type::Int8 => bytes[0] as i8 as f64,
+                    Type::Uint8 => bytes[0] as f64,
+                    Type::Int16 => i16::from_ne_bytes([bytes[0], bytes[1]]) as f64,
+                    Type::Uint16 => u16::from_ne_bytes([bytes[0], bytes[1]]) as f64,
+                    Type::Int32 => {
+                        i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+                    }
+                    Type::Uint32 => {
+                        u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+                    }
+                    Type::Float32 => {
+                        f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+                    }
+                    Type::Float64 => {
+                        let mut array = [0; 8];
+                        array.copy_from_slice(bytes);
+                        f64::from_ne_bytes(array)
+                    }
+                };
+
+                Ok(value)
+            }
+        }
+    }
+
+    /// Reads the values of `property`, returning the first one.
+    fn scalar(&mut self, property: &Property) -> Result<f64, PlyError> {
+        match property.count {
+            Some(count) => {
+                let count = self.value(count)? as usize;
+                let mut first = 0.;
+                for i in 0..count {
+                    let value = self.value(property.kind)?;
+                    if i == 0 {
+                        first = value;
+                    }
+                }
+                Ok(first)
+            }
+            None => self.value(property.kind),
+        }
+    }
+}
+
+/// The whitespace separated values of the body of an ASCII PLY file.
+struct AsciiValues<R> {
+    reader: R,
+    // values of the current line, in reverse order
+    pending: Vec<String>,
+}
+
+impl<R: BufRead> AsciiValues<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pending: Vec::new(),
+        }
+    }
+
+    fn next(&mut self) -> Result<f64, PlyError> {
+        while self.pending.is_empty() {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(PlyError::UnexpectedEnd);
+            }
+            self.pending = line
+                .split_ascii_whitespace()
+                .rev()
+                .map(str::to_owned)
+                .collect();
+        }
+
+        let token = self.pending.pop().unwrap();
+        token.parse().map_err(|_| PlyError::InvalidNumber(token))
+    }
+}
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    InvalidHeader(String),
+    UnsupportedType(String),
+    MissingProperty(&'static str),
+    InvalidNumber(String),
+    InvalidIndex(usize),
+    UnexpectedEnd,
+}
+
+impl Display for PlyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PlyError::Io(err) => write!(f, "failed to read PLY file: {}", err),
+            PlyError::InvalidHeader(line) => write!(f, "invalid header: {}", line),
+            PlyError::UnsupportedType(kind) => write!(f, "unsupported type: {}", kind),
+            PlyError::MissingProperty(name) => write!(f, "missing vertex property {}", name),
+            PlyError::InvalidNumber(token) => write!(f, "invalid number: {}", token),
+            PlyError::InvalidIndex(index) => write!(f, "invalid vertex index: {}", index),
+            PlyError::UnexpectedEnd => write!(f, "unexpected end of file"),
+        }
+    }
+}
+
+impl Error for PlyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlyError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(err: io::Error) -> Self {
+        PlyError::Io(err)
+    }
+}
@@ -2,7 +2,7 @@ use nalgebra::Unit;
 
 use crate::math::Ray;
 use crate::sampler::Sample;
-use crate::shape::{Aabb, Bounded, Hit, Intersect};
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample, Union};
 use crate::{Point2, Point3, Vector, K_EPSILON};
 
 #[derive(Clone)]
@@ -18,17 +18,18 @@ impl Rectangle {
         let normal = Unit::new_normalize(a.cross(&b));
         Self { p, a, b, normal }
     }
+}
 
-    pub fn area(&self) -> f64 {
-        self.a.cross(&self.b).norm()
-    }
-
-    pub fn sample(&self, sample: &Sample) -> Point3 {
-        self.p + sample.0 * self.a + sample.1 * self.b
+impl Sampleable for Rectangle {
+    fn sample(&self, _reference: &Point3, sample: Sample) -> SurfaceSample {
+        SurfaceSample {
+            point: self.p + sample.0 * self.a + sample.1 * self.b,
+            normal: self.normal,
+        }
     }
 
-    pub fn normal_at(&self, _p: &Point3) -> Unit<Vector> {
-        self.normal
+    fn area(&self) -> f64 {
+        self.a.cross(&self.b).norm()
     }
 }
 
@@ -37,7 +38,9 @@ impl Bounded for Rectangle {
         let p0 = self.p;
         let p1 = self.p + self.a + self.b;
 
-        Aabb::new(p0, p1)
+        Aabb::new(p0.inf(&p1), p0.sup(&p1))
+            .union(self.p + self.a)
+            .union(self.p + self.b)
     }
 }
 
@@ -1,5 +1,9 @@
-use crate::math::Ray;
-use crate::shape::{Aabb, Bounded, Hit, Intersect};
+use nalgebra::Unit;
+use std::f64::consts::PI;
+
+use crate::math::{OrthonormalBasis, Ray};
+use crate::sampler::{uniform_sample_sphere, Sample};
+use crate::shape::{Aabb, Bounded, Hit, Intersect, Sampleable, SurfaceSample};
 use crate::{Point2, Point3, K_EPSILON};
 
 /// Represents a three-dimensional unit sphere, centered at the origin.
@@ -70,3 +74,80 @@ impl Intersect for Sphere {
         1
     }
 }
+
+impl Sampleable for Sphere {
+    /// Samples the part of the sphere that is visible from `reference`, by uniformly sampling the
+    /// cone of directions it subtends. If `reference` lies inside the sphere, the whole surface
+    /// is sampled uniformly.
+    fn sample(&self, reference: &Point3, sample: Sample) -> SurfaceSample {
+        let distance_squared = reference.coords.norm_squared();
+        if distance_squared <= 1. {
+            let normal = Unit::new_normalize(uniform_sample_sphere(sample));
+
+            return SurfaceSample {
+                point: Point3::from(normal.into_inner()),
+                normal,
+            };
+        }
+
+        let sin2_theta_max = 1. / distance_squared;
+        let sin_theta_max = sin2_theta_max.sqrt();
+        let cos_theta_max = (1. - sin2_theta_max).max(0.).sqrt();
+
+        let mut cos_theta = (cos_theta_max - 1.) * sample.0 + 1.;
+        let mut sin2_theta = 1. - cos_theta * cos_theta;
+        if sin2_theta_max < 0.00068523 {
+            // avoids the loss of precision for spheres that are far away, sin^2(1.5 deg)
+            sin2_theta = sin2_theta_max * sample.0;
+            cos_theta = (1. - sin2_theta).sqrt();
+        }
+
+        // angle between the direction from the center to the reference point and the normal
+        // of the sampled point
+        let cos_alpha = sin2_theta / sin_theta_max
+            + cos_theta * (1. - sin2_theta / sin2_theta_max).max(0.).sqrt();
+        let sin_alpha = (1. - cos_alpha * cos_alpha).max(0.).sqrt();
+        let phi = 2. * PI * sample.1;
+
+        let basis = OrthonormalBasis::from_vector(&reference.coords).unwrap();
+        let normal = Unit::new_normalize(
+            sin_alpha * phi.cos() * *basis.u
+                + sin_alpha * phi.sin() * *basis.v
+                + cos_alpha * *basis.w,
+        );
+
+        SurfaceSample {
+            point: Point3::from(normal.into_inner()),
+            normal,
+        }
+    }
+
+    fn pdf(&self, reference: &Point3, point: &SurfaceSample) -> f64 {
+        let distance_squared = reference.coords.norm_squared();
+        if distance_squared <= 1. {
+            return 1. / self.area();
+        }
+
+        let cos_theta_max = (1. - 1. / distance_squared).max(0.).sqrt();
+        let solid_angle_pdf = 1. / (2. * PI * (1. - cos_theta_max));
+
+        let d = reference - point.point;
+        let d_squared = d.norm_squared();
+        let cos_point = point.normal.dot(&d).abs() / d_squared.sqrt();
+
+        solid_angle_pdf * cos_point / d_squared
+    }
+
+    fn sample_uniform(&self, sample: Sample) -> SurfaceSample {
+        let normal = Unit::new_normalize(uniform_sample_sphere(sample));
+
+        SurfaceSample {
+            point: Point3::from(normal.into_inner()),
+            normal,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        4. * PI
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::str;
+
+use crate::shape::Obj;
+use crate::Point3;
+
+// size of the header and the triangle count of binary files
+const BINARY_HEADER_SIZE: usize = 84;
+// size of a normal, three vertexes and the attribute byte count
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+impl Obj {
+    /// Loads a mesh from an STL file, in ASCII or binary format. The triangles don't share their
+    /// vertexes, and their normals are computed from their winding, as the normals in STL files
+    /// are often unreliable.
+    pub fn load_stl(path: &str) -> Result<Obj, StlError> {
+        Self::parse_stl(&fs::read(path)?)
+    }
+
+    pub fn parse_stl(bytes: &[u8]) -> Result<Obj, StlError> {
+        // binary files may start with "solid" too, so they're recognized by their size
+        let is_binary = bytes.len() >= BINARY_HEADER_SIZE && {
+            let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
+            bytes.len() == BINARY_HEADER_SIZE + count as usize * BINARY_TRIANGLE_SIZE
+        };
+
+        let vertexes = if is_binary {
+            parse_binary(&bytes[BINARY_HEADER_SIZE..])
+        } else {
+            parse_ascii(str::from_utf8(bytes).map_err(|_| StlError::InvalidText)?)?
+        };
+        let triangles = (0..vertexes.len() / 3)
+            .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+            .collect::<Vec<_>>();
+
+        Ok(Obj::new(vertexes, Vec::new(), Vec::new(), &triangles))
+    }
+}
+
+fn parse_binary(bytes: &[u8]) -> Vec<Point3> {
+    let coordinate = |bytes: &[u8]| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    bytes
+        .chunks_exact(BINARY_TRIANGLE_SIZE)
+        .flat_map(|triangle| {
+            // the normal comes first and the attribute byte count last
+            triangle[12..48].chunks_exact(12).map(move |vertex| {
+                Point3::new(
+                    coordinate(&vertex[0..4]) as f64,
+                    coordinate(&vertex[4..8]) as f64,
+                    coordinate(&vertex[8..12]) as f64,
+                )
+            })
+        })
+        .collect()
+}
+
+fn parse_ascii(input: &str) -> Result<Vec<Point3>, StlError> {
+    let mut vertexes = Vec::new();
+    let mut facet_vertexes = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let mut parts = line.split_ascii_whitespace();
+        let keyword = parts.next();
+        let mut next_number = || -> Result<f64, StlError> {
+            let token = parts.next().ok_or(StlError::MissingValue(line_number))?;
+            token
+                .parse()
+                .map_err(|_| StlError::InvalidNumber(line_number, token.to_owned()))
+        };
+
+        match keyword {
+            Some("vertex") => {
+                let x = next_number()?;
+                let y = next_number()?;
+                let z = next_number()?;
+                vertexes.push(Point3::new(x, y, z));
+                facet_vertexes += 1;
+            }
+            Some("endfacet") => {
+                if facet_vertexes != 3 {
+                    return Err(StlError::InvalidFacet(line_number));
+                }
+                facet_vertexes = 0;
+            }
+            // the normals are ignored, and so are the names of the solids
+            _ => {}
+        }
+    }
+
+    if facet_vertexes != 0 {
+        return Err(StlError::InvalidFacet(input.lines().count()));
+    }
+
+    Ok(vertexes)
+}
+
+#[derive(Debug)]
+pub enum StlError {
+    Io(io::Error),
+    InvalidText,
+    // line numbers count from 1
+    MissingValue(usize),
+    InvalidNumber(usize, String),
+    InvalidFacet(usize),
+}
+
+impl Display for StlError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            StlError::Io(err) => write!(f, "failed to read STL file: {}", err),
+            StlError::InvalidText => write!(f, "neither a binary nor a valid ASCII STL file"),
+            StlError::MissingValue(line) => write!(f, "line {}: missing value", line),
+            StlError::InvalidNumber(line, token) => {
+                write!(f, "line {}: invalid number: {}", line, token)
+            }
+            StlError::InvalidFacet(line) => {
+                write!(f, "line {}: facet without three vertexes", line)
+            }
+        }
+    }
+}
+
+impl Error for StlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StlError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StlError {
+    fn from(err: io::Error) -> Self {
+        StlError::Io(err)
+    }
+}
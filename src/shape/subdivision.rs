@@ -0,0 +1,424 @@
+use nalgebra::Vector2;
+use std::collections::{HashMap, HashSet};
+
+use crate::film::Rgb;
+use crate::{Point2, Point3, Vector};
+
+/// A mesh of polygons, as subdivided by `Obj::loop_subdivide` and `Obj::catmull_clark`.
+pub(super) struct PolygonMesh {
+    pub(super) positions: Vec<Point3>,
+    // colors of the vertexes, if any
+    pub(super) colors: Vec<Rgb>,
+    pub(super) faces: Vec<Face>,
+    // edges that stay sharp, by their vertexes in increasing order
+    pub(super) creases: HashSet<Edge>,
+}
+
+pub(super) struct Face {
+    pub(super) vertexes: Vec<usize>,
+    pub(super) texture_coordinates: Vec<Point2>,
+    pub(super) material: Option<usize>,
+}
+
+pub(super) type Edge = (usize, usize);
+
+pub(super) fn edge(a: usize, b: usize) -> Edge {
+    (a.min(b), a.max(b))
+}
+
+// weights of the vertexes of the coarse mesh that make a vertex of the subdivided mesh
+type Stencil = Vec<(usize, f64)>;
+
+impl PolygonMesh {
+    /// Subdivides every triangle into four with Loop's scheme.
+    pub(super) fn loop_subdivide(&self) -> Self {
+        assert!(self.faces.iter().all(|face| face.vertexes.len() == 3));
+        let topology = Topology::new(self);
+
+        let mut stencils = (0..self.positions.len())
+            .map(|v| {
+                topology.vertex_stencil(v, |neighbors| {
+                    let k = neighbors.len() as f64;
+                    let beta = if neighbors.len() == 3 {
+                        3. / 16.
+                    } else {
+                        3. / (8. * k)
+                    };
+                    let mut stencil = vec![(v, 1. - k * beta)];
+                    stencil.extend(neighbors.iter().map(|&n| (n, beta)));
+                    stencil
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let edge_points = self.edge_points(&mut stencils, |(a, b)| {
+            if topology.is_sharp((a, b)) {
+                return vec![(a, 0.5), (b, 0.5)];
+            }
+
+            let mut stencil = vec![(a, 3. / 8.), (b, 3. / 8.)];
+            for &f in &topology.edge_faces[&(a, b)] {
+                let opposite = self.faces[f]
+                    .vertexes
+                    .iter()
+                    .find(|&&v| v != a && v != b)
+                    .copied();
+                stencil.extend(opposite.map(|v| (v, 1. / 8.)));
+            }
+            stencil
+        });
+
+        let mut faces = Vec::with_capacity(4 * self.faces.len());
+        for face in &self.faces {
+            let [v0, v1, v2] = [face.vertexes[0], face.vertexes[1], face.vertexes[2]];
+            let [t0, t1, t2] = [
+                face.texture_coordinates[0],
+                face.texture_coordinates[1],
+                face.texture_coordinates[2],
+            ];
+            let e01 = edge_points[&edge(v0, v1)];
+            let e12 = edge_points[&edge(v1, v2)];
+            let e20 = edge_points[&edge(v2, v0)];
+            let t01 = midpoint(t0, t1);
+            let t12 = midpoint(t1, t2);
+            let t20 = midpoint(t2, t0);
+
+            for &(vertexes, texture_coordinates) in &[
+                ([v0, e01, e20], [t0, t01, t20]),
+                ([v1, e12, e01], [t1, t12, t01]),
+                ([v2, e20, e12], [t2, t20, t12]),
+                ([e01, e12, e20], [t01, t12, t20]),
+            ] {
+                faces.push(Face {
+                    vertexes: vertexes.to_vec(),
+                    texture_coordinates: texture_coordinates.to_vec(),
+                    material: face.material,
+                });
+            }
+        }
+
+        self.refined(&stencils, faces, &edge_points)
+    }
+
+    /// Subdivides every polygon into quadrilaterals with the scheme of Catmull and Clark.
+    pub(super) fn catmull_clark(&self) -> Self {
+        let topology = Topology::new(self);
+        let face_stencils = self
+            .faces
+            .iter()
+            .map(|face| {
+                let weight = 1. / face.vertexes.len() as f64;
+                face.vertexes.iter().map(|&v| (v, weight)).collect()
+            })
+            .collect::<Vec<Stencil>>();
+
+        let mut stencils = (0..self.positions.len())
+            .map(|v| {
+                topology.vertex_stencil(v, |neighbors| {
+                    // the average of the face points, plus twice the average of the edge
+                    // midpoints, plus k - 3 times the vertex itself, divided by k
+                    let k = neighbors.len() as f64;
+                    let faces = &topology.vertex_faces[v];
+                    let mut stencil = vec![(v, (k - 2.) / k)];
+                    stencil.extend(neighbors.iter().map(|&n| (n, 1. / (k * k))));
+                    for &f in faces {
+                        let weight = 1. / (k * faces.len() as f64);
+                        stencil.extend(face_stencils[f].iter().map(|&(u, w)| (u, w * weight)));
+                    }
+                    stencil
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let first_face_point = stencils.len();
+        stencils.extend(face_stencils.iter().cloned());
+
+        let edge_points = self.edge_points(&mut stencils, |(a, b)| {
+            if topology.is_sharp((a, b)) {
+                return vec![(a, 0.5), (b, 0.5)];
+            }
+
+            let mut stencil = vec![(a, 0.25), (b, 0.25)];
+            for &f in &topology.edge_faces[&(a, b)] {
+                stencil.extend(face_stencils[f].iter().map(|&(v, w)| (v, w * 0.25)));
+            }
+            stencil
+        });
+
+        let mut faces = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            let n = face.vertexes.len();
+            let face_point = first_face_point + f;
+            let center = face
+                .texture_coordinates
+                .iter()
+                .fold(Vector2::zeros(), |sum, t| sum + t.coords)
+                / n as f64;
+
+            for i in 0..n {
+                let previous = (i + n - 1) % n;
+                let next = (i + 1) % n;
+                let vertexes = [
+                    face.vertexes[i],
+                    edge_points[&edge(face.vertexes[i], face.vertexes[next])],
+                    face_point,
+                    edge_points[&edge(face.vertexes[previous], face.vertexes[i])],
+                ];
+                let t = &face.texture_coordinates;
+                let texture_coordinates = [
+                    t[i],
+                    midpoint(t[i], t[next]),
+                    Point2::from(center),
+                    midpoint(t[previous], t[i]),
+                ];
+
+                faces.push(Face {
+                    vertexes: vertexes.to_vec(),
+                    texture_coordinates: texture_coordinates.to_vec(),
+                    material: face.material,
+                });
+            }
+        }
+
+        self.refined(&stencils, faces, &edge_points)
+    }
+
+    /// Returns the normal of each corner of each face, averaged with the normals of the faces
+    /// around its vertex, up to the first crease on either side.
+    pub(super) fn corner_normals(&self) -> Vec<Vec<Vector>> {
+        let face_normals = self
+            .faces
+            .iter()
+            .map(|face| {
+                let p = |i: usize| self.positions[face.vertexes[i]];
+                (2..face.vertexes.len())
+                    .map(|i| (p(i - 1) - p(0)).cross(&(p(i) - p(0))))
+                    .sum::<Vector>()
+                    .try_normalize(0.)
+                    .unwrap_or_else(Vector::zeros)
+            })
+            .collect::<Vec<_>>();
+
+        // the corners are joined across the smooth edges, into sets sharing their normal
+        let mut offsets = Vec::with_capacity(self.faces.len());
+        let mut nb_corners = 0;
+        for face in &self.faces {
+            offsets.push(nb_corners);
+            nb_corners += face.vertexes.len();
+        }
+        let corner = |f: usize, v: usize| {
+            offsets[f] + self.faces[f].vertexes.iter().position(|&u| u == v).unwrap()
+        };
+        let topology = Topology::new(self);
+        let mut sets = DisjointSets::new(nb_corners);
+        for (&(a, b), faces) in &topology.edge_faces {
+            if !topology.is_sharp((a, b)) {
+                for &v in &[a, b] {
+                    sets.union(corner(faces[0], v), corner(faces[1], v));
+                }
+            }
+        }
+
+        // the normals of the faces are weighted by their angle at the corner
+        let mut sums = vec![Vector::zeros(); nb_corners];
+        for (f, face) in self.faces.iter().enumerate() {
+            let n = face.vertexes.len();
+            for i in 0..n {
+                let p = self.positions[face.vertexes[i]];
+                let e1 = self.positions[face.vertexes[(i + 1) % n]] - p;
+                let e2 = self.positions[face.vertexes[(i + n - 1) % n]] - p;
+                let angle = if e1.norm_squared() > 0. && e2.norm_squared() > 0. {
+                    e1.angle(&e2)
+                } else {
+                    0.
+                };
+                sums[sets.find(offsets[f] + i)] += angle * face_normals[f];
+            }
+        }
+
+        self.faces
+            .iter()
+            .enumerate()
+            .map(|(f, face)| {
+                (0..face.vertexes.len())
+                    .map(|i| {
+                        sums[sets.find(offsets[f] + i)]
+                            .try_normalize(0.)
+                            .or_else(|| face_normals[f].try_normalize(0.))
+                            .unwrap_or_else(Vector::z)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Adds a vertex for each edge of the faces, with the stencil given by `stencil`, and returns
+    /// their indices.
+    fn edge_points<F>(&self, stencils: &mut Vec<Stencil>, stencil: F) -> HashMap<Edge, usize>
+    where
+        F: Fn(Edge) -> Stencil,
+    {
+        let mut edge_points = HashMap::new();
+        for face in &self.faces {
+            let n = face.vertexes.len();
+            for i in 0..n {
+                let e = edge(face.vertexes[i], face.vertexes[(i + 1) % n]);
+                edge_points.entry(e).or_insert_with(|| {
+                    stencils.push(stencil(e));
+                    stencils.len() - 1
+                });
+            }
+        }
+
+        edge_points
+    }
+
+    /// Returns the mesh of `faces`, of which the vertexes are given by `stencils`. The halves of
+    /// the creases stay creases.
+    fn refined(
+        &self,
+        stencils: &[Stencil],
+        faces: Vec<Face>,
+        edge_points: &HashMap<Edge, usize>,
+    ) -> Self {
+        let positions = stencils
+            .iter()
+            .map(|stencil| {
+                Point3::from(
+                    stencil
+                        .iter()
+                        .map(|&(v, w)| w * self.positions[v].coords)
+                        .sum::<Vector>(),
+                )
+            })
+            .collect();
+        let colors = if self.colors.is_empty() {
+            Vec::new()
+        } else {
+            stencils
+                .iter()
+                .map(|stencil| stencil.iter().map(|&(v, w)| self.colors[v] * w).sum())
+                .collect()
+        };
+        let creases = self
+            .creases
+            .iter()
+            .filter_map(|&(a, b)| edge_points.get(&(a, b)).map(|&e| (a, b, e)))
+            .flat_map(|(a, b, e)| vec![edge(a, e), edge(e, b)])
+            .collect();
+
+        Self {
+            positions,
+            colors,
+            faces,
+            creases,
+        }
+    }
+}
+
+fn midpoint(a: Point2, b: Point2) -> Point2 {
+    Point2::from((a.coords + b.coords) / 2.)
+}
+
+/// The adjacency of the vertexes, edges and faces of a polygon mesh.
+struct Topology<'a> {
+    mesh: &'a PolygonMesh,
+    edge_faces: HashMap<Edge, Vec<usize>>,
+    // the vertexes sharing an edge with each vertex
+    neighbors: Vec<Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl<'a> Topology<'a> {
+    fn new(mesh: &'a PolygonMesh) -> Self {
+        let mut edge_faces: HashMap<Edge, Vec<usize>> = HashMap::new();
+        let mut neighbors = vec![Vec::new(); mesh.positions.len()];
+        let mut vertex_faces = vec![Vec::new(); mesh.positions.len()];
+
+        for (f, face) in mesh.faces.iter().enumerate() {
+            let n = face.vertexes.len();
+            for i in 0..n {
+                let (a, b) = (face.vertexes[i], face.vertexes[(i + 1) % n]);
+                let faces = edge_faces.entry(edge(a, b)).or_default();
+                if faces.is_empty() {
+                    neighbors[a].push(b);
+                    neighbors[b].push(a);
+                }
+                faces.push(f);
+                vertex_faces[a].push(f);
+            }
+        }
+
+        Self {
+            mesh,
+            edge_faces,
+            neighbors,
+            vertex_faces,
+        }
+    }
+
+    /// Returns whether the surface is sharp along `edge`, which is the case for creases,
+    /// boundaries and edges shared by more than two faces.
+    fn is_sharp(&self, edge: Edge) -> bool {
+        self.mesh.creases.contains(&edge) || self.edge_faces[&edge].len() != 2
+    }
+
+    /// Returns the stencil of the vertex replacing `v`, given by `smooth` if there are less than
+    /// two sharp edges around it. Vertexes between two sharp edges move along them, and those at
+    /// the corner of more sharp edges, or of a single face, stay in place.
+    fn vertex_stencil<F>(&self, v: usize, smooth: F) -> Stencil
+    where
+        F: Fn(&[usize]) -> Stencil,
+    {
+        let neighbors = &self.neighbors[v];
+        let sharp = neighbors
+            .iter()
+            .copied()
+            .filter(|&n| self.is_sharp(edge(v, n)))
+            .collect::<Vec<_>>();
+
+        match sharp.len() {
+            _ if neighbors.is_empty() => vec![(v, 1.)],
+            0 | 1 => smooth(neighbors),
+            2 if self.vertex_faces[v].len() > 1 => {
+                vec![(v, 3. / 4.), (sharp[0], 1. / 8.), (sharp[1], 1. / 8.)]
+            }
+            _ => vec![(v, 1.)],
+        }
+    }
+}
+
+/// Disjoint sets of indices, with path compression.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut i = i;
+        while self.parents[i] != root {
+            let parent = self.parents[i];
+            self.parents[i] = root;
+            i = parent;
+        }
+
+        root
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        let root_i = self.find(i);
+        let root_j = self.find(j);
+        self.parents[root_i] = root_j;
+    }
+}
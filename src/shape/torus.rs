@@ -0,0 +1,96 @@
+use std::f64::consts::PI;
+
+use crate::math::{quartic, Ray};
+use crate::shape::{Aabb, Bounded, Hit, Intersect};
+use crate::{Point2, Point3, Vector, K_EPSILON};
+
+/// A torus around the z-axis, centered at the origin, of which the tube of radius
+/// `minor_radius` circles the z-axis at a distance `major_radius`.
+#[derive(Debug, Copy, Clone)]
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        assert!(minor_radius > 0.);
+        assert!(major_radius > minor_radius);
+
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Bounded for Torus {
+    fn bbox(&self) -> Aabb {
+        let extent = self.major_radius + self.minor_radius;
+
+        Aabb::new(
+            Point3::new(-extent, -extent, -self.minor_radius),
+            Point3::new(extent, extent, self.minor_radius),
+        )
+    }
+}
+
+impl Intersect for Torus {
+    type Intersection = ();
+
+    /// Solves (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2) for the points p along the ray.
+    fn intersect(&self, ray: &Ray) -> Option<Hit<()>> {
+        // starts the ray at its bounding box, as the quartic loses precision far away from the
+        // torus
+        let (t_start, _) = self.bbox().intersect_range(ray)?;
+        let t_start = t_start.max(0.);
+
+        let origin = (ray.origin() + t_start * ray.direction()).coords;
+        let direction = ray.direction();
+        let major_squared = self.major_radius * self.major_radius;
+
+        let d_d = direction.norm_squared();
+        let o_d = origin.dot(direction);
+        let e = origin.norm_squared() + major_squared - self.minor_radius.powi(2);
+        let d_xy = direction.x * direction.x + direction.y * direction.y;
+        let o_d_xy = origin.x * direction.x + origin.y * direction.y;
+        let o_xy = origin.x * origin.x + origin.y * origin.y;
+
+        let roots = quartic(
+            d_d * d_d,
+            4. * d_d * o_d,
+            4. * o_d * o_d + 2. * d_d * e - 4. * major_squared * d_xy,
+            4. * o_d * e - 8. * major_squared * o_d_xy,
+            e * e - 4. * major_squared * o_xy,
+        );
+        let t = roots
+            .into_iter()
+            .map(|t| t_start + t)
+            .find(|&t| t > K_EPSILON)?;
+
+        let p = ray.origin() + t * direction;
+        let g = p.coords.norm_squared() + major_squared - self.minor_radius.powi(2);
+        let normal = Vector::new(
+            p.x * (g - 2. * major_squared),
+            p.y * (g - 2. * major_squared),
+            p.z * g,
+        );
+
+        let phi = p.y.atan2(p.x).rem_euclid(2. * PI);
+        let theta =
+            p.z.atan2(p.x.hypot(p.y) - self.major_radius)
+                .rem_euclid(2. * PI);
+
+        Some(Hit {
+            t,
+            normal,
+            local_hit_point: p,
+            shape: (),
+            uv: Point2::new(phi / (2. * PI), theta / (2. * PI)),
+        })
+    }
+
+    fn count_intersection_tests(&self, _ray: &Ray) -> usize {
+        1
+    }
+}
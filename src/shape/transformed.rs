@@ -1,9 +1,15 @@
 use itertools::Itertools;
+use nalgebra::{Matrix3, Unit};
+use std::sync::Arc;
 
 use crate::math::{Ray, Transformation};
+use crate::sampler::Sample;
 use crate::shape::compound::Compound;
 use crate::shape::obj::SmoothTriangle;
-use crate::shape::{Aabb, Bounded, Cuboid, Hit, Intersect, Obj, Plane, Sphere};
+use crate::shape::{
+    Aabb, Annulus, Bounded, CompactMesh, Cone, Cuboid, Cylinder, Disk, Hit, Intersect, Obj, Plane,
+    Sampleable, Sphere, SurfaceSample, Torus,
+};
 use crate::{Point3, Vector};
 
 pub struct Transformed<S> {
@@ -20,12 +26,45 @@ impl<S> Transformed<S> {
     }
 
     fn inverse_transform_normal(&self, normal: &Vector) -> Vector {
+        (self.inverse_linear().transpose() * normal).normalize()
+    }
+
+    fn transform_normal(&self, normal: &Vector) -> Vector {
+        (self.linear().transpose() * normal).normalize()
+    }
+
+    /// Returns the factor by which an infinitesimal area of the untransformed shape, with the
+    /// given normal, is scaled by the transformation.
+    fn area_scale(&self, normal: &Vector) -> f64 {
+        self.linear().determinant().abs() * (self.inverse_linear().transpose() * normal).norm()
+    }
+
+    /// Returns whether the transformation scales all areas by the same factor, whatever their
+    /// orientation, which is the case when it's a rotation combined with a uniform scaling.
+    fn scales_areas_uniformly(&self) -> bool {
+        let linear = self.linear();
+        let gram = linear.transpose() * linear;
+        let scale = gram.trace() / 3.;
+
+        (gram - Matrix3::identity() * scale)
+            .iter()
+            .all(|entry| entry.abs() <= 1e-9 * scale)
+    }
+
+    fn linear(&self) -> Matrix3<f64> {
+        self.transformation
+            .matrix()
+            .matrix()
+            .fixed_slice::<3, 3>(0, 0)
+            .into()
+    }
+
+    fn inverse_linear(&self) -> Matrix3<f64> {
         self.transformation
             .inverse()
             .matrix()
-            .transpose()
-            .transform_vector(normal)
-            .normalize()
+            .fixed_slice::<3, 3>(0, 0)
+            .into()
     }
 
     fn transform_bounding_box(&self, aabb: Aabb) -> Aabb {
@@ -72,6 +111,14 @@ impl<S> Transformed<S> {
     }
 }
 
+impl<S> Transformed<Arc<S>> {
+    /// Creates an instance of `shape`, which shares the shape and its acceleration structure with
+    /// all other instances.
+    pub fn instance(shape: &Arc<S>, transformation: Transformation) -> Self {
+        Self::new(Arc::clone(shape), transformation)
+    }
+}
+
 impl Transformed<Cuboid> {
     pub fn cuboid(corner: Point3, transformation: Transformation) -> Self {
         let shape = Cuboid::new(corner);
@@ -86,6 +133,48 @@ impl<'a> Transformed<Compound<'a, SmoothTriangle>> {
     }
 }
 
+impl Transformed<CompactMesh> {
+    pub fn compact_mesh(obj: Obj, transformation: Transformation) -> Self {
+        let shape = obj.compact_smooth();
+        Self::new(shape, transformation)
+    }
+}
+
+impl Transformed<Cylinder> {
+    pub fn cylinder(radius: f64, z_min: f64, z_max: f64, transformation: Transformation) -> Self {
+        let shape = Cylinder::new(radius, z_min, z_max);
+        Self::new(shape, transformation)
+    }
+}
+
+impl Transformed<Disk> {
+    pub fn disk(radius: f64, transformation: Transformation) -> Self {
+        let shape = Disk::new(radius);
+        Self::new(shape, transformation)
+    }
+}
+
+impl Transformed<Annulus> {
+    pub fn annulus(inner_radius: f64, outer_radius: f64, transformation: Transformation) -> Self {
+        let shape = Annulus::new(inner_radius, outer_radius);
+        Self::new(shape, transformation)
+    }
+}
+
+impl Transformed<Cone> {
+    pub fn cone(radius: f64, height: f64, transformation: Transformation) -> Self {
+        let shape = Cone::new(radius, height);
+        Self::new(shape, transformation)
+    }
+}
+
+impl Transformed<Torus> {
+    pub fn torus(major_radius: f64, minor_radius: f64, transformation: Transformation) -> Self {
+        let shape = Torus::new(major_radius, minor_radius);
+        Self::new(shape, transformation)
+    }
+}
+
 impl Transformed<Plane> {
     pub fn plane(normal: Vector, point: Point3, transformation: Transformation) -> Self {
         let shape = Plane::new(normal, point);
@@ -128,3 +217,45 @@ impl<S: Intersect> Intersect for Transformed<S> {
         self.shape.hit(&inv_ray)
     }
 }
+
+impl<S: Sampleable> Sampleable for Transformed<S> {
+    fn sample(&self, reference: &Point3, sample: Sample) -> SurfaceSample {
+        let local_reference = self.transformation.apply_inverse(reference);
+        let local_sample = self.shape.sample(&local_reference, sample);
+
+        SurfaceSample {
+            point: self.transformation.apply(&local_sample.point),
+            normal: Unit::new_unchecked(self.inverse_transform_normal(&local_sample.normal)),
+        }
+    }
+
+    fn sample_uniform(&self, sample: Sample) -> SurfaceSample {
+        let local_sample = self.shape.sample_uniform(sample);
+
+        SurfaceSample {
+            point: self.transformation.apply(&local_sample.point),
+            normal: Unit::new_unchecked(self.inverse_transform_normal(&local_sample.normal)),
+        }
+    }
+
+    fn pdf(&self, reference: &Point3, point: &SurfaceSample) -> f64 {
+        let local_reference = self.transformation.apply_inverse(reference);
+        let local_point = SurfaceSample {
+            point: self.transformation.apply_inverse(&point.point),
+            normal: Unit::new_unchecked(self.transform_normal(&point.normal)),
+        };
+
+        self.shape.pdf(&local_reference, &local_point) / self.area_scale(&local_point.normal)
+    }
+
+    /// Returns the area of the transformed shape. This is only exact for transformations that
+    /// scale all areas by the same factor, see `has_exact_area`.
+    fn area(&self) -> f64 {
+        let local_sample = self.shape.sample(&Point3::origin(), (0.5, 0.5));
+        self.shape.area() * self.area_scale(&local_sample.normal)
+    }
+
+    fn has_exact_area(&self) -> bool {
+        self.shape.has_exact_area() && self.scales_areas_uniformly()
+    }
+}
@@ -1,15 +1,27 @@
+use image::codecs::hdr::HdrDecoder;
 use image::io::Reader as ImageReader;
 use image::GenericImageView;
-use image::{ImageError, Pixel};
+use image::ImageError;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
 
-use crate::film::Rgb;
+use crate::film::{ColorSpace, Encoding, Rgb, TransferFunction};
 use crate::shade_rec::ShadeRec;
-use crate::Point3;
+use crate::{Point2, Point3};
 
 pub trait Texture {
     fn get_color(&self, sr: &ShadeRec) -> Rgb;
 }
 
+/// A texture of single values that can be evaluated without shading, given only the point of a
+/// surface, as for displacement. Textures of colors give the average of the channels.
+pub trait ScalarTexture {
+    /// Returns the value at the point of a surface with texture coordinates `uv` and the object
+    /// space coordinates `local_hit_point`.
+    fn get_value(&self, uv: Point2, local_hit_point: Point3) -> f64;
+}
+
 pub struct ConstantColor(Rgb);
 
 impl Texture for ConstantColor {
@@ -18,6 +30,12 @@ impl Texture for ConstantColor {
     }
 }
 
+impl ScalarTexture for ConstantColor {
+    fn get_value(&self, _uv: Point2, _local_hit_point: Point3) -> f64 {
+        self.0.average()
+    }
+}
+
 #[derive(Clone)]
 pub struct ImageTexture {
     hres: u32,
@@ -28,48 +46,173 @@ pub struct ImageTexture {
 
 impl Texture for ImageTexture {
     fn get_color(&self, sr: &ShadeRec) -> Rgb {
-        let (u, v) = match &self.mapping {
+        self.color_at_point(sr.uv, sr.local_hit_point)
+    }
+}
+
+impl ScalarTexture for ImageTexture {
+    fn get_value(&self, uv: Point2, local_hit_point: Point3) -> f64 {
+        self.color_at_point(uv, local_hit_point).average()
+    }
+}
+
+impl ImageTexture {
+    /// Loads an image texture, of which the colors are converted to the sRGB working space.
+    pub fn new(path: &str) -> Result<Self, ImageError> {
+        Self::load(path, ColorSpace::Srgb)
+    }
+
+    /// Loads an image texture, of which the colors are converted to `working_space`. The
+    /// encoding of the image is read from the file where possible: Radiance HDR images are
+    /// linear, PNG images may describe their encoding in their chunks and all other images are
+    /// assumed to be sRGB encoded.
+    pub fn load(path: &str, working_space: ColorSpace) -> Result<Self, ImageError> {
+        let (width, height, pixels, encoding) = read_image(path)?;
+        Ok(Self::from_pixels(
+            width,
+            height,
+            pixels,
+            &encoding,
+            working_space,
+        ))
+    }
+
+    /// Loads an image texture of which the colors are encoded with `encoding`, whatever the file
+    /// states, such as textures that don't hold colors, and converts them to `working_space`.
+    pub fn with_encoding(
+        path: &str,
+        encoding: &Encoding,
+        working_space: ColorSpace,
+    ) -> Result<Self, ImageError> {
+        let (width, height, pixels, _) = read_image(path)?;
+        Ok(Self::from_pixels(
+            width,
+            height,
+            pixels,
+            encoding,
+            working_space,
+        ))
+    }
+
+    fn color_at_point(&self, uv: Point2, local_hit_point: Point3) -> Rgb {
+        match &self.mapping {
             Some(mapping) => {
-                mapping.get_texel_coordinates(sr.local_hit_point, self.hres, self.vres)
+                let (u, v) = mapping.get_texel_coordinates(local_hit_point, self.hres, self.vres);
+                self.texel(u, v)
             }
-            None => (
-                (self.hres as f64 * sr.uv.x).round() as u32 % self.hres,
-                (self.vres as f64 * sr.uv.y).round() as u32 % self.vres,
-            ),
-        };
+            None => self.color_at(uv),
+        }
+    }
 
+    /// Returns the color at the texture coordinates `uv`, of which v points up the image.
+    pub(crate) fn color_at(&self, uv: Point2) -> Rgb {
+        self.texel(
+            (self.hres as f64 * uv.x).round() as u32 % self.hres,
+            (self.vres as f64 * uv.y).round() as u32 % self.vres,
+        )
+    }
+
+    fn texel(&self, u: u32, v: u32) -> Rgb {
         let index = u + self.hres * (self.vres - v - 1);
         *self.image.get(index as usize).unwrap()
     }
+
+    /// Creates a texture from `pixels`, row by row from the top, of which the colors are encoded
+    /// with `encoding`, and converts them to `working_space`.
+    pub(crate) fn from_pixels(
+        width: u32,
+        height: u32,
+        pixels: Vec<Rgb>,
+        encoding: &Encoding,
+        working_space: ColorSpace,
+    ) -> Self {
+        let image = pixels
+            .into_iter()
+            .map(|pixel| encoding.decode(pixel, working_space))
+            .collect();
+
+        Self {
+            hres: width,
+            vres: height,
+            image,
+            mapping: None,
+        }
+    }
 }
 
-impl ImageTexture {
-    pub fn new(path: &str) -> Result<Self, ImageError> {
-        const INV_MAX_VALUE: f64 = 1. / 255.;
-
-        let image = ImageReader::open(path)?.decode()?;
-        let pixels = image
-            .pixels()
-            .map(|(_, _, pixel)| {
-                let [red, green, blue] = pixel.to_rgb().0;
-                Rgb::new(
-                    INV_MAX_VALUE * red as f64,
-                    INV_MAX_VALUE * green as f64,
-                    INV_MAX_VALUE * blue as f64,
-                )
-                .pow(2.2)
+/// Reads the image at `path`, returning its width, its height, its pixels row by row from the
+/// top and the encoding the pixels are stored with.
+fn read_image(path: &str) -> Result<(u32, u32, Vec<Rgb>, Encoding), ImageError> {
+    let is_hdr = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("hdr"));
+    if is_hdr {
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let metadata = decoder.metadata();
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .map(|pixel| {
+                let [red, green, blue] = pixel.0;
+                Rgb::new(red as f64, green as f64, blue as f64)
             })
             .collect();
 
-        let texture = Self {
-            hres: image.height(),
-            vres: image.width(),
-            image: pixels,
-            mapping: None,
-        };
+        return Ok((
+            metadata.width,
+            metadata.height,
+            pixels,
+            Encoding::LINEAR_SRGB,
+        ));
+    }
+
+    const INV_MAX_VALUE: f64 = 1. / 65535.;
+
+    let image = ImageReader::open(path)?.decode()?;
+    let pixels = image
+        .to_rgb16()
+        .pixels()
+        .map(|pixel| {
+            let [red, green, blue] = pixel.0;
+            Rgb::new(
+                INV_MAX_VALUE * red as f64,
+                INV_MAX_VALUE * green as f64,
+                INV_MAX_VALUE * blue as f64,
+            )
+        })
+        .collect();
+    let encoding = png_encoding(path).unwrap_or(Encoding::SRGB);
+
+    Ok((image.width(), image.height(), pixels, encoding))
+}
+
+/// Returns the encoding described by the chunks of a PNG file, if any.
+fn png_encoding(path: &str) -> Option<Encoding> {
+    let reader = png::Decoder::new(File::open(path).ok()?).read_info().ok()?;
+    let info = reader.info();
 
-        Ok(texture)
+    // embedded ICC profiles are assumed to describe sRGB
+    if info.srgb.is_some() || info.icc_profile.is_some() {
+        return Some(Encoding::SRGB);
     }
+
+    let exponent = info.source_gamma?.into_value() as f64;
+    let transfer_function = if (exponent - 1.).abs() < 0.01 {
+        TransferFunction::Linear
+    } else if (exponent - 1. / 2.2).abs() < 0.01 {
+        TransferFunction::Srgb
+    } else {
+        TransferFunction::Gamma(1. / exponent)
+    };
+    let color_space = match info.source_chromaticities {
+        Some(chromaticities) if (chromaticities.red.0.into_value() - 0.713).abs() < 0.01 => {
+            ColorSpace::AcesCg
+        }
+        _ => ColorSpace::Srgb,
+    };
+
+    Some(Encoding::new(color_space, transfer_function))
 }
 
 #[derive(Debug, Copy, Clone)]
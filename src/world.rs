@@ -1,20 +1,24 @@
 use crate::accel::bvh::SplittingConfig;
 use crate::film::Rgb;
-use crate::light::{AmbientLight, Light};
+use crate::light::{Ambient, AmbientLight, Light};
 use crate::math::Ray;
+use crate::medium::{BoundedMedium, Medium};
 use crate::shade_rec::ShadeRec;
 use crate::shape::{Compound, GeometricObject, Hit, Intersect};
 use crate::Vector;
 
 pub struct World<'a> {
     geometric_objects: Compound<'a, GeometricObject>,
-    ambient_light: AmbientLight,
+    ambient_light: Box<dyn Ambient + Sync>,
     lights: Vec<Box<dyn Light + Sync>>,
+    // medium filling the space outside the bounded media, if any
+    medium: Option<Box<dyn Medium + Sync>>,
+    bounded_media: Vec<BoundedMedium>,
     background_color: Rgb,
 }
 
 impl<'a> World<'a> {
-    pub fn hit_objects(&self, ray: &Ray) -> Option<ShadeRec> {
+    pub fn hit_objects(&self, ray: &Ray) -> Option<ShadeRec<'_>> {
         self.geometric_objects.intersect(ray).map(|hit| {
             // safety: since shape is in the world, this reference will at least be valid within
             // this function.
@@ -24,7 +28,8 @@ impl<'a> World<'a> {
                 local_hit_point: hit.local_hit_point,
                 uv: hit.uv,
                 normal: hit.normal,
-                shape: hit.shape,
+                shape: Some(hit.shape.0),
+                material: hit.shape.1,
                 direction: Vector::default(),
                 world: self,
             }
@@ -42,7 +47,7 @@ impl<'a> World<'a> {
                 local_hit_point: hit.local_hit_point,
                 // safety: since shape is in the world, this reference will at least be valid within
                 // this function.
-                shape: unsafe { hit.shape.as_ref() },
+                shape: unsafe { hit.shape.0.as_ref() },
                 uv: hit.uv,
             };
 
@@ -58,19 +63,56 @@ impl<'a> World<'a> {
         self.lights.as_slice()
     }
 
-    pub fn ambient_light(&self) -> &AmbientLight {
-        &self.ambient_light
+    pub fn ambient_light(&self) -> &dyn Ambient {
+        self.ambient_light.as_ref()
+    }
+
+    /// Returns the medium filling the space outside the bounded media, if any.
+    pub fn medium(&self) -> Option<&dyn Medium> {
+        self.medium
+            .as_ref()
+            .map(|medium| medium.as_ref() as &dyn Medium)
+    }
+
+    /// Returns the parameter along `ray` of the nearest boundary of a bounded medium, together
+    /// with the medium on the other side of it.
+    pub fn hit_medium_boundary(&self, ray: &Ray) -> Option<(f64, Option<&dyn Medium>)> {
+        self.bounded_media
+            .iter()
+            .filter_map(|bounded| {
+                let hit = bounded.shape.intersect(ray)?;
+                let medium = if hit.normal.dot(ray.direction()) < 0. {
+                    Some(bounded.medium.as_ref() as &dyn Medium)
+                } else {
+                    self.medium()
+                };
+
+                Some((hit.t, medium))
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
     }
 
     pub fn background_color(&self) -> Rgb {
         self.background_color
     }
+
+    /// Returns the radiance arriving along a ray that doesn't hit any object.
+    pub fn background(&self, ray: &Ray) -> Rgb {
+        self.background_color
+            + self
+                .lights
+                .iter()
+                .map(|light| light.background(ray))
+                .sum::<Rgb>()
+    }
 }
 
 pub struct WorldBuilder {
     geometric_objects: Vec<GeometricObject>,
     lights: Vec<Box<dyn Light + Sync>>,
-    ambient_light: Option<AmbientLight>,
+    medium: Option<Box<dyn Medium + Sync>>,
+    bounded_media: Vec<BoundedMedium>,
+    ambient_light: Option<Box<dyn Ambient + Sync>>,
     background_color: Option<Rgb>,
     splitting_splitting_config: Option<SplittingConfig>,
 }
@@ -95,6 +137,24 @@ impl WorldBuilder {
         self
     }
 
+    /// Fills the space outside the bounded media with `medium`.
+    pub fn medium(mut self, medium: Box<dyn Medium + Sync>) -> Self {
+        self.medium = Some(medium);
+        self
+    }
+
+    /// Adds a medium bounded by a closed shape. Bounded media must not overlap, and rays leaving
+    /// one enter the medium of the world.
+    pub fn bounded_medium(mut self, bounded_medium: BoundedMedium) -> Self {
+        self.bounded_media.push(bounded_medium);
+        self
+    }
+
+    pub fn ambient_light(mut self, ambient_light: Box<dyn Ambient + Sync>) -> Self {
+        self.ambient_light = Some(ambient_light);
+        self
+    }
+
     pub fn background(mut self, color: Rgb) -> Self {
         self.background_color = Some(color);
         self
@@ -107,11 +167,11 @@ impl WorldBuilder {
 
     pub fn build(self) -> Option<World<'static>> {
         let mut geometric_objects = self.geometric_objects;
-        geometric_objects.extend(
-            self.lights
-                .iter()
-                .filter_map(|light| light.geometric_object()),
-        );
+        for (i, light) in self.lights.iter().enumerate() {
+            if let Some(geometric_object) = light.geometric_object() {
+                geometric_objects.push(geometric_object.with_emitter(i));
+            }
+        }
         let geometric_objects = Compound::new_with_splitting_heuristic(
             geometric_objects,
             self.splitting_splitting_config.unwrap_or_default(),
@@ -119,13 +179,15 @@ impl WorldBuilder {
         let lights = self.lights;
         let ambient_light = self
             .ambient_light
-            .unwrap_or_else(|| AmbientLight::white(0.25));
+            .unwrap_or_else(|| Box::new(AmbientLight::white(0.25)));
         let background_color = self.background_color.unwrap_or_default();
 
         let world = World {
             geometric_objects,
             ambient_light,
             lights,
+            medium: self.medium,
+            bounded_media: self.bounded_media,
             background_color,
         };
 
@@ -137,6 +199,8 @@ impl Default for WorldBuilder {
     fn default() -> Self {
         let geometric_objects = Vec::new();
         let lights = Vec::new();
+        let medium = None;
+        let bounded_media = Vec::new();
         let ambient_light = None;
         let background_color = None;
         let splitting_heuristic = None;
@@ -144,6 +208,8 @@ impl Default for WorldBuilder {
         Self {
             geometric_objects,
             lights,
+            medium,
+            bounded_media,
             ambient_light,
             background_color,
             splitting_splitting_config: splitting_heuristic,
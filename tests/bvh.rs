@@ -0,0 +1,68 @@
+use ray_tracer::bvh::{AxisSelection, SplittingConfig, SplittingHeuristic};
+use ray_tracer::math::{Ray, Transformation};
+use ray_tracer::shape::{Compound, Intersect, Plane, Rectangle, Shape, Sphere, Transformed};
+use ray_tracer::{Point3, Vector};
+
+fn splitting_configs() -> Vec<SplittingConfig> {
+    let heuristics = [
+        SplittingHeuristic::SpaceMedianSplit,
+        SplittingHeuristic::ObjectMedianSplit,
+        SplittingHeuristic::SurfaceAreaHeuristic(12),
+    ];
+    let axis_selections = [AxisSelection::Alternate(0), AxisSelection::Longest];
+
+    heuristics
+        .iter()
+        .flat_map(|&splitting_heuristic| {
+            axis_selections
+                .iter()
+                .map(move |&axis_selection| SplittingConfig {
+                    splitting_heuristic,
+                    axis_selection,
+                })
+        })
+        .collect()
+}
+
+fn spheres() -> Vec<Box<dyn Shape>> {
+    (0..8)
+        .map(|i| {
+            let transformation = Transformation::translate(3. * i as f64, 1., 0.);
+            Box::new(Transformed::new(Sphere::new(), transformation)) as Box<dyn Shape>
+        })
+        .collect()
+}
+
+#[test]
+fn unbounded_plane_is_hit_inside_a_bvh() {
+    for cfg in splitting_configs() {
+        let mut shapes = spheres();
+        shapes.push(Box::new(Plane::new(Vector::y(), Point3::origin())));
+        let compound = Compound::new_with_splitting_heuristic(shapes, cfg);
+
+        let ray = Ray::new(Point3::new(-50., 10., 50.), -Vector::y());
+        let hit = compound.intersect(&ray).expect("the plane must be hit");
+        assert!((hit.t - 10.).abs() < 1e-9, "{}: t = {}", cfg, hit.t);
+
+        let ray = Ray::new(Point3::new(6., 10., 0.), -Vector::y());
+        let hit = compound.intersect(&ray).expect("the sphere must be hit");
+        assert!((hit.t - 8.).abs() < 1e-9, "{}: t = {}", cfg, hit.t);
+    }
+}
+
+#[test]
+fn rectangle_with_negative_edges_is_hit_inside_a_bvh() {
+    for cfg in splitting_configs() {
+        let mut shapes = spheres();
+        shapes.push(Box::new(Rectangle::new(
+            Point3::new(-10., 5., 10.),
+            Vector::new(-2., 0., 0.),
+            Vector::new(0., 0., -2.),
+        )));
+        let compound = Compound::new_with_splitting_heuristic(shapes, cfg);
+
+        let ray = Ray::new(Point3::new(-11., 10., 9.), -Vector::y());
+        let hit = compound.intersect(&ray).expect("the rectangle must be hit");
+        assert!((hit.t - 5.).abs() < 1e-9, "{}: t = {}", cfg, hit.t);
+    }
+}
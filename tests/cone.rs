@@ -0,0 +1,20 @@
+use ray_tracer::math::Ray;
+use ray_tracer::shape::{Cone, Intersect};
+use ray_tracer::{Point3, Vector};
+
+#[test]
+fn ray_along_the_slope_is_not_a_hit() {
+    let cone = Cone::new(1., 1.);
+    let ray = Ray::new(Point3::new(-1., 0., 2.), Vector::new(1., 0., -1.));
+
+    assert!(cone.intersect(&ray).is_none());
+}
+
+#[test]
+fn ray_parallel_to_the_slope_hits_once() {
+    let cone = Cone::new(1., 1.);
+    let ray = Ray::new(Point3::new(-1.5, 0., 2.), Vector::new(1., 0., -1.));
+    let hit = cone.intersect(&ray).expect("the cone must be hit");
+
+    assert!((hit.t - 1.25).abs() < 1e-9, "t = {}", hit.t);
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::PathBuf;
+
+use ray_tracer::math::Ray;
+use ray_tracer::scene::{GltfError, GltfScene};
+use ray_tracer::shape::Intersect;
+use ray_tracer::{Point3, Vector};
+
+const TRIANGLE: [[f32; 3]; 3] = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+
+/// A glTF asset with a single node, which holds a mesh of a single triangle primitive. The
+/// vertex data is written to a separate buffer.
+struct Fixture {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u16>,
+    // properties added to the node, such as its transformation
+    node: &'static str,
+}
+
+impl Fixture {
+    fn triangle() -> Self {
+        Self {
+            positions: TRIANGLE.to_vec(),
+            normals: Vec::new(),
+            indices: vec![0, 1, 2],
+            node: "",
+        }
+    }
+
+    /// Writes the asset to a temporary directory, under a `name` unique to the test, and
+    /// loads it.
+    fn load(&self, name: &str) -> Result<GltfScene, GltfError> {
+        let mut bytes = Vec::new();
+        let mut views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut attributes = Vec::new();
+
+        for (semantic, data) in &[("POSITION", &self.positions), ("NORMAL", &self.normals)] {
+            if data.is_empty() {
+                continue;
+            }
+            attributes.push(format!("\"{}\": {}", semantic, accessors.len()));
+            views.push(format!(
+                "{{\"buffer\": 0, \"byteOffset\": {}, \"byteLength\": {}}}",
+                bytes.len(),
+                12 * data.len()
+            ));
+            bytes.extend(data.iter().flatten().flat_map(|x| x.to_le_bytes().to_vec()));
+            accessors.push(format!(
+                "{{\"bufferView\": {}, \"componentType\": 5126, \"count\": {}, \"type\": \"VEC3\", \
+                 \"min\": [-10, -10, -10], \"max\": [10, 10, 10]}}",
+                views.len() - 1,
+                data.len()
+            ));
+        }
+        views.push(format!(
+            "{{\"buffer\": 0, \"byteOffset\": {}, \"byteLength\": {}}}",
+            bytes.len(),
+            2 * self.indices.len()
+        ));
+        bytes.extend(self.indices.iter().flat_map(|i| i.to_le_bytes().to_vec()));
+        accessors.push(format!(
+            "{{\"bufferView\": {}, \"componentType\": 5123, \"count\": {}, \"type\": \"SCALAR\"}}",
+            views.len() - 1,
+            self.indices.len()
+        ));
+
+        let json = format!(
+            "{{\"asset\": {{\"version\": \"2.0\"}}, \"scene\": 0, \"scenes\": [{{\"nodes\": [0]}}], \
+             \"nodes\": [{{\"mesh\": 0{}}}], \
+             \"meshes\": [{{\"primitives\": [{{\"attributes\": {{{}}}, \"indices\": {}}}]}}], \
+             \"buffers\": [{{\"byteLength\": {}, \"uri\": \"{}.bin\"}}], \
+             \"bufferViews\": [{}], \"accessors\": [{}]}}",
+            self.node,
+            attributes.join(", "),
+            accessors.len() - 1,
+            bytes.len(),
+            name,
+            views.join(", "),
+            accessors.join(", ")
+        );
+
+        let directory = temporary_directory();
+        fs::write(directory.join(format!("{}.bin", name)), bytes).unwrap();
+        write_and_load(name, &json)
+    }
+}
+
+fn temporary_directory() -> PathBuf {
+    let directory = std::env::temp_dir().join("ray-tracer-gltf-tests");
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn write_and_load(name: &str, json: &str) -> Result<GltfScene, GltfError> {
+    let path = temporary_directory().join(format!("{}.gltf", name));
+    fs::write(&path, json).unwrap();
+
+    GltfScene::load(path)
+}
+
+fn load_error(result: Result<GltfScene, GltfError>) -> GltfError {
+    match result {
+        Ok(_) => panic!("the asset must be rejected"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn triangle_is_imported() {
+    let fixture = Fixture {
+        node: ", \"translation\": [0, 0, -2]",
+        ..Fixture::triangle()
+    };
+    let scene = fixture.load("triangle").unwrap();
+    assert_eq!(scene.geometric_objects.len(), 1);
+
+    let ray = Ray::new(Point3::new(0.25, 0.25, 1.), -Vector::z());
+    let hit = scene.geometric_objects[0]
+        .intersect(&ray)
+        .expect("the triangle must be hit");
+    assert!((hit.t - 3.).abs() < 1e-6, "t = {}", hit.t);
+}
+
+#[test]
+fn invalid_file_is_reported() {
+    let err = load_error(write_and_load("invalid", "{\"asset\": "));
+
+    assert!(matches!(err, GltfError::Gltf(_)), "{}", err);
+}
+
+#[test]
+fn asset_without_scenes_is_reported() {
+    let err = load_error(write_and_load(
+        "no-scene",
+        "{\"asset\": {\"version\": \"2.0\"}}",
+    ));
+
+    assert!(matches!(err, GltfError::MissingScene), "{}", err);
+}
+
+#[test]
+fn singular_transformation_is_reported() {
+    let fixture = Fixture {
+        node: ", \"scale\": [1, 0, 1]",
+        ..Fixture::triangle()
+    };
+    let err = load_error(fixture.load("singular"));
+
+    assert!(
+        matches!(err, GltfError::SingularTransformation(0)),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn attributes_of_different_lengths_are_reported() {
+    let fixture = Fixture {
+        normals: vec![[0., 0., 1.]; 2],
+        ..Fixture::triangle()
+    };
+    let err = load_error(fixture.load("mismatched"));
+
+    assert!(matches!(err, GltfError::MismatchedAttributes(0)), "{}", err);
+}
+
+#[test]
+fn index_out_of_range_is_reported() {
+    let fixture = Fixture {
+        indices: vec![0, 1, 7],
+        ..Fixture::triangle()
+    };
+    let err = load_error(fixture.load("invalid-index"));
+
+    assert!(matches!(err, GltfError::InvalidIndex(0, 7)), "{}", err);
+}
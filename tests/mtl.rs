@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use ray_tracer::film::Rgb;
+use ray_tracer::material::{Emissive, Material};
+use ray_tracer::math::Transformation;
+use ray_tracer::shape::{GeometricObject, MtlError, MtlLibrary, Obj};
+
+const LIBRARY: &str = "
+# a comment
+newmtl glow
+Ke 2 1 0
+
+newmtl glass
+Kd 0.5
+Ni 1.5
+illum 7
+
+newmtl paint
+Kd 0.2 0.4 0.6
+bump -bm 0.5 bump.png
+";
+
+fn parse_error(input: &str) -> MtlError {
+    match MtlLibrary::parse(input, Path::new("")) {
+        Ok(_) => panic!("the input must be rejected"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn materials_are_parsed_and_mapped() {
+    let library = MtlLibrary::parse(LIBRARY, Path::new("textures")).unwrap();
+
+    let glow = library.get("glow").unwrap();
+    assert_eq!(glow.emission, Rgb::new(2., 1., 0.));
+    assert!(matches!(glow.to_material(), Material::Emissive(_)));
+
+    let glass = library.get("glass").unwrap();
+    assert_eq!(glass.diffuse, Rgb::new(0.5, 0.5, 0.5));
+    assert_eq!(glass.ior, 1.5);
+    assert!(matches!(glass.to_material(), Material::Glass(_)));
+
+    let paint = library.get("paint").unwrap();
+    assert_eq!(paint.diffuse, Rgb::new(0.2, 0.4, 0.6));
+    assert_eq!(
+        paint.bump_map,
+        Some(Path::new("textures/bump.png").to_owned())
+    );
+    assert!(matches!(paint.to_material(), Material::Matte { .. }));
+
+    assert!(library.get("missing").is_none());
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let err = match MtlLibrary::load("tests/does-not-exist.mtl") {
+        Ok(_) => panic!("the file must not exist"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(err, MtlError::Io(_)), "{}", err);
+}
+
+#[test]
+fn missing_texture_is_reported() {
+    let err = parse_error("newmtl a\nmap_Kd tests/does-not-exist.png");
+
+    assert!(matches!(err, MtlError::Texture(_)), "{}", err);
+}
+
+#[test]
+fn statement_before_newmtl_is_reported() {
+    let err = parse_error("\nKd 1 1 1\nnewmtl a");
+
+    assert!(matches!(err, MtlError::MissingNewmtl(2)), "{}", err);
+}
+
+#[test]
+fn missing_value_is_reported() {
+    let err = parse_error("newmtl a\nKd");
+
+    assert!(matches!(err, MtlError::MissingValue(2)), "{}", err);
+}
+
+#[test]
+fn invalid_number_is_reported() {
+    let err = parse_error("newmtl a\nKd 1 x 1");
+
+    assert!(
+        matches!(err, MtlError::InvalidNumber(2, ref token) if token == "x"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn texture_path_that_is_not_unicode_is_reported() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let directory = Path::new(OsStr::from_bytes(b"\xff"));
+    let err = match MtlLibrary::parse("newmtl a\nmap_Kd a.png", directory) {
+        Ok(_) => panic!("the path must be rejected"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(err, MtlError::InvalidPath(_)), "{}", err);
+}
+
+#[test]
+fn material_missing_from_the_libraries_is_reported() {
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl missing\nf 1 2 3"
+        .parse::<Obj>()
+        .unwrap();
+    let default_material = Material::Emissive(Emissive::new(1., Rgb::white()));
+
+    let err = match GeometricObject::triangle_mesh_with_materials(
+        obj,
+        Transformation::identity(),
+        default_material,
+    ) {
+        Ok(_) => panic!("the material must be unknown"),
+        Err(err) => err,
+    };
+
+    assert!(
+        matches!(err, MtlError::UnknownMaterial(ref name) if name == "missing"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,130 @@
+use ray_tracer::film::Rgb;
+use ray_tracer::math::Ray;
+use ray_tracer::shape::{Intersect, Obj, ObjError};
+use ray_tracer::{Point3, Vector};
+
+const QUAD: &str = "
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1
+vn 0 0 1
+usemtl first
+f -4/-2 -3/-1 -2/-1/-1 -1//-1
+usemtl second
+f 1 2 3
+usemtl first
+f 1 3 4
+";
+
+fn parse_error(input: &str) -> ObjError {
+    match input.parse::<Obj>() {
+        Ok(_) => panic!("the input must be rejected"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn polygons_with_relative_indices_are_split_into_fans() {
+    let obj = QUAD.parse::<Obj>().unwrap();
+    let normals = obj.face_normals();
+    assert_eq!(normals.len(), 4);
+    assert!(normals.iter().all(|n| (n - Vector::z()).norm() < 1e-9));
+
+    let mesh = obj.flat();
+    for &(x, y) in &[(0.75, 0.25), (0.25, 0.75)] {
+        let ray = Ray::new(Point3::new(x, y, 1.), -Vector::z());
+        let hit = mesh.intersect(&ray).expect("the quad must be hit");
+        assert!((hit.t - 1.).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn material_names_are_listed_once_in_order() {
+    let (_, names) = QUAD.parse::<Obj>().unwrap().smooth_with_materials();
+
+    assert_eq!(names, vec!["first".to_owned(), "second".to_owned()]);
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let err = match Obj::load("tests/does-not-exist.obj") {
+        Ok(_) => panic!("the file must not exist"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(err, ObjError::Io(_)), "{}", err);
+}
+
+#[test]
+fn missing_coordinate_is_reported() {
+    let err = parse_error("v 0 0 0\nv 1 2");
+
+    assert!(matches!(err, ObjError::MissingValue(2)), "{}", err);
+}
+
+#[test]
+fn invalid_coordinates_are_reported() {
+    let err = parse_error("v 0 x 0");
+    assert!(
+        matches!(err, ObjError::InvalidNumber(1, ref token) if token == "x"),
+        "{}",
+        err
+    );
+
+    // the v texture coordinate is optional, but not when it is invalid
+    let err = parse_error("vt 0.5 abc");
+    assert!(
+        matches!(err, ObjError::InvalidNumber(1, ref token) if token == "abc"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn invalid_indices_are_reported() {
+    let vertexes = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    for &index in &["0", "4", "-4", "a"] {
+        let err = parse_error(&format!("{}f 1 2 {}", vertexes, index));
+        assert!(
+            matches!(err, ObjError::InvalidIndex(4, ref token) if token == index),
+            "{}",
+            err
+        );
+    }
+
+    let err = parse_error(&format!("{}vt 0 0\nf 1/1 2/2 3/1", vertexes));
+    assert!(
+        matches!(err, ObjError::InvalidIndex(5, ref token) if token == "2"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn faces_with_fewer_than_three_corners_are_reported() {
+    let err = parse_error("v 0 0 0\nv 1 0 0\nf 1 2");
+
+    assert!(matches!(err, ObjError::TooFewCorners(3)), "{}", err);
+}
+
+#[test]
+fn welding_without_tolerance_merges_signed_zeros() {
+    let vertexes = vec![
+        Point3::new(0., 0., 0.),
+        Point3::new(1., 0., 0.),
+        Point3::new(0., 1., 0.),
+        Point3::new(-0., 0., -0.),
+        Point3::new(1., 0., 0.),
+        Point3::new(0., -1., 0.),
+    ];
+    let obj = Obj::new(vertexes, Vec::new(), Vec::new(), &[[0, 1, 2], [3, 5, 4]])
+        .with_vertex_colors(vec![Rgb::white(); 6])
+        .weld(0.);
+
+    // the colors of the merged vertexes are dropped along with them
+    assert_eq!(obj.vertex_colors().len(), 4);
+}
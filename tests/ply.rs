@@ -0,0 +1,175 @@
+use ray_tracer::film::Rgb;
+use ray_tracer::math::Ray;
+use ray_tracer::shape::{Intersect, Obj, PlyError};
+use ray_tracer::{Point3, Vector};
+
+const QUAD: &str = "ply
+format ascii 1.0
+comment a unit square, with a color per vertex
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+/// The header of a triangle in binary format, followed by the triangle itself.
+fn binary_triangle(format: &str, big_endian: bool) -> Vec<u8> {
+    let mut bytes = format!(
+        "ply\nformat {} 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+         property float z\nelement face 1\nproperty list uchar uint vertex_indices\nend_header\n",
+        format
+    )
+    .into_bytes();
+
+    let to_bytes = |x: f32| {
+        if big_endian {
+            x.to_be_bytes()
+        } else {
+            x.to_le_bytes()
+        }
+    };
+    for &coordinate in &[0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+        bytes.extend_from_slice(&to_bytes(coordinate));
+    }
+    bytes.push(3);
+    for &index in &[0u32, 1, 2] {
+        let index = if big_endian {
+            index.to_be_bytes()
+        } else {
+            index.to_le_bytes()
+        };
+        bytes.extend_from_slice(&index);
+    }
+
+    bytes
+}
+
+fn read_error(input: &[u8]) -> PlyError {
+    match Obj::read_ply(input) {
+        Ok(_) => panic!("the input must be rejected"),
+        Err(err) => err,
+    }
+}
+
+fn assert_unit_triangle_is_hit(obj: Obj, x: f64, y: f64) {
+    let mesh = obj.flat();
+    let ray = Ray::new(Point3::new(x, y, 1.), -Vector::z());
+    let hit = mesh.intersect(&ray).expect("the mesh must be hit");
+
+    assert!((hit.t - 1.).abs() < 1e-9, "t = {}", hit.t);
+}
+
+#[test]
+fn ascii_polygons_are_split_into_fans() {
+    let obj = Obj::read_ply(QUAD.as_bytes()).unwrap();
+    assert_eq!(obj.face_normals().len(), 2);
+    assert_eq!(obj.vertex_colors()[0], Rgb::new(1., 0., 0.));
+    assert_eq!(obj.vertex_colors()[3], Rgb::new(1., 1., 1.));
+
+    assert_unit_triangle_is_hit(obj, 0.25, 0.75);
+}
+
+#[test]
+fn binary_files_of_either_endianness_are_read() {
+    for &(format, big_endian) in &[("binary_little_endian", false), ("binary_big_endian", true)] {
+        let obj = Obj::read_ply(binary_triangle(format, big_endian).as_slice()).unwrap();
+        assert_eq!(obj.face_normals().len(), 1);
+
+        assert_unit_triangle_is_hit(obj, 0.25, 0.25);
+    }
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let err = match Obj::load_ply("tests/does-not-exist.ply") {
+        Ok(_) => panic!("the file must not exist"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(err, PlyError::Io(_)), "{}", err);
+}
+
+#[test]
+fn invalid_headers_are_reported() {
+    let headers = [
+        "plx\nformat ascii 1.0\nend_header\n",
+        "ply\nend_header\n",
+        "ply\nformat ascii 1.0\nproperty float x\nend_header\n",
+        "ply\nformat ascii 1.0\nunknown\nend_header\n",
+    ];
+
+    for header in &headers {
+        let err = read_error(header.as_bytes());
+        assert!(matches!(err, PlyError::InvalidHeader(_)), "{}", err);
+    }
+}
+
+#[test]
+fn unsupported_type_is_reported() {
+    let err =
+        read_error(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty int64 x\nend_header\n");
+
+    assert!(
+        matches!(err, PlyError::UnsupportedType(ref kind) if kind == "int64"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn vertex_without_position_is_reported() {
+    let err = read_error(
+        b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\n\
+          end_header\n0 0\n",
+    );
+
+    assert!(matches!(err, PlyError::MissingProperty(_)), "{}", err);
+}
+
+#[test]
+fn invalid_numbers_are_reported() {
+    let err = read_error(b"ply\nformat ascii 1.0\nelement vertex x\nend_header\n");
+    assert!(
+        matches!(err, PlyError::InvalidNumber(ref token) if token == "x"),
+        "{}",
+        err
+    );
+
+    let err = read_error(QUAD.replace("1 1 0 0 0 255", "1 y 0 0 0 255").as_bytes());
+    assert!(
+        matches!(err, PlyError::InvalidNumber(ref token) if token == "y"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn index_out_of_range_is_reported() {
+    let err = read_error(QUAD.replace("4 0 1 2 3", "4 0 1 2 4").as_bytes());
+
+    assert!(matches!(err, PlyError::InvalidIndex(4)), "{}", err);
+}
+
+#[test]
+fn truncated_files_are_reported() {
+    let err = read_error(b"ply\nformat ascii 1.0\n");
+    assert!(matches!(err, PlyError::UnexpectedEnd), "{}", err);
+
+    let err = read_error(QUAD.replace("4 0 1 2 3", "4 0 1 2").as_bytes());
+    assert!(matches!(err, PlyError::UnexpectedEnd), "{}", err);
+
+    let bytes = binary_triangle("binary_little_endian", false);
+    let err = read_error(&bytes[..bytes.len() - 1]);
+    assert!(matches!(err, PlyError::UnexpectedEnd), "{}", err);
+}
@@ -0,0 +1,108 @@
+use ray_tracer::math::Ray;
+use ray_tracer::shape::{Intersect, Obj, StlError};
+use ray_tracer::{Point3, Vector};
+
+const SQUARE: &str = "solid square
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 0 0
+    vertex 1 1 0
+  endloop
+endfacet
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 1 0
+    vertex 0 1 0
+  endloop
+endfacet
+endsolid square
+";
+
+fn parse_error(input: &[u8]) -> StlError {
+    match Obj::parse_stl(input) {
+        Ok(_) => panic!("the input must be rejected"),
+        Err(err) => err,
+    }
+}
+
+fn assert_unit_square_is_hit(obj: Obj, x: f64, y: f64) {
+    let mesh = obj.flat();
+    let ray = Ray::new(Point3::new(x, y, 1.), -Vector::z());
+    let hit = mesh.intersect(&ray).expect("the mesh must be hit");
+
+    assert!((hit.t - 1.).abs() < 1e-9, "t = {}", hit.t);
+}
+
+#[test]
+fn ascii_facets_are_read() {
+    let obj = Obj::parse_stl(SQUARE.as_bytes()).unwrap();
+    let normals = obj.face_normals();
+    assert_eq!(normals.len(), 2);
+    assert!(normals.iter().all(|n| (n - Vector::z()).norm() < 1e-9));
+
+    assert_unit_square_is_hit(obj, 0.25, 0.75);
+}
+
+#[test]
+fn binary_facets_are_read() {
+    // binary files may start with "solid" as well
+    let mut bytes = b"solid".to_vec();
+    bytes.resize(80, 0);
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    for &coordinate in &[0., 0., 1., 0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+        bytes.extend_from_slice(&(coordinate as f32).to_le_bytes());
+    }
+    bytes.extend_from_slice(&[0, 0]);
+
+    let obj = Obj::parse_stl(&bytes).unwrap();
+    assert_eq!(obj.face_normals().len(), 1);
+
+    assert_unit_square_is_hit(obj, 0.25, 0.25);
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let err = match Obj::load_stl("tests/does-not-exist.stl") {
+        Ok(_) => panic!("the file must not exist"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(err, StlError::Io(_)), "{}", err);
+}
+
+#[test]
+fn text_that_is_not_utf8_is_reported() {
+    let err = parse_error(b"solid \xff\nendsolid\n");
+
+    assert!(matches!(err, StlError::InvalidText), "{}", err);
+}
+
+#[test]
+fn missing_coordinate_is_reported() {
+    let err = parse_error(SQUARE.replace("vertex 1 0 0", "vertex 1 0").as_bytes());
+
+    assert!(matches!(err, StlError::MissingValue(5)), "{}", err);
+}
+
+#[test]
+fn invalid_coordinate_is_reported() {
+    let err = parse_error(SQUARE.replace("vertex 1 0 0", "vertex 1 z 0").as_bytes());
+
+    assert!(
+        matches!(err, StlError::InvalidNumber(5, ref token) if token == "z"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn facets_without_three_vertexes_are_reported() {
+    let err = parse_error(SQUARE.replace("    vertex 0 1 0\n", "").as_bytes());
+    assert!(matches!(err, StlError::InvalidFacet(14)), "{}", err);
+
+    // a facet that isn't closed is reported on the last line
+    let err = parse_error(b"solid\nfacet\nouter loop\nvertex 0 0 0\n");
+    assert!(matches!(err, StlError::InvalidFacet(4)), "{}", err);
+}
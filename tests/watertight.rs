@@ -0,0 +1,217 @@
+use std::f64::consts::PI;
+
+use ray_tracer::math::Ray;
+use ray_tracer::shape::{Intersect, Obj, Shape};
+use ray_tracer::{Point3, Vector};
+
+type Mesh = (Vec<Point3>, Vec<[usize; 3]>);
+
+fn octahedron_mesh() -> Mesh {
+    let vertexes = vec![
+        Point3::new(1., 0., 0.),
+        Point3::new(-1., 0., 0.),
+        Point3::new(0., 1., 0.),
+        Point3::new(0., -1., 0.),
+        Point3::new(0., 0., 1.),
+        Point3::new(0., 0., -1.),
+    ];
+    let triangles = vec![
+        [0, 2, 4],
+        [2, 1, 4],
+        [1, 3, 4],
+        [3, 0, 4],
+        [2, 0, 5],
+        [1, 2, 5],
+        [3, 1, 5],
+        [0, 3, 5],
+    ];
+
+    (vertexes, triangles)
+}
+
+fn cube_mesh() -> Mesh {
+    let vertexes = (0..8)
+        .map(|i| {
+            let coordinate = |bit: usize| if i & bit == 0 { -1. } else { 1. };
+            Point3::new(coordinate(1), coordinate(2), coordinate(4))
+        })
+        .collect();
+    let triangles = vec![
+        [0, 2, 3],
+        [0, 3, 1],
+        [4, 5, 7],
+        [4, 7, 6],
+        [0, 1, 5],
+        [0, 5, 4],
+        [2, 6, 7],
+        [2, 7, 3],
+        [0, 4, 6],
+        [0, 6, 2],
+        [1, 3, 7],
+        [1, 7, 5],
+    ];
+
+    (vertexes, triangles)
+}
+
+/// A sphere of `rings` by `segments` quads, with triangle fans around the poles.
+fn sphere_mesh(rings: usize, segments: usize) -> Mesh {
+    let mut vertexes = vec![Point3::new(0., 0., 1.)];
+    for i in 1..rings {
+        for j in 0..segments {
+            let theta = PI * i as f64 / rings as f64;
+            let phi = 2. * PI * j as f64 / segments as f64;
+            vertexes.push(Point3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ));
+        }
+    }
+    vertexes.push(Point3::new(0., 0., -1.));
+
+    let south = vertexes.len() - 1;
+    let vertex = |i: usize, j: usize| 1 + (i - 1) * segments + j % segments;
+    let mut triangles = Vec::new();
+    for j in 0..segments {
+        triangles.push([0, vertex(1, j), vertex(1, j + 1)]);
+        for i in 1..rings - 1 {
+            let (a, b) = (vertex(i, j), vertex(i, j + 1));
+            let (c, d) = (vertex(i + 1, j), vertex(i + 1, j + 1));
+            triangles.push([a, c, b]);
+            triangles.push([b, c, d]);
+        }
+        triangles.push([vertex(rings - 1, j), south, vertex(rings - 1, j + 1)]);
+    }
+
+    (vertexes, triangles)
+}
+
+/// Returns the vertexes of `mesh` and points along its edges.
+fn targets((vertexes, triangles): &Mesh) -> Vec<Point3> {
+    let mut targets = vertexes.clone();
+    for &[a, b, c] in triangles {
+        for &(from, to) in &[(a, b), (b, c), (c, a)] {
+            for &s in &[0.5, 1. / 3., 0.123_456_789] {
+                targets.push(vertexes[from] + s * (vertexes[to] - vertexes[from]));
+            }
+        }
+    }
+
+    targets
+}
+
+fn obj((vertexes, triangles): &Mesh) -> Obj {
+    Obj::new(vertexes.clone(), Vec::new(), Vec::new(), triangles)
+}
+
+/// Asserts that all rays from `origins` inside `mesh` through its edges and vertexes hit it, at a
+/// point no further than the targeted point.
+fn assert_watertight_from_inside(shape: &dyn Shape, mesh: &Mesh, origins: &[Point3]) {
+    for target in targets(mesh) {
+        for origin in origins {
+            let direction = target - origin;
+            let ray = Ray::new(*origin, direction.normalize());
+            assert_hit(shape, &ray, direction.norm());
+        }
+    }
+}
+
+/// Asserts that all rays entering `mesh` through its edges and vertexes, towards `interior`
+/// points, hit it no further than where they enter.
+fn assert_watertight_from_outside(shape: &dyn Shape, mesh: &Mesh, interior: &[Point3]) {
+    for target in targets(mesh) {
+        for point in interior {
+            let direction = point - target;
+            let origin = target - 3. * direction;
+            let ray = Ray::new(origin, direction.normalize());
+            assert_hit(shape, &ray, 3. * direction.norm());
+        }
+    }
+}
+
+fn assert_hit(shape: &dyn Shape, ray: &Ray, distance: f64) {
+    let hit = shape.intersect(ray);
+    assert!(
+        hit.is_some(),
+        "ray from {} along {} leaked",
+        ray.origin(),
+        ray.direction()
+    );
+
+    let hit = hit.unwrap();
+    assert!(hit.t.is_finite() && hit.normal.iter().all(|n| n.is_finite()));
+    // compact meshes round their vertexes to single precision
+    assert!(hit.t <= distance * (1. + 1e-6));
+}
+
+fn assert_all_watertight(mesh: &Mesh) {
+    let interior = [
+        Point3::origin(),
+        Point3::new(0.1, -0.2, 0.05),
+        Point3::new(-0.3, 0.25, -0.15),
+    ];
+
+    let shapes: [Box<dyn Shape>; 3] = [
+        Box::new(obj(mesh).smooth()),
+        Box::new(obj(mesh).flat()),
+        Box::new(obj(mesh).compact_smooth()),
+    ];
+
+    for check in &[
+        assert_watertight_from_inside,
+        assert_watertight_from_outside,
+    ] {
+        for shape in &shapes {
+            check(shape.as_ref(), mesh, &interior);
+        }
+    }
+}
+
+#[test]
+fn octahedron() {
+    assert_all_watertight(&octahedron_mesh());
+}
+
+#[test]
+fn cube() {
+    assert_all_watertight(&cube_mesh());
+}
+
+#[test]
+fn sphere() {
+    assert_all_watertight(&sphere_mesh(12, 17));
+}
+
+#[test]
+fn axis_aligned_rays_through_shared_edges() {
+    let mesh = cube_mesh();
+    let shape = obj(&mesh).flat();
+
+    // rays along the axes through the diagonals splitting the faces of the cube
+    for &s in &[-0.75, -0.5, 0., 0.25, 0.9] {
+        for direction in &[Vector::x(), Vector::y(), Vector::z()] {
+            let origin = Point3::new(s, s, s) - 5. * direction;
+            let ray = Ray::new(origin, *direction);
+            let hit = shape
+                .intersect(&ray)
+                .expect("ray leaked through a diagonal");
+            assert!((hit.t - (4. - s)).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn degenerate_triangles_are_missed() {
+    let vertexes = vec![
+        Point3::new(0., 0., 0.),
+        Point3::new(1., 0., 0.),
+        Point3::new(2., 0., 0.),
+    ];
+    let shape = Obj::new(vertexes, Vec::new(), Vec::new(), &[[0, 1, 2], [0, 0, 1]]).flat();
+
+    let ray = Ray::new(Point3::new(0.5, 0., 1.), -Vector::z());
+    assert!(shape.intersect(&ray).is_none());
+    let ray = Ray::new(Point3::new(-1., 0., 0.), Vector::x());
+    assert!(shape.intersect(&ray).is_none());
+}